@@ -0,0 +1,511 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::export::Track;
+
+/// Where an activity came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Origin {
+    Strava,
+    File(PathBuf),
+    /// A Strava "download your data" export ZIP
+    Archive(PathBuf),
+}
+
+/// A one-kilometer split
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Split {
+    /// Distance covered in meters (the last split may be shorter than 1 km)
+    pub distance: f64,
+    pub moving_time: i32,
+    /// Elevation change in meters
+    pub elevation_difference: Option<f64>,
+    pub average_heartrate: Option<f64>,
+}
+
+/// The activity model every tool works with, whether the data came from Strava or a local file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Activity {
+    /// Strava activity ID (None for files that never synced)
+    pub id: Option<i64>,
+    pub name: String,
+    pub sport_type: String,
+    pub start_date: DateTime<Utc>,
+    pub start_date_local: NaiveDateTime,
+    /// Distance in meters
+    pub distance: f64,
+    pub moving_time: i32,
+    pub elapsed_time: i32,
+    /// Elevation gain in meters
+    pub total_elevation_gain: f64,
+    /// Average speed in meters per second
+    pub average_speed: Option<f64>,
+    pub average_heartrate: Option<f64>,
+    pub max_heartrate: Option<f64>,
+    pub gear_id: Option<String>,
+    #[serde(default)]
+    pub splits: Vec<Split>,
+    pub origin: Origin,
+}
+
+impl Activity {
+    /// Check if this activity is a run
+    pub fn is_run(&self) -> bool {
+        is_run_type(&self.sport_type)
+    }
+
+    /// Local calendar date the activity started on
+    pub fn date(&self) -> NaiveDate {
+        self.start_date_local.date()
+    }
+
+    /// Whether two records describe the same workout (e.g. a file that was also synced to Strava)
+    pub fn is_same_workout(&self, other: &Activity) -> bool {
+        const START_TOLERANCE_SECONDS: i64 = 60;
+        (self.start_date - other.start_date).num_seconds().abs() <= START_TOLERANCE_SECONDS
+    }
+
+    /// Build an activity from a parsed file's samples, with local time in the athlete's `timezone`
+    pub fn from_track(track: &Track, path: PathBuf, timezone: Tz) -> Self {
+        let summary = TrackSummary::from_track(track);
+
+        let average_speed = if summary.moving_time > 0 && summary.distance > 0.0 {
+            Some(summary.distance / summary.moving_time as f64)
+        } else {
+            None
+        };
+
+        Self {
+            id: None,
+            name: track.name.clone(),
+            sport_type: track.sport_type.clone(),
+            start_date: track.start,
+            // Files record UTC only, so local time comes from the profile's timezone
+            start_date_local: track.start.with_timezone(&timezone).naive_local(),
+            distance: summary.distance,
+            moving_time: summary.moving_time,
+            elapsed_time: summary.elapsed_time,
+            total_elevation_gain: summary.elevation_gain,
+            average_speed,
+            average_heartrate: summary.average_heartrate,
+            max_heartrate: summary.max_heartrate,
+            gear_id: None,
+            splits: summary.splits,
+            origin: Origin::File(path),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Activity {
+    /// A Strava run starting at `start` (UTC), for tests; other fields are set with struct update syntax
+    pub fn test_run(start: NaiveDateTime, distance: f64, moving_time: i32) -> Self {
+        Self {
+            id: Some(1),
+            name: "Run".to_string(),
+            sport_type: "Run".to_string(),
+            start_date: start.and_utc(),
+            start_date_local: start,
+            distance,
+            moving_time,
+            elapsed_time: moving_time,
+            total_elevation_gain: 0.0,
+            average_speed: (moving_time > 0).then(|| distance / moving_time as f64),
+            average_heartrate: None,
+            max_heartrate: None,
+            gear_id: None,
+            splits: Vec::new(),
+            origin: Origin::Strava,
+        }
+    }
+}
+
+impl TryFrom<&strava_api::SummaryActivity> for Activity {
+    type Error = String;
+
+    fn try_from(activity: &strava_api::SummaryActivity) -> Result<Self, Self::Error> {
+        let (start_date, start_date_local) = parse_start_dates(&activity.start_date, &activity.start_date_local)?;
+
+        // Older activities only carry the legacy type field
+        let sport_type = if activity.sport_type.is_empty() {
+            activity.activity_type.clone()
+        } else {
+            activity.sport_type.clone()
+        };
+
+        Ok(Self {
+            id: Some(activity.id),
+            name: activity.name.clone(),
+            sport_type,
+            start_date,
+            start_date_local,
+            distance: activity.distance,
+            moving_time: activity.moving_time,
+            elapsed_time: activity.elapsed_time,
+            total_elevation_gain: activity.total_elevation_gain,
+            average_speed: activity.average_speed,
+            average_heartrate: activity.average_heartrate,
+            max_heartrate: activity.max_heartrate,
+            gear_id: activity.gear_id.clone(),
+            splits: Vec::new(),
+            origin: Origin::Strava,
+        })
+    }
+}
+
+impl TryFrom<&strava_api::DetailedActivity> for Activity {
+    type Error = String;
+
+    fn try_from(activity: &strava_api::DetailedActivity) -> Result<Self, Self::Error> {
+        let (start_date, start_date_local) = parse_start_dates(&activity.start_date, &activity.start_date_local)?;
+
+        // Older activities only carry the legacy type field
+        let sport_type = if activity.sport_type.is_empty() {
+            activity.activity_type.clone()
+        } else {
+            activity.sport_type.clone()
+        };
+
+        let splits = activity
+            .splits_metric
+            .iter()
+            .flatten()
+            .map(|split| Split {
+                distance: split.distance,
+                moving_time: split.moving_time,
+                elevation_difference: split.elevation_difference,
+                average_heartrate: split.average_heartrate,
+            })
+            .collect();
+
+        Ok(Self {
+            id: Some(activity.id),
+            name: activity.name.clone(),
+            sport_type,
+            start_date,
+            start_date_local,
+            distance: activity.distance,
+            moving_time: activity.moving_time,
+            elapsed_time: activity.elapsed_time,
+            total_elevation_gain: activity.total_elevation_gain,
+            average_speed: activity.average_speed,
+            average_heartrate: activity.average_heartrate,
+            max_heartrate: activity.max_heartrate,
+            gear_id: activity.gear_id.clone(),
+            splits,
+            origin: Origin::Strava,
+        })
+    }
+}
+
+/// Parse Strava's UTC and local start timestamps
+fn parse_start_dates(start_date: &str, start_date_local: &str) -> Result<(DateTime<Utc>, NaiveDateTime), String> {
+    let utc = DateTime::parse_from_rfc3339(start_date)
+        .map_err(|e| format!("Invalid start_date '{}': {}", start_date, e))?
+        .with_timezone(&Utc);
+    // Strava reports local time with a misleading 'Z' suffix, so drop the offset
+    let local = DateTime::parse_from_rfc3339(start_date_local)
+        .map_err(|e| format!("Invalid start_date_local '{}': {}", start_date_local, e))?
+        .naive_local();
+    Ok((utc, local))
+}
+
+/// Totals derived from raw samples
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TrackSummary {
+    distance: f64,
+    moving_time: i32,
+    elapsed_time: i32,
+    elevation_gain: f64,
+    average_heartrate: Option<f64>,
+    max_heartrate: Option<f64>,
+    splits: Vec<Split>,
+}
+
+/// A sample with a timestamp, at its cumulative distance
+struct TimedSample {
+    time: DateTime<Utc>,
+    distance: f64,
+    heartrate: Option<u32>,
+    elevation: Option<f64>,
+}
+
+impl TrackSummary {
+    /// Below this speed (m/s) an interval counts as stopped
+    const MOVING_SPEED_THRESHOLD: f64 = 0.5;
+    /// Elevation changes smaller than this (m) are treated as GPS/barometer noise
+    const ELEVATION_NOISE: f64 = 2.0;
+
+    fn from_track(track: &Track) -> Self {
+        let distances = cumulative_distances(track);
+
+        let mut summary = TrackSummary {
+            distance: distances.last().copied().unwrap_or(0.0),
+            ..Default::default()
+        };
+
+        // Elapsed and moving time from consecutive timestamped samples, with their HR and elevation
+        let timed: Vec<TimedSample> = track
+            .points
+            .iter()
+            .zip(&distances)
+            .filter_map(|(p, &distance)| {
+                p.time.map(|time| TimedSample { time, distance, heartrate: p.heartrate, elevation: p.elevation })
+            })
+            .collect();
+        if let (Some(first), Some(last)) = (timed.first(), timed.last()) {
+            summary.elapsed_time = (last.time - first.time).num_seconds() as i32;
+        }
+
+        let mut split_start = (0.0, 0i32, timed.first().and_then(|sample| sample.elevation));
+        let mut split_heartrates: Vec<f64> = Vec::new();
+        let mut moving_time = 0i64;
+
+        for window in timed.windows(2) {
+            let dt = (window[1].time - window[0].time).num_seconds();
+            let dd = window[1].distance - window[0].distance;
+            if dt > 0 && dd / dt as f64 >= Self::MOVING_SPEED_THRESHOLD {
+                moving_time += dt;
+            }
+
+            if let Some(hr) = window[1].heartrate {
+                split_heartrates.push(hr as f64);
+            }
+
+            // Close a split each time another full kilometer is reached
+            if window[1].distance - split_start.0 >= 1000.0 {
+                let elevation = window[1].elevation;
+                summary.splits.push(Split {
+                    distance: window[1].distance - split_start.0,
+                    moving_time: moving_time as i32 - split_start.1,
+                    elevation_difference: elevation.zip(split_start.2).map(|(end, start)| end - start),
+                    average_heartrate: mean(&split_heartrates),
+                });
+                split_start = (window[1].distance, moving_time as i32, elevation);
+                split_heartrates.clear();
+            }
+        }
+        summary.moving_time = moving_time as i32;
+
+        // Trailing partial split
+        if summary.distance - split_start.0 >= 100.0 && !summary.splits.is_empty() {
+            let elevation = track.points.iter().rev().find_map(|p| p.elevation);
+            summary.splits.push(Split {
+                distance: summary.distance - split_start.0,
+                moving_time: summary.moving_time - split_start.1,
+                elevation_difference: elevation.zip(split_start.2).map(|(end, start)| end - start),
+                average_heartrate: mean(&split_heartrates),
+            });
+        }
+
+        // Elevation gain with a noise band: only count climbs once they exceed the threshold
+        let mut reference: Option<f64> = None;
+        for elevation in track.points.iter().filter_map(|p| p.elevation) {
+            match reference {
+                None => reference = Some(elevation),
+                Some(r) if elevation - r >= Self::ELEVATION_NOISE => {
+                    summary.elevation_gain += elevation - r;
+                    reference = Some(elevation);
+                }
+                Some(r) if r - elevation >= Self::ELEVATION_NOISE => reference = Some(elevation),
+                _ => {}
+            }
+        }
+
+        let heartrates: Vec<f64> = track.points.iter().filter_map(|p| p.heartrate).map(|hr| hr as f64).collect();
+        summary.average_heartrate = mean(&heartrates);
+        summary.max_heartrate = heartrates.iter().copied().reduce(f64::max);
+
+        summary
+    }
+}
+
+/// Cumulative distance per point, from the recorded distance or else from GPS positions
+fn cumulative_distances(track: &Track) -> Vec<f64> {
+    let mut distances = Vec::with_capacity(track.points.len());
+    let mut total = 0.0;
+    let mut last_position: Option<(f64, f64)> = None;
+
+    for point in &track.points {
+        if let Some(recorded) = point.distance {
+            total = recorded;
+        } else if let Some(position) = point.position {
+            if let Some(previous) = last_position {
+                total += haversine_distance(previous, position);
+            }
+        }
+        if point.position.is_some() {
+            last_position = point.position;
+        }
+        distances.push(total);
+    }
+
+    distances
+}
+
+/// Whether a Strava sport type is a run (road or trail)
+pub fn is_run_type(sport_type: &str) -> bool {
+    matches!(sport_type, "Run" | "TrailRun")
+}
+
+/// Great-circle distance in meters between two (lat, lon) points in degrees
+pub fn haversine_distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+    let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (b.1 - a.1).to_radians();
+
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().asin()
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::TrackPoint;
+    use chrono::{Duration, TimeZone};
+
+    /// A steady 5:00/km run sampled every 10 seconds
+    fn steady_run(kilometers: usize) -> Track {
+        let start = Utc.with_ymd_and_hms(2026, 2, 3, 6, 0, 0).unwrap();
+        let samples = kilometers * 30;
+        Track {
+            name: "Steady".to_string(),
+            sport_type: "Run".to_string(),
+            start,
+            points: (0..=samples)
+                .map(|i| TrackPoint {
+                    time: Some(start + Duration::seconds(i as i64 * 10)),
+                    distance: Some(i as f64 * 1000.0 / 30.0),
+                    elevation: Some(if i % 2 == 0 { 10.0 } else { 11.0 }),
+                    heartrate: Some(150),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_from_track() {
+        let activity = Activity::from_track(&steady_run(3), PathBuf::from("run.gpx"), Tz::UTC);
+
+        assert_eq!(activity.distance.round(), 3000.0);
+        assert_eq!(activity.moving_time, 900);
+        assert_eq!(activity.elapsed_time, 900);
+        // 1m oscillation is within the noise band
+        assert_eq!(activity.total_elevation_gain, 0.0);
+        assert_eq!(activity.average_heartrate, Some(150.0));
+        assert_eq!(activity.splits.len(), 3);
+        assert_eq!(activity.splits[0].moving_time, 300);
+        assert!(activity.is_run());
+        assert_eq!(activity.origin, Origin::File(PathBuf::from("run.gpx")));
+    }
+
+    #[test]
+    fn test_local_start_in_profile_timezone() {
+        // 06:00 UTC is still the previous evening on the US west coast
+        let activity = Activity::from_track(&steady_run(1), PathBuf::from("run.gpx"), Tz::America__Los_Angeles);
+        assert_eq!(activity.date(), NaiveDate::from_ymd_opt(2026, 2, 2).unwrap());
+        assert_eq!(activity.start_date_local.to_string(), "2026-02-02 22:00:00");
+    }
+
+    #[test]
+    fn test_splits_skip_untimed_points() {
+        let mut track = steady_run(2);
+        for (i, point) in track.points.iter_mut().enumerate() {
+            point.elevation = point.distance.map(|d| d / 50.0);
+            point.heartrate = Some(if i <= 30 { 140 } else { 160 });
+        }
+        // A sample without a time (and so without a place in the timed sequence)
+        track.points.insert(
+            10,
+            TrackPoint {
+                elevation: Some(999.0),
+                heartrate: Some(90),
+                ..Default::default()
+            },
+        );
+
+        let activity = Activity::from_track(&track, PathBuf::from("run.gpx"), Tz::UTC);
+
+        assert_eq!(activity.splits.len(), 2);
+        assert_eq!(activity.splits[0].average_heartrate, Some(140.0));
+        assert_eq!(activity.splits[1].average_heartrate, Some(160.0));
+        assert_eq!(activity.splits[0].elevation_difference.map(f64::round), Some(20.0));
+        assert_eq!(activity.splits[1].elevation_difference.map(f64::round), Some(20.0));
+    }
+
+    #[test]
+    fn test_stopped_time_is_not_moving() {
+        let mut track = steady_run(1);
+        // Stand still for two minutes at the end
+        let last = track.points.last().cloned().unwrap();
+        track.points.push(TrackPoint {
+            time: last.time.map(|t| t + Duration::seconds(120)),
+            ..last
+        });
+
+        let activity = Activity::from_track(&track, PathBuf::from("run.fit"), Tz::UTC);
+
+        assert_eq!(activity.moving_time, 300);
+        assert_eq!(activity.elapsed_time, 420);
+    }
+
+    #[test]
+    fn test_distance_from_positions() {
+        let start = Utc.with_ymd_and_hms(2026, 2, 3, 6, 0, 0).unwrap();
+        let track = Track {
+            name: "GPS only".to_string(),
+            sport_type: "Run".to_string(),
+            start,
+            points: vec![
+                TrackPoint {
+                    position: Some((0.0, 0.0)),
+                    time: Some(start),
+                    ..Default::default()
+                },
+                TrackPoint {
+                    position: Some((0.0, 0.01)),
+                    time: Some(start + Duration::seconds(300)),
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let activity = Activity::from_track(&track, PathBuf::from("gps.gpx"), Tz::UTC);
+
+        // 0.01 degrees of longitude at the equator is ~1112m
+        assert_eq!(activity.distance.round(), 1112.0);
+    }
+
+    #[test]
+    fn test_is_run_type() {
+        assert!(is_run_type("Run"));
+        assert!(is_run_type("TrailRun"));
+        assert!(!is_run_type("VirtualRun"));
+        assert!(!is_run_type("Ride"));
+    }
+
+    #[test]
+    fn test_is_same_workout() {
+        let a = Activity::from_track(&steady_run(1), PathBuf::from("a.fit"), Tz::UTC);
+        let mut b = a.clone();
+        b.start_date += Duration::seconds(30);
+        assert!(a.is_same_workout(&b));
+
+        b.start_date += Duration::minutes(10);
+        assert!(!a.is_same_workout(&b));
+    }
+}
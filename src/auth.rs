@@ -0,0 +1,181 @@
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::OsRng;
+use std::time::{Duration, Instant};
+
+/// How long a headless authorization stays open before it must be restarted
+const PENDING_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A headless authorization waiting for the athlete to paste back the redirect
+#[derive(Debug, Clone)]
+pub struct PendingAuthorization {
+    pub state: String,
+    pub scope: String,
+    expires_at: Instant,
+}
+
+impl PendingAuthorization {
+    /// Start a new authorization with a random state value
+    pub fn new(scope: &str) -> Self {
+        let mut bytes = [0u8; 24];
+        OsRng.fill_bytes(&mut bytes);
+        Self {
+            state: BASE64_URL.encode(bytes),
+            scope: scope.to_string(),
+            expires_at: Instant::now() + PENDING_TIMEOUT,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    /// Check the pasted response against this authorization and return the code
+    pub fn verify(&self, response: &CallbackResponse) -> Result<String> {
+        if self.is_expired() {
+            bail!("Authorization expired; run authorize again");
+        }
+        match &response.state {
+            Some(state) if *state == self.state => Ok(response.code.clone()),
+            Some(_) => bail!("State does not match the pending authorization; run authorize again"),
+            // Without the state a code from someone else's authorization could be slipped in
+            None => bail!("Paste the full redirect URL, not just its code, so its state can be checked"),
+        }
+    }
+}
+
+/// The parts of Strava's OAuth redirect needed to finish authorization
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallbackResponse {
+    pub code: String,
+    /// Missing when only the code was pasted, which `verify` rejects
+    pub state: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// Parse a pasted redirect URL, its query string, or a bare authorization code.
+///
+/// A bare code parses so that `verify` can explain why it is not enough.
+pub fn parse_callback(input: &str) -> Result<CallbackResponse> {
+    let input = input.trim();
+    if input.is_empty() {
+        bail!("Paste the redirect URL from the browser's address bar");
+    }
+
+    if !input.contains('=') {
+        if !input.chars().all(|c| c.is_ascii_alphanumeric()) {
+            bail!("'{}' is not an authorization code or redirect URL", input);
+        }
+        return Ok(CallbackResponse {
+            code: input.to_string(),
+            state: None,
+            scope: None,
+        });
+    }
+
+    let query = input.split_once('?').map_or(input, |(_, query)| query);
+    let query = query.split('#').next().unwrap_or(query);
+
+    let mut code = None;
+    let mut state = None;
+    let mut scope = None;
+    let mut error = None;
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value);
+        match key {
+            "code" => code = Some(value),
+            "state" => state = Some(value),
+            "scope" => scope = Some(value),
+            "error" => error = Some(value),
+            _ => {}
+        }
+    }
+
+    if let Some(error) = error {
+        if error == "access_denied" {
+            bail!("Authorization was denied on Strava's consent page");
+        }
+        bail!("Strava returned an error: {}", error);
+    }
+
+    Ok(CallbackResponse {
+        code: code.filter(|c| !c.is_empty()).ok_or_else(|| anyhow!("Redirect URL has no code parameter"))?,
+        state,
+        scope,
+    })
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_callback() {
+        let response = parse_callback(
+            "http://localhost:8089/?state=abc123&code=f00dcafe&scope=read,activity:read_all",
+        )
+        .unwrap();
+        assert_eq!(response.code, "f00dcafe");
+        assert_eq!(response.state.as_deref(), Some("abc123"));
+        assert_eq!(response.scope.as_deref(), Some("read,activity:read_all"));
+
+        let encoded = parse_callback("state=abc123&code=f00dcafe&scope=read%2Cactivity%3Awrite").unwrap();
+        assert_eq!(encoded.scope.as_deref(), Some("read,activity:write"));
+
+        let bare = parse_callback("  f00dcafe\n").unwrap();
+        assert_eq!(bare.code, "f00dcafe");
+        assert_eq!(bare.state, None);
+
+        assert!(parse_callback("http://localhost:8089/?state=abc&error=access_denied").is_err());
+        assert!(parse_callback("http://localhost:8089/?state=abc").is_err());
+        assert!(parse_callback("not a code").is_err());
+    }
+
+    #[test]
+    fn test_verify_state() {
+        let pending = PendingAuthorization::new("activity:read_all");
+        assert_ne!(pending.state, PendingAuthorization::new("activity:read_all").state);
+
+        let matching = format!("http://localhost:8089/?state={}&code=abc", pending.state);
+        assert_eq!(pending.verify(&parse_callback(&matching).unwrap()).unwrap(), "abc");
+
+        let forged = parse_callback("http://localhost:8089/?state=forged&code=abc").unwrap();
+        assert!(pending.verify(&forged).is_err());
+
+        // A code without its state cannot be tied to this authorization
+        let error = pending.verify(&parse_callback("abc").unwrap()).unwrap_err();
+        assert!(error.to_string().contains("full redirect URL"));
+
+        let expired = PendingAuthorization {
+            expires_at: Instant::now(),
+            ..pending
+        };
+        assert!(expired.verify(&parse_callback("abc").unwrap()).is_err());
+    }
+}
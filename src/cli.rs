@@ -0,0 +1,255 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Subcommand;
+use rmcp::handler::server::wrapper::Parameters;
+use rmcp::model::CallToolResult;
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::time::Instant;
+use tracing::Instrument;
+
+use crate::{logging, McpErrorExt, StravaMcpServer};
+
+/// Commands that run tools directly and print their output, without an MCP client
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Run the MCP server over stdio (default)
+    Serve,
+    /// Runs on a date
+    Runs {
+        /// Date in YYYY-MM-DD format, today or yesterday, or a period such as last week
+        #[arg(long)]
+        date: String,
+    },
+    /// Most recent runs
+    Recent {
+        /// Number of runs (default: 5)
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Weekly summary
+    Week {
+        /// ISO week (2026-W07), last week, or a date to start the week on (default: this week)
+        #[arg(long)]
+        start: Option<String>,
+    },
+    /// Runs in a month
+    Month {
+        /// Month in YYYY-MM format, last month, or a period such as 2026-Q1
+        #[arg(long)]
+        month: String,
+    },
+    /// Monthly running summary
+    Summary {
+        /// Month in YYYY-MM format, last month, or a period such as 2026-Q1
+        #[arg(long)]
+        month: String,
+    },
+    /// Authorize with Strava
+    Authorize {
+        /// Port for the OAuth callback server (default: 8089)
+        #[arg(long)]
+        port: Option<u16>,
+        /// OAuth scope, or several separated by commas (default: activity:read_all)
+        #[arg(long)]
+        scope: Option<String>,
+        /// Print the authorization URL and read the redirect URL from stdin
+        #[arg(long)]
+        headless: bool,
+    },
+    /// List every tool
+    Tools,
+    /// Run any tool by name
+    Tool {
+        /// Tool name, e.g. get_gear_report
+        name: String,
+        /// Tool arguments as a JSON object, e.g. '{"days": 90}'
+        #[arg(long, default_value = "{}")]
+        args: String,
+    },
+}
+
+/// Run a command and print its output as markdown, or as the raw tool result with `json`
+pub async fn run(server: &StravaMcpServer, command: Command, json: bool) -> Result<()> {
+    let result = match command {
+        Command::Serve => bail!("serve is handled by main"),
+        Command::Tools => {
+            let mut tools = server.tool_router.list_all();
+            tools.sort_by(|a, b| a.name.cmp(&b.name));
+            if json {
+                println!("{}", serde_json::to_string_pretty(&tools)?);
+            } else {
+                for tool in tools {
+                    println!("- **{}**: {}", tool.name, tool.description.as_deref().unwrap_or(""));
+                }
+            }
+            return Ok(());
+        }
+        Command::Authorize { port, scope, headless } => {
+            let args = json!({ "port": port, "scope": scope, "headless": headless });
+            let result = call_tool(server, "authorize", args).await?;
+            if !headless || json {
+                return print_result(&result, json);
+            }
+
+            // The pending state lives in this process, so finish the flow here
+            print_result(&result, false)?;
+            eprintln!("Paste the redirect URL:");
+            let mut response = String::new();
+            std::io::stdin().read_line(&mut response)?;
+            call_tool(server, "complete_authorization", json!({ "response": response.trim() })).await?
+        }
+        command => {
+            let (name, args) = tool_call(command)?;
+            call_tool(server, &name, args).await?
+        }
+    };
+
+    print_result(&result, json)
+}
+
+/// The tool and arguments a shortcut command stands for
+fn tool_call(command: Command) -> Result<(String, Value)> {
+    let (name, args) = match command {
+        Command::Runs { date } => ("get_runs_for_date", json!({ "date": date })),
+        Command::Recent { limit } => ("get_recent_runs", json!({ "limit": limit })),
+        Command::Week { start } => ("get_weekly_summary", json!({ "week_start": start })),
+        Command::Month { month } => ("get_runs_for_month", json!({ "month": month })),
+        Command::Summary { month } => ("get_monthly_running_summary", json!({ "month": month })),
+        Command::Tool { name, args } => {
+            let args: Value = serde_json::from_str(&args).map_err(|e| anyhow!("--args is not valid JSON: {}", e))?;
+            if !args.is_object() {
+                bail!("--args must be a JSON object");
+            }
+            return Ok((name, args));
+        }
+        Command::Serve | Command::Tools | Command::Authorize { .. } => bail!("{:?} is not a tool call", command),
+    };
+    Ok((name.to_string(), args))
+}
+
+/// Call a tool by name, as an MCP client would
+async fn call_tool(server: &StravaMcpServer, name: &str, args: Value) -> Result<CallToolResult> {
+    // Tools disabled by the config are not callable here either
+    if !server.tool_router.has_route(name) {
+        bail!("Unknown or disabled tool '{}'. Run `strava-mcp tools` to list them.", name);
+    }
+
+    let span = logging::tool_span(name, args.as_object());
+    let started = Instant::now();
+    let result = dispatch(server, name, args).instrument(span.clone()).await;
+    span.in_scope(|| logging::record_call(name, started, &result));
+
+    result.map_err(|e| anyhow!("{}", e.message))
+}
+
+/// Generates `dispatch` and the `TOOLS` it handles from one list, so the test below can check it against the router
+macro_rules! tools {
+    ($($tool:ident),* $(,)?) => {
+        /// Every tool `dispatch` can call
+        #[cfg(test)]
+        const TOOLS: &[&str] = &["list_profiles", "list_webhook_subscriptions", $(stringify!($tool)),*];
+
+        async fn dispatch(server: &StravaMcpServer, name: &str, args: Value) -> Result<CallToolResult, McpError> {
+            match name {
+                "list_profiles" => server.list_profiles().await,
+                "list_webhook_subscriptions" => server.list_webhook_subscriptions().await,
+                $(stringify!($tool) => match serde_json::from_value(args) {
+                    Ok(params) => server.$tool(Parameters(params)).await,
+                    Err(e) => Err(McpError::invalid_params_no_data(format!("Invalid arguments for {}: {}", name, e))),
+                },)*
+                _ => Err(McpError::invalid_params_no_data(format!("Unknown tool '{}'", name))),
+            }
+        }
+    };
+}
+
+tools!(
+    get_runs_for_date,
+    get_recent_runs,
+    get_weekly_summary,
+    get_gear_report,
+    get_runs_for_month,
+    get_monthly_running_summary,
+    get_athlete_profile,
+    get_athlete_stats,
+    list_starred_segments,
+    get_segment,
+    get_segment_efforts,
+    get_activity_segment_efforts,
+    list_routes,
+    get_route,
+    export_route_gpx,
+    list_clubs,
+    get_club_activities,
+    get_club_recent_leaderboard,
+    update_activity,
+    describe_activity,
+    create_manual_activity,
+    upload_activity_file,
+    import_activity_files,
+    import_strava_archive,
+    export_activity_gpx,
+    export_activity_tcx,
+    authorize,
+    complete_authorization,
+    get_auth_status,
+    deauthorize,
+    show_config,
+    create_webhook_subscription,
+    delete_webhook_subscription,
+);
+
+fn print_result(result: &CallToolResult, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(result)?);
+    } else {
+        for content in &result.content {
+            if let Some(text) = content.as_text() {
+                println!("{}", text.text);
+            }
+        }
+    }
+
+    if result.is_error == Some(true) {
+        bail!("Tool reported an error");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_call() {
+        let (name, args) = tool_call(Command::Runs {
+            date: "2026-03-01".to_string(),
+        })
+        .unwrap();
+        assert_eq!(name, "get_runs_for_date");
+        assert_eq!(args, json!({ "date": "2026-03-01" }));
+
+        let (name, args) = tool_call(Command::Tool {
+            name: "get_gear_report".to_string(),
+            args: r#"{"days": 90}"#.to_string(),
+        })
+        .unwrap();
+        assert_eq!(name, "get_gear_report");
+        assert_eq!(args["days"], 90);
+
+        assert!(tool_call(Command::Tool {
+            name: "get_gear_report".to_string(),
+            args: "[90]".to_string(),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_every_tool_is_dispatched() {
+        let routes = StravaMcpServer::tool_router().list_all();
+        for tool in &routes {
+            assert!(TOOLS.contains(&tool.name.as_ref()), "{} is missing from tools!", tool.name);
+        }
+        assert_eq!(TOOLS.len(), routes.len());
+    }
+}
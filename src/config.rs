@@ -0,0 +1,457 @@
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+use crate::profiles::{self, ProfileSettings};
+
+/// Every config key with its environment variable, in display order
+pub const KEYS: &[(&str, &str)] = &[
+    ("units", "STRAVA_UNITS"),
+    ("timezone", "STRAVA_TIMEZONE"),
+    ("sport", "STRAVA_SPORT"),
+    ("hr_zones", "STRAVA_HR_ZONES"),
+    ("max_hr", "STRAVA_MAX_HR"),
+    ("default_limit", "STRAVA_DEFAULT_LIMIT"),
+    ("max_limit", "STRAVA_MAX_LIMIT"),
+    ("oauth_port", "STRAVA_OAUTH_PORT"),
+    ("oauth_scope", "STRAVA_OAUTH_SCOPE"),
+    ("cache_dir", "STRAVA_CACHE_DIR"),
+    ("tools", "STRAVA_TOOLS"),
+    ("deny_tools", "STRAVA_DENY_TOOLS"),
+    ("read_only", "STRAVA_READ_ONLY"),
+    ("webhook", "STRAVA_WEBHOOK"),
+    ("webhook_addr", "STRAVA_WEBHOOK_ADDR"),
+    ("webhook_verify_token", "STRAVA_WEBHOOK_VERIFY_TOKEN"),
+];
+
+/// Tools that change Strava data or start an OAuth flow; read-only mode leaves them out
+pub const WRITE_TOOLS: &[&str] = &[
+    "update_activity",
+    "create_manual_activity",
+    "upload_activity_file",
+    "authorize",
+    "complete_authorization",
+    "deauthorize",
+    "create_webhook_subscription",
+    "delete_webhook_subscription",
+];
+
+/// Keys whose values show_config does not reveal
+const SECRET_KEYS: &[&str] = &["webhook_verify_token"];
+
+/// Keys that are per-profile defaults; a profile's settings.toml can override them
+const PROFILE_KEYS: &[&str] = &["units", "timezone", "sport", "hr_zones", "max_hr"];
+
+/// Where a setting came from, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    Default,
+    File,
+    Profile,
+    Env,
+    Cli,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::File => "config file",
+            Self::Profile => "profile settings",
+            Self::Env => "environment",
+            Self::Cli => "command line",
+        })
+    }
+}
+
+/// Server-wide keys as written in config.toml
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ServerValues {
+    /// Capped at max_limit when unset
+    default_limit: Option<usize>,
+    max_limit: usize,
+    oauth_port: u16,
+    oauth_scope: String,
+    cache_dir: Option<String>,
+    tools: Option<Vec<String>>,
+    deny_tools: Vec<String>,
+    read_only: bool,
+    webhook: bool,
+    webhook_addr: String,
+    webhook_verify_token: Option<String>,
+}
+
+impl Default for ServerValues {
+    fn default() -> Self {
+        Self {
+            default_limit: None,
+            max_limit: 100,
+            oauth_port: 8089,
+            oauth_scope: "activity:read_all".to_string(),
+            cache_dir: None,
+            tools: None,
+            deny_tools: Vec::new(),
+            read_only: false,
+            webhook: false,
+            webhook_addr: "127.0.0.1:8090".to_string(),
+            webhook_verify_token: None,
+        }
+    }
+}
+
+/// Configuration merged from the command line, environment, config.toml and defaults, in that order
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// The config file, whether or not it exists
+    pub path: PathBuf,
+    /// Number of runs get_recent_runs returns by default
+    pub default_limit: usize,
+    /// Most runs get_recent_runs returns
+    pub max_limit: usize,
+    pub oauth_port: u16,
+    pub oauth_scope: String,
+    /// Directory for tokens, imported activities and profiles
+    pub cache_dir: PathBuf,
+    /// Tools to register; all of them when unset
+    pub tools: Option<Vec<String>>,
+    /// Tools never to register, even if listed in `tools`
+    pub deny_tools: Vec<String>,
+    /// Leave out write tools and refuse to save to Strava
+    pub read_only: bool,
+    /// Run the webhook receiver alongside the MCP server
+    pub webhook: bool,
+    /// Address the webhook receiver listens on
+    pub webhook_addr: SocketAddr,
+    /// Shared secret Strava echoes back when validating a subscription
+    pub webhook_verify_token: Option<String>,
+    layers: Vec<(Source, Table)>,
+}
+
+impl Config {
+    /// ~/.config/strava-mcp/config.toml
+    pub fn default_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().context("Could not determine home directory")?;
+        Ok(home.join(".config").join("strava-mcp").join("config.toml"))
+    }
+
+    /// Merge the layers; a missing config file is an error only when it was asked for explicitly
+    pub fn load(path: PathBuf, required: bool, env: Table, cli: Table) -> Result<Self> {
+        let file = if path.exists() {
+            read_table(&path).with_context(|| format!("Failed to load config file: {}", path.display()))?
+        } else if required {
+            bail!("Config file not found: {}", path.display());
+        } else {
+            Table::new()
+        };
+
+        let layers = vec![(Source::File, file), (Source::Env, env), (Source::Cli, cli)];
+        let mut server = merge(&layers);
+        server.retain(|key, _| !PROFILE_KEYS.contains(&key));
+        let values: ServerValues = Value::Table(server).try_into().context("Invalid configuration")?;
+
+        if values.max_limit == 0 {
+            bail!("max_limit must be greater than 0");
+        }
+        const DEFAULT_LIMIT: usize = 5;
+        let default_limit = values.default_limit.unwrap_or(DEFAULT_LIMIT.min(values.max_limit));
+        if default_limit == 0 || default_limit > values.max_limit {
+            bail!(
+                "default_limit must be between 1 and max_limit ({}), got {}",
+                values.max_limit,
+                default_limit
+            );
+        }
+        // Ports below 1024 require elevated privileges
+        if values.oauth_port < 1024 {
+            bail!("oauth_port must be >= 1024 (got: {})", values.oauth_port);
+        }
+
+        let webhook_addr: SocketAddr = values
+            .webhook_addr
+            .parse()
+            .with_context(|| format!("webhook_addr must be an address such as 127.0.0.1:8090 (got: {})", values.webhook_addr))?;
+        if values.webhook && values.webhook_verify_token.is_none() {
+            bail!("webhook_verify_token (or STRAVA_WEBHOOK_VERIFY_TOKEN) must be set to run the webhook receiver");
+        }
+
+        let cache_dir = match values.cache_dir {
+            Some(dir) => expand_home(&dir)?,
+            None => profiles::base_dir()?,
+        };
+
+        let config = Self {
+            path,
+            default_limit,
+            max_limit: values.max_limit,
+            oauth_port: values.oauth_port,
+            oauth_scope: values.oauth_scope,
+            cache_dir,
+            tools: values.tools,
+            deny_tools: values.deny_tools,
+            read_only: values.read_only,
+            webhook: values.webhook,
+            webhook_addr,
+            webhook_verify_token: values.webhook_verify_token,
+            layers,
+        };
+
+        // Catch bad profile defaults at startup rather than on first use
+        settings_from(&config.layers).context("Invalid configuration")?;
+        Ok(config)
+    }
+
+    /// Whether a tool is registered: allowed by `tools`, not denied, and not a write tool in read-only mode
+    pub fn tool_enabled(&self, name: &str) -> bool {
+        let allowed = self.tools.as_ref().map_or(true, |tools| tools.iter().any(|t| t == name));
+        let denied = self.deny_tools.iter().any(|t| t == name);
+        allowed && !denied && !(self.read_only && WRITE_TOOLS.contains(&name))
+    }
+
+    /// A profile's settings: its settings.toml overrides the config file, but not the environment or command line
+    pub fn profile_settings(&self, path: &Path) -> Result<ProfileSettings> {
+        settings_from(&self.layers_with_profile(path)?)
+            .with_context(|| format!("Failed to parse profile settings: {}", path.display()))
+    }
+
+    /// Each key's value and where it came from, with a profile's settings.toml taken into account
+    pub fn describe(&self, profile_settings: &Path) -> Result<Vec<(&'static str, String, Source)>> {
+        let layers = self.layers_with_profile(profile_settings)?;
+        let described = KEYS
+            .iter()
+            .map(|(key, _)| {
+                let set = layers.iter().rev().find_map(|(source, table)| table.get(*key).map(|v| (v, *source)));
+                match set {
+                    Some((_, source)) if SECRET_KEYS.contains(key) => (*key, "(set)".to_string(), source),
+                    Some((value, source)) => (*key, display(value), source),
+                    None => (*key, self.default_value(key), Source::Default),
+                }
+            })
+            .collect();
+        Ok(described)
+    }
+
+    fn layers_with_profile(&self, path: &Path) -> Result<Vec<(Source, Table)>> {
+        let mut layers = self.layers.clone();
+        if path.is_file() {
+            let profile = read_table(path).with_context(|| format!("Failed to read profile settings: {}", path.display()))?;
+            if let Some(key) = profile.keys().find(|key| !PROFILE_KEYS.contains(&key.as_str())) {
+                bail!(
+                    "Unknown setting '{}' in {} (profiles can set {})",
+                    key,
+                    path.display(),
+                    PROFILE_KEYS.join(", ")
+                );
+            }
+            layers.push((Source::Profile, profile));
+            layers.sort_by_key(|(source, _)| *source);
+        }
+        Ok(layers)
+    }
+
+    fn default_value(&self, key: &str) -> String {
+        match key {
+            "units" => "metric".to_string(),
+            "timezone" => "UTC".to_string(),
+            "sport" => "run".to_string(),
+            "hr_zones" => "from Strava".to_string(),
+            "max_hr" => "not set".to_string(),
+            "default_limit" => self.default_limit.to_string(),
+            "max_limit" => self.max_limit.to_string(),
+            "oauth_port" => self.oauth_port.to_string(),
+            "oauth_scope" => self.oauth_scope.clone(),
+            "cache_dir" => self.cache_dir.display().to_string(),
+            "tools" => "all".to_string(),
+            "deny_tools" => "none".to_string(),
+            "read_only" => "false".to_string(),
+            "webhook" => "false".to_string(),
+            "webhook_addr" => self.webhook_addr.to_string(),
+            "webhook_verify_token" => "not set".to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The environment layer: each key's STRAVA_* variable, when set
+pub fn env_layer() -> Table {
+    let values: Vec<_> = KEYS.iter().map(|(key, var)| (*key, std::env::var(var).ok())).collect();
+    layer(&values)
+}
+
+/// A layer from raw key/value strings, as given on the command line or in the environment
+pub fn layer(values: &[(&str, Option<String>)]) -> Table {
+    values
+        .iter()
+        .filter_map(|(key, raw)| {
+            let raw = raw.as_deref()?.trim();
+            (!raw.is_empty()).then(|| (key.to_string(), parse_value(key, raw)))
+        })
+        .collect()
+}
+
+/// Numbers and arrays are read as TOML; anything else is a plain string. Tools may be comma-separated.
+fn parse_value(key: &str, raw: &str) -> Value {
+    if (key == "tools" || key == "deny_tools") && !raw.starts_with('[') {
+        let tools = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(|t| Value::String(t.to_string()))
+            .collect();
+        return Value::Array(tools);
+    }
+
+    toml::from_str::<Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(raw.to_string()))
+}
+
+fn settings_from(layers: &[(Source, Table)]) -> Result<ProfileSettings> {
+    let mut settings = merge(layers);
+    settings.retain(|key, _| PROFILE_KEYS.contains(&key));
+    ProfileSettings::from_table(settings)
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&text)?)
+}
+
+/// Later layers override earlier ones key by key
+fn merge(layers: &[(Source, Table)]) -> Table {
+    let mut merged = Table::new();
+    for (_, table) in layers {
+        merged.extend(table.clone());
+    }
+    merged
+}
+
+fn expand_home(path: &str) -> Result<PathBuf> {
+    match path.strip_prefix("~/") {
+        Some(rest) => Ok(dirs::home_dir().context("Could not determine home directory")?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiles::Units;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("strava-mcp-config-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(parse_value("max_hr", "190"), Value::Integer(190));
+        assert_eq!(parse_value("units", "imperial"), Value::String("imperial".to_string()));
+        assert_eq!(parse_value("timezone", "Europe/London"), Value::String("Europe/London".to_string()));
+        assert_eq!(parse_value("oauth_scope", "read,activity:write"), Value::String("read,activity:write".to_string()));
+        assert_eq!(parse_value("tools", "get_recent_runs, show_config").as_array().unwrap().len(), 2);
+        assert_eq!(parse_value("hr_zones", "[[0, 140], [140, -1]]").as_array().unwrap().len(), 2);
+        assert_eq!(parse_value("read_only", "true"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let dir = temp_dir("precedence");
+        let path = dir.join("config.toml");
+        std::fs::write(&path, "units = \"imperial\"\ntimezone = \"Europe/London\"\nmax_hr = 185\ndefault_limit = 10\noauth_port = 9000\n").unwrap();
+        let profile = dir.join("settings.toml");
+        std::fs::write(&profile, "timezone = \"America/New_York\"\nmax_hr = 178\n").unwrap();
+
+        let env = layer(&[
+            ("default_limit", Some("20".to_string())),
+            ("max_hr", Some("190".to_string())),
+            ("webhook_verify_token", Some("hunter2".to_string())),
+        ]);
+        let cli = layer(&[("default_limit", Some("7".to_string())), ("cache_dir", Some(dir.display().to_string()))]);
+        let config = Config::load(path, true, env, cli).unwrap();
+
+        assert_eq!(config.default_limit, 7);
+        assert_eq!(config.max_limit, 100);
+        assert_eq!(config.oauth_port, 9000);
+        assert_eq!(config.cache_dir, dir);
+
+        // The profile's file beats config.toml but not the environment
+        let settings = config.profile_settings(&profile).unwrap();
+        assert_eq!(settings.units, Units::Imperial);
+        assert_eq!(settings.timezone.as_deref(), Some("America/New_York"));
+        assert_eq!(settings.max_hr, Some(190));
+
+        let described = config.describe(&profile).unwrap();
+        let find = |key: &str| described.iter().find(|(k, _, _)| *k == key).cloned().unwrap();
+        assert_eq!(find("default_limit"), ("default_limit", "7".to_string(), Source::Cli));
+        assert_eq!(find("max_hr"), ("max_hr", "190".to_string(), Source::Env));
+        assert_eq!(find("timezone").2, Source::Profile);
+        assert_eq!(find("units").2, Source::File);
+        assert_eq!(find("max_limit"), ("max_limit", "100".to_string(), Source::Default));
+        assert_eq!(find("webhook_addr"), ("webhook_addr", "127.0.0.1:8090".to_string(), Source::Default));
+        assert_eq!(find("webhook_verify_token"), ("webhook_verify_token", "(set)".to_string(), Source::Env));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_tool_enabled() {
+        let load = |cli: &[(&str, Option<String>)]| {
+            Config::load(PathBuf::from("/nonexistent/config.toml"), false, Table::new(), layer(cli)).unwrap()
+        };
+
+        let config = load(&[]);
+        assert!(config.tool_enabled("update_activity"));
+
+        let config = load(&[("read_only", Some("true".to_string()))]);
+        assert!(!config.tool_enabled("update_activity"));
+        assert!(!config.tool_enabled("authorize"));
+        assert!(config.tool_enabled("get_recent_runs"));
+
+        let config = load(&[
+            ("tools", Some("get_recent_runs,get_gear_report".to_string())),
+            ("deny_tools", Some("get_gear_report".to_string())),
+        ]);
+        assert!(config.tool_enabled("get_recent_runs"));
+        assert!(!config.tool_enabled("get_gear_report"));
+        assert!(!config.tool_enabled("get_weekly_summary"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let dir = temp_dir("invalid");
+        let path = dir.join("config.toml");
+        let load = |text: &str| {
+            std::fs::write(&path, text).unwrap();
+            Config::load(path.clone(), true, Table::new(), Table::new())
+        };
+
+        assert!(load("default_limit = 5\n").is_ok());
+        assert!(load("default_limit = 500\n").is_err());
+        assert_eq!(load("max_limit = 3\n").unwrap().default_limit, 3);
+        assert!(load("oauth_port = 80\n").is_err());
+        assert!(load("timezone = \"Mars/Olympus\"\n").is_err());
+        assert!(load("colour = \"blue\"\n").is_err());
+        assert!(load("webhook_addr = \"localhost\"\n").is_err());
+        assert!(load("webhook = true\n").is_err());
+        assert!(load("webhook = true\nwebhook_verify_token = \"secret\"\n").is_ok());
+
+        // Only an explicitly requested file must exist
+        assert!(Config::load(dir.join("missing.toml"), true, Table::new(), Table::new()).is_err());
+        assert!(Config::load(dir.join("missing.toml"), false, Table::new(), Table::new()).is_ok());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
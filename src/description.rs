@@ -0,0 +1,294 @@
+use crate::activity::{Activity, Split};
+use crate::plan::Adherence;
+use crate::profiles::Units;
+use crate::totals::Totals;
+use crate::utils::{format_duration, format_pace};
+
+/// Seconds spent in each heart rate zone, from paired time and heart rate streams.
+///
+/// Zones are (min, max) in bpm; Strava marks the top zone's open upper bound with -1.
+pub fn time_in_zones(time: &[i32], heartrate: &[i32], zones: &[(i32, i32)]) -> Vec<i32> {
+    let mut seconds = vec![0; zones.len()];
+
+    for (window, hr) in time.windows(2).zip(heartrate) {
+        let delta = window[1] - window[0];
+        // Skip pauses so stopped time is not counted against a zone
+        if delta <= 0 || delta > 30 {
+            continue;
+        }
+        if let Some(zone) = zones
+            .iter()
+            .position(|&(min, max)| *hr >= min && (max < 0 || *hr < max))
+        {
+            seconds[zone] += delta;
+        }
+    }
+
+    seconds
+}
+
+/// Where an activity falls within its week, counting only activities up to and including it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeekContext {
+    /// 1 for the first run of the week
+    pub run_number: usize,
+    pub totals: Totals,
+}
+
+impl WeekContext {
+    pub fn new(activity: &Activity, week: &[Activity]) -> Self {
+        let runs: Vec<&Activity> = week
+            .iter()
+            .filter(|a| a.is_run() && a.start_date <= activity.start_date)
+            .collect();
+        Self {
+            run_number: runs.len(),
+            totals: runs.into_iter().collect(),
+        }
+    }
+}
+
+/// Everything a description can be built from; optional parts are left out when missing
+#[derive(Debug, Default)]
+pub struct DescriptionParts {
+    pub units: Units,
+    /// Labelled seconds per heart rate zone
+    pub zones: Vec<(String, i32)>,
+    pub week: Option<WeekContext>,
+    /// One line per PR or top-3 effort, e.g. "PR: 5k in 24m 10s"
+    pub achievements: Vec<String>,
+    pub adherence: Option<Adherence>,
+}
+
+/// Lines around the generated text in a saved description, so a later run replaces it
+pub const BLOCK_START: &str = "[strava-mcp]";
+pub const BLOCK_END: &str = "[/strava-mcp]";
+
+/// Compose a plain-text description (Strava does not render markdown)
+pub fn compose(activity: &Activity, parts: &DescriptionParts) -> String {
+    let mut lines = Vec::new();
+
+    let mut headline = format!(
+        "{} in {}",
+        parts.units.distance(activity.distance),
+        format_duration(activity.moving_time)
+    );
+    if let Some(speed) = activity.average_speed.filter(|_| activity.is_run()) {
+        headline.push_str(&format!(" ({})", parts.units.pace(speed)));
+    }
+    if activity.total_elevation_gain >= 1.0 {
+        headline.push_str(&format!(", {} climbing", parts.units.elevation(activity.total_elevation_gain)));
+    }
+    lines.push(headline);
+
+    if let Some(splits) = describe_splits(&activity.splits) {
+        lines.push(splits);
+    }
+
+    let zone_total: i32 = parts.zones.iter().map(|(_, s)| s).sum();
+    if zone_total > 0 {
+        let zones = parts
+            .zones
+            .iter()
+            .filter(|(_, s)| *s > 0)
+            .map(|(label, s)| format!("{} {:.0}%", label, *s as f64 / zone_total as f64 * 100.0))
+            .collect::<Vec<_>>()
+            .join(", ");
+        lines.push(format!("HR zones: {}", zones));
+    } else if let Some(hr) = activity.average_heartrate {
+        lines.push(format!("Avg HR {:.0} bpm", hr));
+    }
+
+    lines.extend(parts.achievements.iter().cloned());
+
+    if let Some(week) = parts.week.as_ref().filter(|w| w.run_number > 0) {
+        lines.push(format!(
+            "{} run this week, {} week so far",
+            ordinal(week.run_number),
+            parts.units.distance(week.totals.distance)
+        ));
+    }
+
+    if let Some(adherence) = &parts.adherence {
+        let mut plan = format!("Plan: {}", adherence.session);
+        if let Some(percent) = adherence.distance_percent {
+            plan.push_str(&format!(" ({:.0}% of planned distance", percent));
+            match adherence.pace_in_range {
+                Some(true) => plan.push_str(", pace on target)"),
+                Some(false) => plan.push_str(", pace off target)"),
+                None => plan.push(')'),
+            }
+        } else if let Some(in_range) = adherence.pace_in_range {
+            plan.push_str(if in_range { " (pace on target)" } else { " (pace off target)" });
+        }
+        lines.push(plan);
+    }
+
+    lines.join("\n")
+}
+
+/// Put generated text into an existing description: replace the block from an earlier run, or append one below
+/// what the athlete wrote. A start marker whose end marker was deleted takes the rest of the description with it.
+pub fn merge(existing: &str, generated: &str) -> String {
+    let block = format!("{}\n{}\n{}", BLOCK_START, generated, BLOCK_END);
+    if let Some(start) = existing.find(BLOCK_START) {
+        let end = existing[start..]
+            .find(BLOCK_END)
+            .map_or(existing.len(), |end| start + end + BLOCK_END.len());
+        return format!("{}{}{}", &existing[..start], block, &existing[end..]);
+    }
+
+    let existing = existing.trim();
+    if existing.is_empty() {
+        block
+    } else {
+        format!("{}\n\n{}", existing, block)
+    }
+}
+
+/// Kilometer paces, with fastest split and whether the second half was quicker
+fn describe_splits(splits: &[Split]) -> Option<String> {
+    // A short final split distorts the comparison
+    let full: Vec<&Split> = splits
+        .iter()
+        .filter(|s| s.distance >= 900.0 && s.moving_time > 0)
+        .collect();
+    if full.len() < 2 {
+        return None;
+    }
+
+    let pace = |s: &Split| s.distance / s.moving_time as f64;
+    let paces = full
+        .iter()
+        .map(|s| format_pace(pace(s)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let (fastest_index, fastest) = full
+        .iter()
+        .enumerate()
+        .max_by(|a, b| pace(a.1).total_cmp(&pace(b.1)))?;
+
+    let half = full.len() / 2;
+    let average = |splits: &[&Split]| {
+        splits.iter().map(|s| s.distance).sum::<f64>() / splits.iter().map(|s| s.moving_time as f64).sum::<f64>()
+    };
+    let negative = average(&full[full.len() - half..]) > average(&full[..half]);
+
+    let mut text = format!(
+        "Splits: {} (fastest km {} at {}/km)",
+        paces,
+        fastest_index + 1,
+        format_pace(pace(fastest))
+    );
+    if negative {
+        text.push_str(", negative split");
+    }
+    Some(text)
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn run(day: i64, distance: f64, moving_time: i32) -> Activity {
+        let start = NaiveDate::from_ymd_opt(2026, 2, 9)
+            .unwrap()
+            .and_hms_opt(7, 0, 0)
+            .unwrap()
+            + Duration::days(day);
+        Activity {
+            id: Some(day),
+            average_heartrate: Some(150.0),
+            ..Activity::test_run(start, distance, moving_time)
+        }
+    }
+
+    fn split(moving_time: i32) -> Split {
+        Split {
+            distance: 1000.0,
+            moving_time,
+            elevation_difference: None,
+            average_heartrate: None,
+        }
+    }
+
+    #[test]
+    fn test_time_in_zones() {
+        let time = [0, 10, 20, 30, 90, 100];
+        let heartrate = [120, 150, 150, 170, 170, 190];
+        let zones = [(0, 130), (130, 160), (160, -1)];
+
+        // The 60s gap from 30 to 90 is a pause
+        assert_eq!(time_in_zones(&time, &heartrate, &zones), vec![10, 20, 10]);
+    }
+
+    #[test]
+    fn test_week_context() {
+        let week = vec![run(0, 5000.0, 1500), run(2, 10_000.0, 3000), run(4, 8000.0, 2400)];
+
+        let context = WeekContext::new(&week[1], &week);
+
+        assert_eq!(context.run_number, 2);
+        assert_eq!(context.totals.distance, 15_000.0);
+    }
+
+    #[test]
+    fn test_compose() {
+        let mut activity = run(2, 3000.0, 900);
+        activity.splits = vec![split(310), split(300), split(290)];
+        let parts = DescriptionParts {
+            units: Units::Metric,
+            zones: vec![("Z2".to_string(), 600), ("Z3".to_string(), 300)],
+            week: Some(WeekContext::new(&activity, &[run(0, 5000.0, 1500), activity.clone()])),
+            achievements: vec!["PR: 1k in 4m 50s".to_string()],
+            adherence: None,
+        };
+
+        let description = compose(&activity, &parts);
+
+        assert_eq!(
+            description,
+            "3.00 km in 15m 0s (5:00/km)\n\
+             Splits: 5:10, 5:00, 4:50 (fastest km 3 at 4:50/km), negative split\n\
+             HR zones: Z2 67%, Z3 33%\n\
+             PR: 1k in 4m 50s\n\
+             2nd run this week, 8.00 km week so far"
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let first = merge("", "5.00 km in 25m 0s");
+        assert_eq!(first, "[strava-mcp]\n5.00 km in 25m 0s\n[/strava-mcp]");
+
+        // A rerun replaces the earlier block and keeps what the athlete wrote around it
+        let edited = format!("Felt great\n\n{}\nThanks for the pacing, Jo", first);
+        assert_eq!(
+            merge(&edited, "5.00 km in 24m 0s"),
+            "Felt great\n\n[strava-mcp]\n5.00 km in 24m 0s\n[/strava-mcp]\nThanks for the pacing, Jo"
+        );
+        assert_eq!(merge("Felt great  ", "5.00 km"), "Felt great\n\n[strava-mcp]\n5.00 km\n[/strava-mcp]");
+        assert_eq!(merge("Felt great\n[strava-mcp]\nold", "new"), "Felt great\n[strava-mcp]\nnew\n[/strava-mcp]");
+    }
+
+    #[test]
+    fn test_ordinal() {
+        assert_eq!(ordinal(1), "1st");
+        assert_eq!(ordinal(3), "3rd");
+        assert_eq!(ordinal(11), "11th");
+        assert_eq!(ordinal(22), "22nd");
+    }
+}
@@ -0,0 +1,306 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use rmcp::model::ErrorCode;
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// JSON-RPC server error codes (-32000 to -32099) for failures the user can act on
+pub const NOT_AUTHORIZED: ErrorCode = ErrorCode(-32001);
+pub const MISSING_SCOPE: ErrorCode = ErrorCode(-32003);
+pub const RATE_LIMITED: ErrorCode = ErrorCode(-32029);
+pub const UNAVAILABLE: ErrorCode = ErrorCode(-32050);
+
+/// Why a tool call failed, with what the user can do about it
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolError {
+    /// The profile has no saved token
+    NotAuthorized { profile: String },
+    /// Strava rejected the token and it could not be refreshed
+    TokenExpired(String),
+    /// The token lacks a scope the request needs
+    MissingScope { scope: String, granted: Option<String> },
+    /// Strava's 15-minute or daily request limit was reached
+    RateLimited { resets_at: DateTime<Utc> },
+    /// No such object, or it belongs to another athlete
+    NotFound { resource: String },
+    /// Strava failed or could not be reached
+    Unavailable(String),
+    /// The request was invalid, as reported by Strava or checked locally
+    InvalidInput(String),
+    Internal(String),
+}
+
+impl ToolError {
+    /// Classify a failed Strava API call from the status code and error body in its message
+    pub fn from_strava(endpoint: &str, message: &str) -> Self {
+        let message = message.to_string();
+        match (status_code(&message), missing_permission(&message)) {
+            (Some(401 | 403), Some(scope)) => ToolError::MissingScope { scope, granted: None },
+            (Some(401), None) => ToolError::TokenExpired(message),
+            (Some(403 | 404), None) => ToolError::NotFound { resource: resource(endpoint) },
+            (Some(429), _) => ToolError::RateLimited { resets_at: next_window(Utc::now()) },
+            (Some(400 | 422), _) => ToolError::InvalidInput(message),
+            (Some(500..=599), _) => ToolError::Unavailable(message),
+            (None, _) if is_network_error(&message) => ToolError::Unavailable(message),
+            _ => ToolError::Internal(message),
+        }
+    }
+
+    /// Classify a failed token refresh: anything but an outage or rate limit means the token is unusable
+    pub fn refresh_failed(message: &str) -> Self {
+        match ToolError::from_strava("POST /oauth/token", message) {
+            error @ (ToolError::RateLimited { .. } | ToolError::Unavailable(_)) => error,
+            _ => ToolError::TokenExpired(message.to_string()),
+        }
+    }
+
+    fn code(&self) -> ErrorCode {
+        match self {
+            ToolError::NotAuthorized { .. } | ToolError::TokenExpired(_) => NOT_AUTHORIZED,
+            ToolError::MissingScope { .. } => MISSING_SCOPE,
+            ToolError::RateLimited { .. } => RATE_LIMITED,
+            ToolError::NotFound { .. } => ErrorCode::RESOURCE_NOT_FOUND,
+            ToolError::Unavailable(_) => UNAVAILABLE,
+            ToolError::InvalidInput(_) => ErrorCode::INVALID_PARAMS,
+            ToolError::Internal(_) => ErrorCode::INTERNAL_ERROR,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            ToolError::NotAuthorized { .. } => "not_authorized",
+            ToolError::TokenExpired(_) => "token_expired",
+            ToolError::MissingScope { .. } => "missing_scope",
+            ToolError::RateLimited { .. } => "rate_limited",
+            ToolError::NotFound { .. } => "not_found",
+            ToolError::Unavailable(_) => "unavailable",
+            ToolError::InvalidInput(_) => "invalid_input",
+            ToolError::Internal(_) => "internal",
+        }
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            ToolError::NotAuthorized { .. } => Some(
+                "Call `authorize` to connect a Strava account (with headless: true if the browser is on another machine)."
+                    .to_string(),
+            ),
+            ToolError::TokenExpired(_) => Some("Call `authorize` again to reconnect the Strava account.".to_string()),
+            ToolError::MissingScope { scope, .. } => Some(format!(
+                "Call `authorize` with scope \"{}\" to grant it.",
+                recommended_scope(scope)
+            )),
+            ToolError::RateLimited { .. } => {
+                Some("Wait for the reset, or ask for a shorter period to make fewer requests.".to_string())
+            }
+            ToolError::NotFound { .. } => {
+                Some("Check the ID; the list tools show the IDs this athlete can see.".to_string())
+            }
+            ToolError::Unavailable(_) => Some("This is usually temporary; try again in a few minutes.".to_string()),
+            ToolError::InvalidInput(_) | ToolError::Internal(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ToolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ToolError::NotAuthorized { profile } => write!(f, "Profile '{}' is not authorized with Strava.", profile),
+            ToolError::TokenExpired(detail) => {
+                write!(f, "The Strava token has expired or was revoked and could not be refreshed ({}).", detail)
+            }
+            ToolError::MissingScope { scope, granted: Some(granted) } => {
+                write!(f, "The Strava token does not include the {} scope (granted: {}).", scope, granted)
+            }
+            ToolError::MissingScope { scope, granted: None } => {
+                write!(f, "The Strava token does not include the {} scope.", scope)
+            }
+            ToolError::RateLimited { resets_at } => write!(
+                f,
+                "Strava's rate limit was reached. The 15-minute limit resets at {} UTC, the daily limit at midnight UTC.",
+                resets_at.format("%H:%M")
+            ),
+            ToolError::NotFound { resource } => {
+                write!(f, "Strava has no {} with that ID, or it is private to another athlete.", resource)
+            }
+            ToolError::Unavailable(detail) => write!(f, "Strava is unavailable ({}).", detail),
+            ToolError::InvalidInput(detail) => write!(f, "{}", detail),
+            ToolError::Internal(detail) => write!(f, "Internal error: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for ToolError {}
+
+/// Errors from activity sources keep their classification; anything else is internal
+impl From<anyhow::Error> for ToolError {
+    fn from(error: anyhow::Error) -> Self {
+        match error.downcast_ref::<ToolError>() {
+            Some(error) => error.clone(),
+            None => ToolError::Internal(format!("{:#}", error)),
+        }
+    }
+}
+
+impl From<ToolError> for McpError {
+    fn from(error: ToolError) -> Self {
+        let hint = error.hint();
+        let message = match &hint {
+            Some(hint) => format!("{} {}", error, hint),
+            None => error.to_string(),
+        };
+        let mut data = json!({ "kind": error.kind() });
+        if let Some(hint) = hint {
+            data["hint"] = Value::String(hint);
+        }
+        if let ToolError::RateLimited { resets_at } = &error {
+            data["retry_after_seconds"] = json!((*resets_at - Utc::now()).num_seconds().max(0));
+        }
+        McpError::new(error.code(), message, Some(data))
+    }
+}
+
+/// The HTTP error status in a "Strava API error <status> <reason>: <body>" message; numbers elsewhere,
+/// such as in the body or a decoding error's line number, are not statuses
+fn status_code(message: &str) -> Option<u16> {
+    const PREFIX: &str = "API error ";
+    let rest = &message[message.find(PREFIX)? + PREFIX.len()..];
+    let digits = &rest[..rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len())];
+    digits.parse().ok().filter(|status| (400..=599).contains(status))
+}
+
+/// The scope named by a Strava "activity:read_permission"-style error field
+fn missing_permission(message: &str) -> Option<String> {
+    message
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == ':' || c == '_'))
+        .find_map(|token| {
+            let (area, permission) = token.split_once(':')?;
+            match permission {
+                "read_permission" => Some(format!("{}:read_all", area)),
+                "write_permission" => Some(format!("{}:write", area)),
+                _ => None,
+            }
+        })
+}
+
+fn is_network_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["error sending request", "connection", "timed out", "dns error"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// What an endpoint such as "GET /activities/{id}/streams" looks up
+fn resource(endpoint: &str) -> String {
+    let collection = endpoint.split('/').nth(1).unwrap_or_default();
+    match collection {
+        "activities" => "activity",
+        "athletes" | "athlete" => "athlete",
+        "segments" => "segment",
+        "segment_efforts" => "segment effort",
+        "routes" => "route",
+        "clubs" => "club",
+        "uploads" => "upload",
+        _ => "object",
+    }
+    .to_string()
+}
+
+/// Scopes to request so reads keep working alongside the missing scope
+fn recommended_scope(scope: &str) -> String {
+    if scope == "activity:read_all" {
+        scope.to_string()
+    } else {
+        format!("activity:read_all,{}", scope)
+    }
+}
+
+/// Strava's short-term limit resets at each quarter hour
+fn next_window(now: DateTime<Utc>) -> DateTime<Utc> {
+    let window = Duration::minutes(15);
+    now.duration_trunc(window).unwrap_or(now) + window
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_strava() {
+        let scope = r#"Strava API error 401 Unauthorized: {"message":"Authorization Error","errors":[{"resource":"AccessToken","field":"activity:read_permission","code":"missing"}]}"#;
+        assert_eq!(
+            ToolError::from_strava("GET /athlete/activities", scope),
+            ToolError::MissingScope { scope: "activity:read_all".to_string(), granted: None }
+        );
+        let revoked = r#"Strava API error 401 Unauthorized: {"message":"Authorization Error"}"#;
+        assert!(matches!(ToolError::from_strava("GET /athlete", revoked), ToolError::TokenExpired(_)));
+        assert_eq!(
+            ToolError::from_strava("GET /routes/{id}", "Strava API error 404 Not Found: {}"),
+            ToolError::NotFound { resource: "route".to_string() }
+        );
+        assert!(matches!(
+            ToolError::from_strava("GET /athlete", "Strava API error 429 Too Many Requests"),
+            ToolError::RateLimited { .. }
+        ));
+        assert!(matches!(
+            ToolError::from_strava("GET /athlete", "Strava API error 503 Service Unavailable"),
+            ToolError::Unavailable(_)
+        ));
+        assert!(matches!(
+            ToolError::from_strava("GET /athlete", "error sending request for url"),
+            ToolError::Unavailable(_)
+        ));
+        assert!(matches!(
+            ToolError::from_strava("PUT /activities/{id}", "Strava API error 400 Bad Request: bad name"),
+            ToolError::InvalidInput(_)
+        ));
+        assert!(matches!(ToolError::from_strava("GET /athlete", "stub"), ToolError::Internal(_)));
+
+        // Only the status after "API error" counts
+        assert!(matches!(
+            ToolError::from_strava("GET /athlete", "error decoding response body: expected value at line 404 column 1"),
+            ToolError::Internal(_)
+        ));
+        assert!(matches!(
+            ToolError::from_strava("GET /athlete", r#"Strava API error 400 Bad Request: {"errors":[{"code":"500"}]}"#),
+            ToolError::InvalidInput(_)
+        ));
+    }
+
+    #[test]
+    fn test_refresh_failed() {
+        let rejected = "Token refresh failed: Strava API error 400 Bad Request: invalid refresh_token";
+        assert!(matches!(ToolError::refresh_failed(rejected), ToolError::TokenExpired(_)));
+        let outage = "Token refresh failed: Strava API error 502 Bad Gateway";
+        assert!(matches!(ToolError::refresh_failed(outage), ToolError::Unavailable(_)));
+    }
+
+    #[test]
+    fn test_mcp_error() {
+        let error = McpError::from(ToolError::MissingScope {
+            scope: "activity:write".to_string(),
+            granted: Some("read,activity:read_all".to_string()),
+        });
+        assert_eq!(error.code, MISSING_SCOPE);
+        assert!(error.message.ends_with("Call `authorize` with scope \"activity:read_all,activity:write\" to grant it."));
+        assert_eq!(error.data.unwrap()["kind"], "missing_scope");
+
+        let error = McpError::from(ToolError::InvalidInput("limit must be greater than 0".to_string()));
+        assert_eq!(error.code, ErrorCode::INVALID_PARAMS);
+        assert_eq!(error.message, "limit must be greater than 0");
+    }
+
+    #[test]
+    fn test_from_anyhow() {
+        let classified = anyhow::Error::new(ToolError::NotFound { resource: "activity".to_string() });
+        assert_eq!(ToolError::from(classified), ToolError::NotFound { resource: "activity".to_string() });
+        assert_eq!(ToolError::from(anyhow::anyhow!("disk full")), ToolError::Internal("disk full".to_string()));
+    }
+
+    #[test]
+    fn test_next_window() {
+        let now = DateTime::parse_from_rfc3339("2026-02-10T17:07:30Z").unwrap().with_timezone(&Utc);
+        assert_eq!(next_window(now).to_rfc3339(), "2026-02-10T17:15:00+00:00");
+    }
+}
@@ -0,0 +1,337 @@
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+use strava_api::StreamSet;
+
+/// Stream keys requested from Strava when building an export
+pub const EXPORT_STREAM_KEYS: &[&str] = &["time", "latlng", "altitude", "distance", "heartrate", "cadence"];
+
+/// A single recorded sample of an activity
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackPoint {
+    /// Latitude and longitude in degrees
+    pub position: Option<(f64, f64)>,
+    /// Altitude in meters
+    pub elevation: Option<f64>,
+    pub time: Option<DateTime<Utc>>,
+    /// Cumulative distance in meters
+    pub distance: Option<f64>,
+    pub heartrate: Option<u32>,
+    pub cadence: Option<u32>,
+}
+
+/// An activity's samples plus the metadata needed to write GPX/TCX files
+#[derive(Debug, Clone)]
+pub struct Track {
+    pub name: String,
+    pub sport_type: String,
+    pub start: DateTime<Utc>,
+    pub points: Vec<TrackPoint>,
+}
+
+impl Track {
+    /// Zip Strava's per-key streams into track points (streams may differ in length or be absent)
+    pub fn from_streams(name: &str, sport_type: &str, start: DateTime<Utc>, streams: &StreamSet) -> Self {
+        let time = streams.time.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+        let latlng = streams.latlng.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+        let altitude = streams.altitude.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+        let distance = streams.distance.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+        let heartrate = streams.heartrate.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+        let cadence = streams.cadence.as_ref().map(|s| s.data.as_slice()).unwrap_or_default();
+
+        let len = [time.len(), latlng.len(), altitude.len(), distance.len(), heartrate.len(), cadence.len()]
+            .into_iter()
+            .max()
+            .unwrap_or(0);
+
+        let points = (0..len)
+            .map(|i| TrackPoint {
+                position: latlng.get(i).map(|p| (p[0], p[1])),
+                elevation: altitude.get(i).copied(),
+                time: time.get(i).map(|&offset| start + Duration::seconds(offset as i64)),
+                distance: distance.get(i).copied(),
+                heartrate: heartrate.get(i).map(|&hr| hr as u32),
+                cadence: cadence.get(i).map(|&cad| cad as u32),
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            sport_type: sport_type.to_string(),
+            start,
+            points,
+        }
+    }
+
+    /// Whether any point carries a GPS position
+    pub fn has_positions(&self) -> bool {
+        self.points.iter().any(|p| p.position.is_some())
+    }
+
+    /// Render as a GPX 1.1 document, with heart rate and cadence in Garmin's TrackPointExtension
+    pub fn to_gpx(&self) -> Result<String, String> {
+        if !self.has_positions() {
+            return Err("Activity has no GPS data; GPX export requires a latlng stream".to_string());
+        }
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(concat!(
+            "<gpx version=\"1.1\" creator=\"strava-mcp\" ",
+            "xmlns=\"http://www.topografix.com/GPX/1/1\" ",
+            "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ",
+            "xmlns:gpxtpx=\"http://www.garmin.com/xmlschemas/TrackPointExtension/v1\" ",
+            "xsi:schemaLocation=\"http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd\">\n"
+        ));
+        out.push_str(&format!(
+            "  <metadata>\n    <name>{}</name>\n    <time>{}</time>\n  </metadata>\n",
+            xml_escape(&self.name),
+            format_time(self.start)
+        ));
+        out.push_str("  <trk>\n");
+        out.push_str(&format!("    <name>{}</name>\n", xml_escape(&self.name)));
+        out.push_str(&format!("    <type>{}</type>\n", xml_escape(&self.sport_type)));
+        out.push_str("    <trkseg>\n");
+
+        for point in &self.points {
+            // GPX track points must have a position; drop samples recorded before GPS lock
+            let Some((lat, lon)) = point.position else {
+                continue;
+            };
+
+            out.push_str(&format!("      <trkpt lat=\"{:.7}\" lon=\"{:.7}\">\n", lat, lon));
+            if let Some(ele) = point.elevation {
+                out.push_str(&format!("        <ele>{:.1}</ele>\n", ele));
+            }
+            if let Some(time) = point.time {
+                out.push_str(&format!("        <time>{}</time>\n", format_time(time)));
+            }
+            if point.heartrate.is_some() || point.cadence.is_some() {
+                out.push_str("        <extensions>\n          <gpxtpx:TrackPointExtension>\n");
+                if let Some(hr) = point.heartrate {
+                    out.push_str(&format!("            <gpxtpx:hr>{}</gpxtpx:hr>\n", hr));
+                }
+                if let Some(cad) = point.cadence {
+                    out.push_str(&format!("            <gpxtpx:cad>{}</gpxtpx:cad>\n", cad));
+                }
+                out.push_str("          </gpxtpx:TrackPointExtension>\n        </extensions>\n");
+            }
+            out.push_str("      </trkpt>\n");
+        }
+
+        out.push_str("    </trkseg>\n  </trk>\n</gpx>\n");
+        Ok(out)
+    }
+
+    /// Render as a Garmin Training Center (TCX v2) document with a single lap
+    pub fn to_tcx(&self) -> Result<String, String> {
+        // TCX requires a timestamp on every trackpoint
+        if !self.points.iter().any(|p| p.time.is_some()) {
+            return Err("Activity has no time stream; TCX export requires timestamps".to_string());
+        }
+
+        let total_seconds = self
+            .points
+            .iter()
+            .rev()
+            .find_map(|p| p.time)
+            .map(|t| (t - self.start).num_seconds().max(0))
+            .unwrap_or(0);
+        let total_distance = self.points.iter().rev().find_map(|p| p.distance).unwrap_or(0.0);
+        let heartrates: Vec<u32> = self.points.iter().filter_map(|p| p.heartrate).collect();
+
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str(concat!(
+            "<TrainingCenterDatabase ",
+            "xmlns=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2\" ",
+            "xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" ",
+            "xsi:schemaLocation=\"http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2 ",
+            "http://www.garmin.com/xmlschemas/TrainingCenterDatabasev2.xsd\">\n"
+        ));
+        out.push_str("  <Activities>\n");
+        out.push_str(&format!("    <Activity Sport=\"{}\">\n", tcx_sport(&self.sport_type)));
+        out.push_str(&format!("      <Id>{}</Id>\n", format_time(self.start)));
+        out.push_str(&format!("      <Lap StartTime=\"{}\">\n", format_time(self.start)));
+        out.push_str(&format!("        <TotalTimeSeconds>{}</TotalTimeSeconds>\n", total_seconds));
+        out.push_str(&format!("        <DistanceMeters>{:.1}</DistanceMeters>\n", total_distance));
+        out.push_str("        <Calories>0</Calories>\n");
+        if !heartrates.is_empty() {
+            let avg = heartrates.iter().sum::<u32>() / heartrates.len() as u32;
+            let max = heartrates.iter().max().copied().unwrap_or(avg);
+            out.push_str(&format!(
+                "        <AverageHeartRateBpm><Value>{}</Value></AverageHeartRateBpm>\n",
+                avg
+            ));
+            out.push_str(&format!(
+                "        <MaximumHeartRateBpm><Value>{}</Value></MaximumHeartRateBpm>\n",
+                max
+            ));
+        }
+        out.push_str("        <Intensity>Active</Intensity>\n");
+        out.push_str("        <TriggerMethod>Manual</TriggerMethod>\n");
+        out.push_str("        <Track>\n");
+
+        for point in &self.points {
+            let Some(time) = point.time else {
+                continue;
+            };
+
+            out.push_str("          <Trackpoint>\n");
+            out.push_str(&format!("            <Time>{}</Time>\n", format_time(time)));
+            if let Some((lat, lon)) = point.position {
+                out.push_str(&format!(
+                    "            <Position>\n              <LatitudeDegrees>{:.7}</LatitudeDegrees>\n              <LongitudeDegrees>{:.7}</LongitudeDegrees>\n            </Position>\n",
+                    lat, lon
+                ));
+            }
+            if let Some(ele) = point.elevation {
+                out.push_str(&format!("            <AltitudeMeters>{:.1}</AltitudeMeters>\n", ele));
+            }
+            if let Some(distance) = point.distance {
+                out.push_str(&format!("            <DistanceMeters>{:.1}</DistanceMeters>\n", distance));
+            }
+            if let Some(hr) = point.heartrate {
+                out.push_str(&format!(
+                    "            <HeartRateBpm><Value>{}</Value></HeartRateBpm>\n",
+                    hr
+                ));
+            }
+            if let Some(cad) = point.cadence {
+                // TCX cadence is limited to 0-254
+                out.push_str(&format!("            <Cadence>{}</Cadence>\n", cad.min(254)));
+            }
+            out.push_str("          </Trackpoint>\n");
+        }
+
+        out.push_str("        </Track>\n      </Lap>\n");
+        // Device_t requires a unit id, product id and version; this server has no device, so the ids are 0
+        out.push_str("      <Creator xsi:type=\"Device_t\">\n        <Name>strava-mcp</Name>\n");
+        out.push_str("        <UnitId>0</UnitId>\n        <ProductID>0</ProductID>\n");
+        out.push_str(&format!(
+            "        <Version>\n          <VersionMajor>{}</VersionMajor>\n          <VersionMinor>{}</VersionMinor>\n        </Version>\n",
+            env!("CARGO_PKG_VERSION_MAJOR"),
+            env!("CARGO_PKG_VERSION_MINOR")
+        ));
+        out.push_str("      </Creator>\n");
+        out.push_str("    </Activity>\n  </Activities>\n</TrainingCenterDatabase>\n");
+        Ok(out)
+    }
+}
+
+/// Map a Strava sport type to one of the three sports TCX understands
+fn tcx_sport(sport_type: &str) -> &'static str {
+    match sport_type {
+        "Run" | "TrailRun" | "VirtualRun" => "Running",
+        "Ride" | "VirtualRide" | "MountainBikeRide" | "GravelRide" | "EBikeRide" | "EMountainBikeRide" => "Biking",
+        _ => "Other",
+    }
+}
+
+/// Format a timestamp as ISO 8601 UTC with a trailing Z
+fn format_time(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Escape the five XML special characters
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sample_track() -> Track {
+        let start = Utc.with_ymd_and_hms(2026, 1, 15, 7, 30, 0).unwrap();
+        Track {
+            name: "Morning Run & Hills".to_string(),
+            sport_type: "Run".to_string(),
+            start,
+            points: vec![
+                TrackPoint {
+                    position: Some((52.5200, 13.4050)),
+                    elevation: Some(34.0),
+                    time: Some(start),
+                    distance: Some(0.0),
+                    heartrate: Some(120),
+                    cadence: Some(84),
+                },
+                TrackPoint {
+                    position: Some((52.5210, 13.4060)),
+                    elevation: Some(35.5),
+                    time: Some(start + Duration::seconds(30)),
+                    distance: Some(110.0),
+                    heartrate: Some(140),
+                    cadence: Some(88),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_gpx() {
+        let gpx = sample_track().to_gpx().unwrap();
+
+        assert!(gpx.contains("<gpx version=\"1.1\""));
+        assert!(gpx.contains("<name>Morning Run &amp; Hills</name>"));
+        assert!(gpx.contains("<trkpt lat=\"52.5200000\" lon=\"13.4050000\">"));
+        assert!(gpx.contains("<time>2026-01-15T07:30:30Z</time>"));
+        assert!(gpx.contains("<gpxtpx:hr>140</gpxtpx:hr>"));
+        assert!(gpx.contains("<gpxtpx:cad>88</gpxtpx:cad>"));
+        assert_eq!(gpx.matches("<trkpt ").count(), 2);
+    }
+
+    #[test]
+    fn test_to_gpx_without_positions() {
+        let mut track = sample_track();
+        for point in &mut track.points {
+            point.position = None;
+        }
+
+        assert!(track.to_gpx().is_err());
+        // TCX does not require GPS
+        assert!(track.to_tcx().is_ok());
+    }
+
+    #[test]
+    fn test_to_tcx() {
+        let tcx = sample_track().to_tcx().unwrap();
+
+        assert!(tcx.contains("<Activity Sport=\"Running\">"));
+        assert!(tcx.contains("<Id>2026-01-15T07:30:00Z</Id>"));
+        assert!(tcx.contains("<TotalTimeSeconds>30</TotalTimeSeconds>"));
+        assert!(tcx.contains("<DistanceMeters>110.0</DistanceMeters>"));
+        assert!(tcx.contains("<AverageHeartRateBpm><Value>130</Value></AverageHeartRateBpm>"));
+        assert!(tcx.contains("<MaximumHeartRateBpm><Value>140</Value></MaximumHeartRateBpm>"));
+        assert_eq!(tcx.matches("<Trackpoint>").count(), 2);
+        // Device_t's required children, in schema order
+        assert!(tcx.contains(
+            "<Creator xsi:type=\"Device_t\">\n        <Name>strava-mcp</Name>\n        <UnitId>0</UnitId>\n        <ProductID>0</ProductID>\n        <Version>\n          <VersionMajor>"
+        ));
+        assert!(tcx.contains("</VersionMinor>\n        </Version>\n      </Creator>\n"));
+    }
+
+    #[test]
+    fn test_tcx_sport() {
+        assert_eq!(tcx_sport("TrailRun"), "Running");
+        assert_eq!(tcx_sport("GravelRide"), "Biking");
+        assert_eq!(tcx_sport("Swim"), "Other");
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+        assert_eq!(xml_escape("plain"), "plain");
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+
+use crate::activity::Activity;
+use crate::profiles::Units;
+
+/// Default shoe retirement mileage, used unless STRAVA_SHOE_RETIREMENT_KM is set
+pub const DEFAULT_SHOE_RETIREMENT_KM: f64 = 700.0;
+
+/// Distance at which gear should be retired
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetirementThresholds {
+    pub shoe_km: f64,
+    /// Bikes are only flagged when a threshold is configured
+    pub bike_km: Option<f64>,
+}
+
+impl Default for RetirementThresholds {
+    fn default() -> Self {
+        Self {
+            shoe_km: DEFAULT_SHOE_RETIREMENT_KM,
+            bike_km: None,
+        }
+    }
+}
+
+impl RetirementThresholds {
+    /// Load thresholds from STRAVA_SHOE_RETIREMENT_KM and STRAVA_BIKE_RETIREMENT_KM
+    pub fn from_env() -> anyhow::Result<Self> {
+        let read = |name: &str| -> anyhow::Result<Option<f64>> {
+            match std::env::var(name) {
+                Ok(value) => {
+                    let km: f64 = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("{} must be a number of kilometers (got: {})", name, value))?;
+                    if km <= 0.0 {
+                        anyhow::bail!("{} must be greater than 0 (got: {})", name, km);
+                    }
+                    Ok(Some(km))
+                }
+                Err(_) => Ok(None),
+            }
+        };
+
+        Ok(Self {
+            shoe_km: read("STRAVA_SHOE_RETIREMENT_KM")?.unwrap_or(DEFAULT_SHOE_RETIREMENT_KM),
+            bike_km: read("STRAVA_BIKE_RETIREMENT_KM")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GearKind {
+    Shoe,
+    Bike,
+}
+
+/// A shoe or bike with its lifetime distance and recent use
+#[derive(Debug, Clone, PartialEq)]
+pub struct GearUsage {
+    pub id: String,
+    pub name: String,
+    pub kind: GearKind,
+    pub primary: bool,
+    /// Marked as retired on Strava; never flagged for retirement again
+    pub retired: bool,
+    /// Lifetime distance in meters as tracked by Strava
+    pub total_distance: f64,
+    /// Distance in meters within the recent window
+    pub recent_distance: f64,
+    pub recent_activities: usize,
+    /// Retirement threshold in kilometers, if one applies
+    pub threshold_km: Option<f64>,
+}
+
+impl GearUsage {
+    pub fn from_summary(gear: &strava_api::SummaryGear, kind: GearKind, thresholds: &RetirementThresholds) -> Self {
+        Self {
+            id: gear.id.clone(),
+            name: gear.name.clone(),
+            kind,
+            primary: gear.primary,
+            retired: gear.retired.unwrap_or(false),
+            total_distance: gear.distance,
+            recent_distance: 0.0,
+            recent_activities: 0,
+            threshold_km: match kind {
+                GearKind::Shoe => Some(thresholds.shoe_km),
+                GearKind::Bike => thresholds.bike_km,
+            },
+        }
+    }
+
+    /// Whether gear still in use has reached the retirement threshold
+    pub fn needs_retirement(&self) -> bool {
+        !self.retired
+            && self
+                .threshold_km
+                .is_some_and(|km| self.total_distance >= km * 1000.0)
+    }
+
+    /// Kilometers left before the threshold (negative once past it)
+    pub fn remaining_km(&self) -> Option<f64> {
+        self.threshold_km.map(|km| km - self.total_distance / 1000.0)
+    }
+}
+
+/// Add recent distance and activity counts to each piece of gear
+pub fn add_recent_usage(gear: &mut [GearUsage], recent: &[Activity]) {
+    let mut usage: HashMap<&str, (f64, usize)> = HashMap::new();
+    for activity in recent {
+        if let Some(gear_id) = activity.gear_id.as_deref() {
+            let entry = usage.entry(gear_id).or_default();
+            entry.0 += activity.distance;
+            entry.1 += 1;
+        }
+    }
+
+    for item in gear.iter_mut() {
+        if let Some(&(distance, count)) = usage.get(item.id.as_str()) {
+            item.recent_distance = distance;
+            item.recent_activities = count;
+        }
+    }
+}
+
+/// One warning line per piece of gear past its retirement threshold, with distances in `units`
+pub fn retirement_warnings(gear: &[GearUsage], units: Units) -> Vec<String> {
+    gear.iter()
+        .filter(|g| g.needs_retirement())
+        .map(|g| {
+            format!(
+                "{} has {}, past its {} retirement threshold",
+                g.name,
+                units.whole_distance(g.total_distance),
+                units.whole_distance(g.threshold_km.unwrap_or_default() * 1000.0)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn gear(id: &str, kind: GearKind, km: f64) -> GearUsage {
+        GearUsage {
+            id: id.to_string(),
+            name: format!("Gear {}", id),
+            kind,
+            primary: false,
+            retired: false,
+            total_distance: km * 1000.0,
+            recent_distance: 0.0,
+            recent_activities: 0,
+            threshold_km: match kind {
+                GearKind::Shoe => Some(700.0),
+                GearKind::Bike => None,
+            },
+        }
+    }
+
+    fn activity(gear_id: Option<&str>, distance: f64) -> Activity {
+        Activity {
+            gear_id: gear_id.map(str::to_string),
+            ..Activity::test_run(Utc::now().naive_utc(), distance, 1800)
+        }
+    }
+
+    #[test]
+    fn test_add_recent_usage() {
+        let mut items = vec![gear("g1", GearKind::Shoe, 100.0), gear("g2", GearKind::Shoe, 50.0)];
+        let recent = vec![
+            activity(Some("g1"), 10_000.0),
+            activity(Some("g1"), 5_000.0),
+            activity(None, 8_000.0),
+        ];
+
+        add_recent_usage(&mut items, &recent);
+
+        assert_eq!(items[0].recent_distance, 15_000.0);
+        assert_eq!(items[0].recent_activities, 2);
+        assert_eq!(items[1].recent_activities, 0);
+    }
+
+    #[test]
+    fn test_retirement() {
+        let worn = gear("g1", GearKind::Shoe, 712.0);
+        let fresh = gear("g2", GearKind::Shoe, 150.0);
+        let bike = gear("b1", GearKind::Bike, 20_000.0);
+        let retired = GearUsage { retired: true, ..gear("g3", GearKind::Shoe, 950.0) };
+
+        assert!(worn.needs_retirement());
+        assert!(!fresh.needs_retirement());
+        assert!(!bike.needs_retirement());
+        assert!(!retired.needs_retirement());
+        assert_eq!(fresh.remaining_km(), Some(550.0));
+        assert_eq!(bike.remaining_km(), None);
+
+        let gear = [worn, fresh, bike, retired];
+        let warnings = retirement_warnings(&gear, Units::Metric);
+        assert_eq!(warnings, vec!["Gear g1 has 712 km, past its 700 km retirement threshold"]);
+        let warnings = retirement_warnings(&gear, Units::Imperial);
+        assert_eq!(warnings, vec!["Gear g1 has 442 mi, past its 435 mi retirement threshold"]);
+    }
+}
@@ -0,0 +1,199 @@
+mod archive;
+mod fit;
+mod gpx;
+mod tcx;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Tz;
+use std::path::{Path, PathBuf};
+
+use crate::activity::Activity;
+use crate::export::Track;
+
+pub use archive::import_archive;
+
+/// Files larger than this are skipped (a long GPS activity is a few MB)
+pub const MAX_FILE_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Activity file formats the importer understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Fit,
+    Gpx,
+    Tcx,
+}
+
+impl FileFormat {
+    /// Detect the format from a file name's extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "fit" => Some(Self::Fit),
+            "gpx" => Some(Self::Gpx),
+            "tcx" => Some(Self::Tcx),
+            _ => None,
+        }
+    }
+}
+
+/// Result of importing a directory of activity files
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub activities: Vec<Activity>,
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Parse raw file contents into samples, naming untitled files by the time of day in `timezone`
+pub fn parse_track(format: FileFormat, bytes: &[u8], timezone: Tz) -> Result<Track> {
+    let track = match format {
+        FileFormat::Fit => fit::parse(bytes, timezone)?,
+        FileFormat::Gpx => gpx::parse(std::str::from_utf8(bytes).context("GPX file is not valid UTF-8")?, timezone)?,
+        FileFormat::Tcx => tcx::parse(std::str::from_utf8(bytes).context("TCX file is not valid UTF-8")?, timezone)?,
+    };
+
+    if track.points.is_empty() {
+        bail!("File contains no track points");
+    }
+
+    Ok(track)
+}
+
+/// Parse a single FIT, GPX or TCX file into an activity, with local times in `timezone`
+pub fn import_file(path: &Path, timezone: Tz) -> Result<Activity> {
+    let format = FileFormat::from_path(path)
+        .with_context(|| format!("Unsupported file type: {}", path.display()))?;
+
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_FILE_SIZE {
+        bail!("File is too large ({} bytes, max {})", size, MAX_FILE_SIZE);
+    }
+
+    let bytes = std::fs::read(path)?;
+    let track = parse_track(format, &bytes, timezone)?;
+
+    Ok(Activity::from_track(&track, path.to_path_buf(), timezone))
+}
+
+/// Recursively import every FIT, GPX and TCX file under a directory, with local times in `timezone`
+pub fn import_directory(dir: &Path, timezone: Tz) -> Result<ImportReport> {
+    if !dir.is_dir() {
+        bail!("Not a directory: {}", dir.display());
+    }
+
+    let mut report = ImportReport::default();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory: {}", current.display()))?;
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            // Not following directory symlinks, which could link back to a parent and loop forever
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if FileFormat::from_path(&path).is_some() {
+                match import_file(&path, timezone) {
+                    Ok(activity) => report.activities.push(activity),
+                    Err(e) => report.failures.push((path, format!("{:#}", e))),
+                }
+            }
+        }
+    }
+
+    report.activities.sort_by_key(|a| a.start_date);
+    Ok(report)
+}
+
+/// Map the sport names used by devices and file formats onto Strava sport types
+pub fn normalize_sport(raw: &str) -> String {
+    let normalized = raw.trim().to_ascii_lowercase().replace([' ', '-'], "_");
+    let sport = match normalized.as_str() {
+        "run" | "running" | "9" => "Run",
+        "trail_run" | "trailrun" | "trail_running" => "TrailRun",
+        "treadmill" | "treadmill_running" | "virtual_run" | "virtualrun" => "VirtualRun",
+        "ride" | "cycling" | "biking" | "bike" | "1" => "Ride",
+        "swim" | "swimming" => "Swim",
+        "walk" | "walking" => "Walk",
+        "hike" | "hiking" => "Hike",
+        _ => "Workout",
+    };
+    sport.to_string()
+}
+
+/// Strava-style default name ("Morning Run") for files without a title, by the start time in `timezone`
+pub fn default_name(sport_type: &str, start: DateTime<Utc>, timezone: Tz) -> String {
+    let period = match start.with_timezone(&timezone).hour() {
+        0..=4 => "Night",
+        5..=11 => "Morning",
+        12..=16 => "Afternoon",
+        17..=20 => "Evening",
+        _ => "Night",
+    };
+    let sport = match sport_type {
+        "TrailRun" => "Trail Run",
+        "VirtualRun" => "Virtual Run",
+        other => other,
+    };
+    format!("{} {}", period, sport)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(FileFormat::from_path(Path::new("a/run.FIT")), Some(FileFormat::Fit));
+        assert_eq!(FileFormat::from_path(Path::new("run.gpx")), Some(FileFormat::Gpx));
+        assert_eq!(FileFormat::from_path(Path::new("run.tcx")), Some(FileFormat::Tcx));
+        assert_eq!(FileFormat::from_path(Path::new("notes.txt")), None);
+        assert_eq!(FileFormat::from_path(Path::new("no_extension")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_import_directory_skips_symlink_loops() {
+        let dir = std::env::temp_dir().join(format!("strava-mcp-import-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("runs")).unwrap();
+        std::fs::write(
+            dir.join("runs/run.gpx"),
+            r#"<?xml version="1.0"?>
+<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1"><trk><trkseg>
+<trkpt lat="52.5200" lon="13.4050"><time>2026-01-15T07:30:00Z</time></trkpt>
+<trkpt lat="52.5290" lon="13.4050"><time>2026-01-15T07:35:00Z</time></trkpt>
+</trkseg></trk></gpx>"#,
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("..", dir.join("runs/up")).unwrap();
+        std::os::unix::fs::symlink(".", dir.join("self")).unwrap();
+
+        let report = import_directory(&dir, Tz::UTC).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.activities.len(), 1);
+        assert!(report.failures.is_empty());
+    }
+
+    #[test]
+    fn test_default_name() {
+        let start = Utc.with_ymd_and_hms(2026, 1, 15, 7, 30, 0).unwrap();
+        assert_eq!(default_name("Run", start, Tz::UTC), "Morning Run");
+        // 07:30 UTC is 23:30 the evening before on the US west coast
+        assert_eq!(default_name("TrailRun", start, Tz::America__Los_Angeles), "Night Trail Run");
+    }
+
+    #[test]
+    fn test_normalize_sport() {
+        assert_eq!(normalize_sport("running"), "Run");
+        assert_eq!(normalize_sport("Running"), "Run");
+        assert_eq!(normalize_sport("trail-running"), "TrailRun");
+        assert_eq!(normalize_sport("Biking"), "Ride");
+        assert_eq!(normalize_sport("9"), "Run");
+        assert_eq!(normalize_sport("Other"), "Workout");
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{bail, Context, Result};
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+use super::{parse_track, FileFormat, ImportReport, MAX_FILE_SIZE};
+use crate::activity::{Activity, Origin};
+
+/// Date format used by `activities.csv`, e.g. "Jan 15, 2026, 7:30:00 AM" (UTC)
+const ARCHIVE_DATE_FORMAT: &str = "%b %d, %Y, %I:%M:%S %p";
+
+/// Column positions in `activities.csv`.
+///
+/// Newer exports repeat some headers: the first "Distance" is in the athlete's
+/// display units, while a later one holds meters, so the last occurrence wins.
+#[derive(Debug)]
+struct Columns {
+    id: usize,
+    date: usize,
+    name: usize,
+    activity_type: usize,
+    filename: Option<usize>,
+    elapsed_time: Option<usize>,
+    moving_time: Option<usize>,
+    distance: Option<usize>,
+    /// Whether `distance` is the kilometer column of an older export
+    distance_in_km: bool,
+    elevation_gain: Option<usize>,
+    average_heartrate: Option<usize>,
+    max_heartrate: Option<usize>,
+}
+
+impl Columns {
+    fn from_headers(headers: &csv::StringRecord) -> Result<Self> {
+        let headers: Vec<&str> = headers.iter().collect();
+        let first = |name: &str| headers.iter().position(|h| *h == name);
+        let last = |name: &str| headers.iter().rposition(|h| *h == name);
+        let required = |name: &str| first(name).with_context(|| format!("activities.csv has no '{}' column", name));
+
+        let distance_columns = headers.iter().filter(|h| **h == "Distance").count();
+
+        Ok(Self {
+            id: required("Activity ID")?,
+            date: required("Activity Date")?,
+            name: required("Activity Name")?,
+            activity_type: required("Activity Type")?,
+            filename: first("Filename"),
+            elapsed_time: last("Elapsed Time"),
+            moving_time: last("Moving Time"),
+            distance: last("Distance"),
+            distance_in_km: distance_columns == 1,
+            elevation_gain: last("Elevation Gain"),
+            average_heartrate: last("Average Heart Rate"),
+            max_heartrate: last("Max Heart Rate"),
+        })
+    }
+}
+
+/// Import every activity listed in a Strava "download your data" archive.
+///
+/// Activities with a recorded file get splits and HR from the file; summary
+/// numbers come from `activities.csv` so they match what Strava shows. The
+/// export's times are UTC, and local times are taken in `timezone`.
+pub fn import_archive(path: &Path, timezone: Tz) -> Result<ImportReport> {
+    let file = File::open(path).with_context(|| format!("Failed to open archive: {}", path.display()))?;
+    let mut archive = ZipArchive::new(file).context("Not a valid ZIP archive")?;
+
+    let csv_bytes = read_entry(&mut archive, "activities.csv")
+        .context("Archive does not contain activities.csv; is this a Strava data export?")?;
+
+    let mut reader = csv::Reader::from_reader(csv_bytes.as_slice());
+    let columns = Columns::from_headers(reader.headers()?)?;
+
+    let mut report = ImportReport::default();
+
+    for (row, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                report.failures.push((PathBuf::from(format!("activities.csv row {}", row + 2)), e.to_string()));
+                continue;
+            }
+        };
+
+        let label = PathBuf::from(format!("activities.csv row {} ({})", row + 2, &record[columns.id]));
+        match import_row(&mut archive, path, &columns, &record, timezone) {
+            Ok(activity) => report.activities.push(activity),
+            Err(e) => report.failures.push((label, format!("{:#}", e))),
+        }
+    }
+
+    report.activities.sort_by_key(|a| a.start_date);
+    Ok(report)
+}
+
+fn import_row<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    archive_path: &Path,
+    columns: &Columns,
+    record: &csv::StringRecord,
+    timezone: Tz,
+) -> Result<Activity> {
+    let field = |index: Option<usize>| index.and_then(|i| record.get(i)).map(str::trim).filter(|v| !v.is_empty());
+    let number = |index: Option<usize>| field(index).and_then(|v| v.parse::<f64>().ok());
+
+    let id: i64 = record[columns.id].trim().parse().context("Invalid Activity ID")?;
+    let start_date = NaiveDateTime::parse_from_str(record[columns.date].trim(), ARCHIVE_DATE_FORMAT)
+        .with_context(|| format!("Invalid Activity Date '{}'", &record[columns.date]))?
+        .and_utc();
+    // "Weight Training" -> "WeightTraining", matching Strava sport types
+    let sport_type = record[columns.activity_type].replace(' ', "");
+
+    let mut activity = match field(columns.filename) {
+        Some(filename) => {
+            let (format, compressed) = entry_format(filename)?;
+            let mut bytes = read_entry(archive, filename)?;
+            if compressed {
+                bytes = gunzip(&bytes)?;
+            }
+            let track = parse_track(format, &bytes, timezone).with_context(|| format!("Failed to parse {}", filename))?;
+            Activity::from_track(&track, archive_path.to_path_buf(), timezone)
+        }
+        // Manual activities have no file
+        None => Activity {
+            id: None,
+            name: String::new(),
+            sport_type: String::new(),
+            start_date,
+            start_date_local: start_date.with_timezone(&timezone).naive_local(),
+            distance: 0.0,
+            moving_time: 0,
+            elapsed_time: 0,
+            total_elevation_gain: 0.0,
+            average_speed: None,
+            average_heartrate: None,
+            max_heartrate: None,
+            gear_id: None,
+            splits: Vec::new(),
+            origin: Origin::Archive(archive_path.to_path_buf()),
+        },
+    };
+
+    activity.id = Some(id);
+    activity.name = record[columns.name].to_string();
+    activity.sport_type = sport_type;
+    activity.start_date = start_date;
+    activity.start_date_local = start_date.with_timezone(&timezone).naive_local();
+    activity.origin = Origin::Archive(archive_path.to_path_buf());
+
+    if let Some(distance) = number(columns.distance) {
+        activity.distance = if columns.distance_in_km { distance * 1000.0 } else { distance };
+    }
+    if let Some(elapsed) = number(columns.elapsed_time) {
+        activity.elapsed_time = elapsed as i32;
+    }
+    if let Some(moving) = number(columns.moving_time) {
+        activity.moving_time = moving as i32;
+    } else if activity.moving_time == 0 {
+        activity.moving_time = activity.elapsed_time;
+    }
+    if let Some(elevation) = number(columns.elevation_gain) {
+        activity.total_elevation_gain = elevation;
+    }
+    if let Some(hr) = number(columns.average_heartrate) {
+        activity.average_heartrate = Some(hr);
+    }
+    if let Some(hr) = number(columns.max_heartrate) {
+        activity.max_heartrate = Some(hr);
+    }
+    if activity.moving_time > 0 && activity.distance > 0.0 {
+        activity.average_speed = Some(activity.distance / activity.moving_time as f64);
+    }
+
+    Ok(activity)
+}
+
+/// Format of an archive entry such as "activities/123.fit.gz"
+fn entry_format(filename: &str) -> Result<(FileFormat, bool)> {
+    let (name, compressed) = match filename.strip_suffix(".gz") {
+        Some(name) => (name, true),
+        None => (filename, false),
+    };
+
+    match FileFormat::from_path(Path::new(name)) {
+        Some(format) => Ok((format, compressed)),
+        None => bail!("Unsupported file type: {}", filename),
+    }
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let entry = archive
+        .by_name(name)
+        .with_context(|| format!("{} not found in archive", name))?;
+    read_limited(entry)
+}
+
+fn gunzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    read_limited(GzDecoder::new(bytes)).context("Failed to decompress")
+}
+
+/// Read at most MAX_FILE_SIZE bytes, so a malformed archive cannot exhaust memory
+fn read_limited(reader: impl Read) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_FILE_SIZE + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_FILE_SIZE {
+        bail!("File is too large (max {} bytes)", MAX_FILE_SIZE);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    const GPX: &str = r#"<?xml version="1.0"?>
+<gpx version="1.1" xmlns="http://www.topografix.com/GPX/1/1"><trk><trkseg>
+<trkpt lat="52.5200" lon="13.4050"><time>2026-01-15T07:30:00Z</time></trkpt>
+<trkpt lat="52.5290" lon="13.4050"><time>2026-01-15T07:35:00Z</time></trkpt>
+</trkseg></trk></gpx>"#;
+
+    fn write_archive(path: &Path) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("activities.csv", options).unwrap();
+        zip.write_all(
+            concat!(
+                "Activity ID,Activity Date,Activity Name,Activity Type,Elapsed Time,Distance,Filename,Elapsed Time,Moving Time,Distance,Elevation Gain\n",
+                "101,\"Jan 15, 2026, 7:30:00 AM\",Lunch Run,Run,320,1.00,activities/101.gpx.gz,320,300,1001.5,4\n",
+                "102,\"Jan 16, 2026, 6:00:00 PM\",Gym,Weight Training,3600,0,,3600,3600,0,0\n",
+                "103,not a date,Broken,Run,0,0,,0,0,0,0\n",
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(GPX.as_bytes()).unwrap();
+        zip.start_file("activities/101.gpx.gz", options).unwrap();
+        zip.write_all(&gz.finish().unwrap()).unwrap();
+
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_import_archive() {
+        let path = std::env::temp_dir().join(format!("strava-mcp-archive-{}.zip", std::process::id()));
+        write_archive(&path);
+
+        let report = import_archive(&path, Tz::America__Los_Angeles).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(report.activities.len(), 2);
+        assert_eq!(report.failures.len(), 1);
+
+        let run = &report.activities[0];
+        assert_eq!(run.id, Some(101));
+        assert_eq!(run.name, "Lunch Run");
+        assert_eq!(run.start_date, Utc.with_ymd_and_hms(2026, 1, 15, 7, 30, 0).unwrap());
+        // The export's UTC time is the previous evening in the athlete's timezone
+        assert_eq!(run.start_date_local.to_string(), "2026-01-14 23:30:00");
+        // Meters column wins over the display-unit column
+        assert_eq!(run.distance, 1001.5);
+        assert_eq!(run.moving_time, 300);
+        assert_eq!(run.elapsed_time, 320);
+        assert!(run.is_run());
+
+        let gym = &report.activities[1];
+        assert_eq!(gym.sport_type, "WeightTraining");
+        assert_eq!(gym.moving_time, 3600);
+        assert_eq!(gym.origin, Origin::Archive(path));
+    }
+
+    #[test]
+    fn test_entry_format() {
+        assert_eq!(entry_format("activities/1.fit.gz").unwrap(), (FileFormat::Fit, true));
+        assert_eq!(entry_format("activities/1.tcx").unwrap(), (FileFormat::Tcx, false));
+        assert!(entry_format("activities/1.jpg").is_err());
+    }
+}
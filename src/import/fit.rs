@@ -0,0 +1,368 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+use super::{default_name, normalize_sport};
+use crate::export::{Track, TrackPoint};
+
+/// Seconds between the Unix epoch and the FIT epoch (1989-12-31T00:00:00Z)
+const FIT_EPOCH_OFFSET: i64 = 631_065_600;
+
+/// Global message numbers from the FIT profile
+const MESG_SESSION: u16 = 18;
+const MESG_RECORD: u16 = 20;
+
+/// Field numbers shared by most messages
+const FIELD_TIMESTAMP: u8 = 253;
+
+/// Record message fields
+const RECORD_POSITION_LAT: u8 = 0;
+const RECORD_POSITION_LONG: u8 = 1;
+const RECORD_ALTITUDE: u8 = 2;
+const RECORD_HEART_RATE: u8 = 3;
+const RECORD_CADENCE: u8 = 4;
+const RECORD_DISTANCE: u8 = 5;
+const RECORD_ENHANCED_ALTITUDE: u8 = 78;
+
+/// Session message fields
+const SESSION_START_TIME: u8 = 2;
+const SESSION_SPORT: u8 = 5;
+const SESSION_SUB_SPORT: u8 = 6;
+
+#[derive(Debug, Clone)]
+struct FieldDefinition {
+    number: u8,
+    size: usize,
+    base_type: u8,
+}
+
+#[derive(Debug, Clone)]
+struct MessageDefinition {
+    global_number: u16,
+    big_endian: bool,
+    fields: Vec<FieldDefinition>,
+    /// Total size of developer fields, which are skipped
+    developer_size: usize,
+}
+
+/// Decoded numeric fields of one data message, keyed by field number
+type Fields = HashMap<u8, f64>;
+
+/// Parse a FIT activity file's record and session messages.
+///
+/// Only the handful of fields needed for summaries are decoded; everything
+/// else (laps, device info, developer fields) is skipped by size.
+pub fn parse(bytes: &[u8], timezone: Tz) -> Result<Track> {
+    let header_size = *bytes.first().context("Empty FIT file")? as usize;
+    if header_size < 12 || bytes.len() < header_size || &bytes[8..12] != b".FIT" {
+        bail!("Not a FIT file (missing .FIT signature)");
+    }
+
+    let data_size = u32::from_le_bytes(bytes[4..8].try_into()?) as usize;
+    let end = header_size
+        .checked_add(data_size)
+        .filter(|&end| end <= bytes.len())
+        .context("FIT file is truncated")?;
+
+    let mut definitions: HashMap<u8, MessageDefinition> = HashMap::new();
+    let mut points = Vec::new();
+    let mut session: Option<Fields> = None;
+    let mut last_timestamp: Option<u32> = None;
+    let mut pos = header_size;
+
+    while pos < end {
+        let header = bytes[pos];
+        pos += 1;
+
+        if header & 0x80 != 0 {
+            // Compressed timestamp header: a data message with a 5-bit time offset
+            let local_type = (header >> 5) & 0x03;
+            let offset = (header & 0x1F) as u32;
+            let definition = definitions
+                .get(&local_type)
+                .context("Data message before its definition")?;
+            let mut fields = read_fields(bytes, &mut pos, end, definition)?;
+
+            if let Some(previous) = last_timestamp {
+                let mut timestamp = (previous & !0x1F) | offset;
+                if offset < (previous & 0x1F) {
+                    timestamp += 0x20;
+                }
+                fields.insert(FIELD_TIMESTAMP, timestamp as f64);
+            }
+            handle_message(definition.global_number, fields, &mut points, &mut session, &mut last_timestamp);
+        } else if header & 0x40 != 0 {
+            let local_type = header & 0x0F;
+            let has_developer_fields = header & 0x20 != 0;
+            let definition = read_definition(bytes, &mut pos, end, has_developer_fields)?;
+            definitions.insert(local_type, definition);
+        } else {
+            let local_type = header & 0x0F;
+            let definition = definitions
+                .get(&local_type)
+                .context("Data message before its definition")?;
+            let fields = read_fields(bytes, &mut pos, end, definition)?;
+            handle_message(definition.global_number, fields, &mut points, &mut session, &mut last_timestamp);
+        }
+    }
+
+    let sport_type = session
+        .as_ref()
+        .map(|s| sport_name(s.get(&SESSION_SPORT).copied(), s.get(&SESSION_SUB_SPORT).copied()))
+        .unwrap_or("Workout");
+    let sport_type = normalize_sport(sport_type);
+
+    let start = session
+        .as_ref()
+        .and_then(|s| s.get(&SESSION_START_TIME).copied())
+        .and_then(|t| fit_time(t as u32))
+        .or_else(|| points.iter().find_map(|p: &TrackPoint| p.time))
+        .context("FIT file has no timestamps")?;
+
+    Ok(Track {
+        name: default_name(&sport_type, start, timezone),
+        sport_type,
+        start,
+        points,
+    })
+}
+
+fn handle_message(
+    global_number: u16,
+    fields: Fields,
+    points: &mut Vec<TrackPoint>,
+    session: &mut Option<Fields>,
+    last_timestamp: &mut Option<u32>,
+) {
+    if let Some(&timestamp) = fields.get(&FIELD_TIMESTAMP) {
+        *last_timestamp = Some(timestamp as u32);
+    }
+
+    match global_number {
+        MESG_RECORD => {
+            // Positions are stored in semicircles
+            let semicircles_to_degrees = |v: f64| v * (180.0 / 2_147_483_648.0);
+            let lat = fields.get(&RECORD_POSITION_LAT).copied().map(semicircles_to_degrees);
+            let lon = fields.get(&RECORD_POSITION_LONG).copied().map(semicircles_to_degrees);
+            let altitude = fields
+                .get(&RECORD_ENHANCED_ALTITUDE)
+                .or_else(|| fields.get(&RECORD_ALTITUDE))
+                .map(|&a| a / 5.0 - 500.0);
+
+            points.push(TrackPoint {
+                position: lat.zip(lon),
+                elevation: altitude,
+                time: fields.get(&FIELD_TIMESTAMP).and_then(|&t| fit_time(t as u32)),
+                distance: fields.get(&RECORD_DISTANCE).map(|&d| d / 100.0),
+                heartrate: fields.get(&RECORD_HEART_RATE).map(|&hr| hr as u32),
+                cadence: fields.get(&RECORD_CADENCE).map(|&cad| cad as u32),
+            });
+        }
+        // Multisport files have several sessions; the first one names the activity
+        MESG_SESSION if session.is_none() => *session = Some(fields),
+        _ => {}
+    }
+}
+
+fn read_definition(bytes: &[u8], pos: &mut usize, end: usize, has_developer_fields: bool) -> Result<MessageDefinition> {
+    let fixed = take(bytes, pos, end, 5)?;
+    let big_endian = fixed[1] == 1;
+    let global_number = if big_endian {
+        u16::from_be_bytes([fixed[2], fixed[3]])
+    } else {
+        u16::from_le_bytes([fixed[2], fixed[3]])
+    };
+    let field_count = fixed[4] as usize;
+
+    let fields = take(bytes, pos, end, field_count * 3)?
+        .chunks_exact(3)
+        .map(|f| FieldDefinition {
+            number: f[0],
+            size: f[1] as usize,
+            base_type: f[2],
+        })
+        .collect();
+
+    let mut developer_size = 0;
+    if has_developer_fields {
+        let count = take(bytes, pos, end, 1)?[0] as usize;
+        developer_size = take(bytes, pos, end, count * 3)?
+            .chunks_exact(3)
+            .map(|f| f[1] as usize)
+            .sum();
+    }
+
+    Ok(MessageDefinition {
+        global_number,
+        big_endian,
+        fields,
+        developer_size,
+    })
+}
+
+fn read_fields(bytes: &[u8], pos: &mut usize, end: usize, definition: &MessageDefinition) -> Result<Fields> {
+    let mut fields = Fields::new();
+    for field in &definition.fields {
+        let raw = take(bytes, pos, end, field.size)?;
+        if let Some(value) = decode_value(raw, field.base_type, definition.big_endian) {
+            fields.insert(field.number, value);
+        }
+    }
+    take(bytes, pos, end, definition.developer_size)?;
+    Ok(fields)
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, end: usize, len: usize) -> Result<&'a [u8]> {
+    let next = pos.checked_add(len).filter(|&next| next <= end).context("FIT message runs past end of data")?;
+    let slice = &bytes[*pos..next];
+    *pos = next;
+    Ok(slice)
+}
+
+/// Decode the first element of a field, returning None for FIT's per-type "invalid" sentinels
+fn decode_value(raw: &[u8], base_type: u8, big_endian: bool) -> Option<f64> {
+    let read = |len: usize| -> Option<u64> {
+        let bytes = raw.get(..len)?;
+        let mut buf = [0u8; 8];
+        if big_endian {
+            buf[8 - len..].copy_from_slice(bytes);
+            Some(u64::from_be_bytes(buf))
+        } else {
+            buf[..len].copy_from_slice(bytes);
+            Some(u64::from_le_bytes(buf))
+        }
+    };
+
+    match base_type & 0x1F {
+        // enum, uint8, byte
+        0x00 | 0x02 | 0x0D => read(1).filter(|&v| v != 0xFF).map(|v| v as f64),
+        0x01 => read(1).filter(|&v| v != 0x7F).map(|v| v as u8 as i8 as f64),
+        0x03 => read(2).filter(|&v| v != 0x7FFF).map(|v| v as u16 as i16 as f64),
+        0x04 => read(2).filter(|&v| v != 0xFFFF).map(|v| v as f64),
+        0x05 => read(4).filter(|&v| v != 0x7FFF_FFFF).map(|v| v as u32 as i32 as f64),
+        0x06 => read(4).filter(|&v| v != 0xFFFF_FFFF).map(|v| v as f64),
+        0x08 => read(4).map(|v| f32::from_bits(v as u32) as f64).filter(|v| v.is_finite()),
+        0x09 => read(8).map(f64::from_bits).filter(|v| v.is_finite()),
+        // uint8z, uint16z, uint32z, uint64z: zero is invalid
+        0x0A => read(1).filter(|&v| v != 0).map(|v| v as f64),
+        0x0B => read(2).filter(|&v| v != 0).map(|v| v as f64),
+        0x0C => read(4).filter(|&v| v != 0).map(|v| v as f64),
+        0x10 => read(8).filter(|&v| v != 0).map(|v| v as f64),
+        0x0E => read(8).filter(|&v| v != 0x7FFF_FFFF_FFFF_FFFF).map(|v| v as i64 as f64),
+        0x0F => read(8).filter(|&v| v != u64::MAX).map(|v| v as f64),
+        // Strings and unknown types are not needed
+        _ => None,
+    }
+}
+
+fn fit_time(seconds: u32) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(seconds as i64 + FIT_EPOCH_OFFSET, 0)
+}
+
+/// Name for the FIT sport/sub_sport enums, in a form `normalize_sport` understands
+fn sport_name(sport: Option<f64>, sub_sport: Option<f64>) -> &'static str {
+    match (sport.map(|s| s as u8), sub_sport.map(|s| s as u8)) {
+        (Some(1), Some(1)) => "treadmill",
+        (Some(1), Some(3)) => "trail_running",
+        (Some(1), _) => "running",
+        (Some(2), _) => "cycling",
+        (Some(5), _) => "swimming",
+        (Some(11), _) => "walking",
+        (Some(17), _) => "hiking",
+        _ => "workout",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Build a minimal FIT file: a session definition + message and two record messages
+    fn sample_fit() -> Vec<u8> {
+        let start = 1_137_000_000u32; // 2026-01-11T19:20:00Z in FIT time
+        let mut data = Vec::new();
+
+        // Definition, local type 0 -> session: start_time (uint32), sport (enum), sub_sport (enum)
+        data.extend([0x40, 0, 0]);
+        data.extend(MESG_SESSION.to_le_bytes());
+        data.extend([3, SESSION_START_TIME, 4, 0x86, SESSION_SPORT, 1, 0x00, SESSION_SUB_SPORT, 1, 0x00]);
+        data.push(0x00);
+        data.extend(start.to_le_bytes());
+        data.extend([1, 0]);
+
+        // Definition, local type 1 -> record: timestamp, lat, long, distance, heart_rate
+        data.extend([0x41, 0, 0]);
+        data.extend(MESG_RECORD.to_le_bytes());
+        data.extend([
+            5,
+            FIELD_TIMESTAMP, 4, 0x86,
+            RECORD_POSITION_LAT, 4, 0x85,
+            RECORD_POSITION_LONG, 4, 0x85,
+            RECORD_DISTANCE, 4, 0x86,
+            RECORD_HEART_RATE, 1, 0x02,
+        ]);
+        for (offset, distance, hr) in [(0u32, 0u32, 130u8), (60, 20_000, 0xFF)] {
+            data.push(0x01);
+            data.extend((start + offset).to_le_bytes());
+            data.extend(596_523_235i32.to_le_bytes()); // ~50 degrees
+            data.extend(0x7FFF_FFFFi32.to_le_bytes()); // invalid longitude
+            data.extend(distance.to_le_bytes());
+            data.push(hr);
+        }
+
+        // Compressed timestamp record (local type 1); offset 0 rolls over past start + 60
+        data.push(0x80 | (1 << 5));
+        data.extend([0u8; 4]);
+        data.extend(596_523_235i32.to_le_bytes());
+        data.extend(0x7FFF_FFFFi32.to_le_bytes());
+        data.extend(40_000u32.to_le_bytes());
+        data.push(140);
+
+        let mut file = vec![14, 0x10, 0, 0];
+        file.extend((data.len() as u32).to_le_bytes());
+        file.extend(b".FIT");
+        file.extend([0, 0]);
+        file.extend(data);
+        file.extend([0, 0]); // CRC (not verified)
+        file
+    }
+
+    #[test]
+    fn test_parse() {
+        let track = parse(&sample_fit(), Tz::UTC).unwrap();
+        let start = Utc.timestamp_opt(1_137_000_000 + FIT_EPOCH_OFFSET, 0).unwrap();
+
+        assert_eq!(track.sport_type, "Run");
+        assert_eq!(track.start, start);
+        assert_eq!(track.points.len(), 3);
+        assert_eq!(track.points[0].heartrate, Some(130));
+        assert_eq!(track.points[1].heartrate, None);
+        assert_eq!(track.points[1].distance, Some(200.0));
+        // Longitude is invalid, so no position is recorded
+        assert_eq!(track.points[0].position, None);
+        assert_eq!(track.points[2].time, Some(start + chrono::Duration::seconds(64)));
+        assert_eq!(track.points[2].distance, Some(400.0));
+    }
+
+    #[test]
+    fn test_rejects_non_fit() {
+        assert!(parse(b"", Tz::UTC).is_err());
+        assert!(parse(b"<gpx></gpx> padding padding", Tz::UTC).is_err());
+
+        // Truncated data section
+        let mut truncated = sample_fit();
+        truncated.truncate(30);
+        assert!(parse(&truncated, Tz::UTC).is_err());
+    }
+
+    #[test]
+    fn test_decode_value() {
+        assert_eq!(decode_value(&[0x34, 0x12], 0x84, false), Some(0x1234 as f64));
+        assert_eq!(decode_value(&[0x12, 0x34], 0x84, true), Some(0x1234 as f64));
+        assert_eq!(decode_value(&[0xFF, 0xFF], 0x84, false), None);
+        assert_eq!(decode_value(&[0xFE], 0x01, false), Some(-2.0));
+        assert_eq!(decode_value(&[0x00], 0x0A, false), None);
+    }
+}
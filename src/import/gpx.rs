@@ -0,0 +1,164 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use roxmltree::{Document, Node};
+
+use super::{default_name, normalize_sport};
+use crate::export::{Track, TrackPoint};
+
+/// Parse a GPX 1.0/1.1 file, including Garmin TrackPointExtension heart rate and cadence
+pub fn parse(text: &str, timezone: Tz) -> Result<Track> {
+    let doc = Document::parse(text).context("Invalid GPX XML")?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "gpx" {
+        anyhow::bail!("Not a GPX file (root element is <{}>)", root.tag_name().name());
+    }
+
+    let trk = child(root, "trk");
+    let metadata = child(root, "metadata");
+
+    let points: Vec<TrackPoint> = root
+        .descendants()
+        .filter(|n| n.has_tag_name_local("trkpt"))
+        .map(parse_point)
+        .collect::<Result<_>>()?;
+
+    let start = points
+        .iter()
+        .find_map(|p| p.time)
+        .or_else(|| metadata.and_then(|m| child_text(m, "time")).and_then(parse_time))
+        .context("GPX file has no timestamps")?;
+
+    let sport_type = trk
+        .and_then(|t| child_text(t, "type"))
+        .map(normalize_sport)
+        .unwrap_or_else(|| "Run".to_string());
+
+    let name = trk
+        .and_then(|t| child_text(t, "name"))
+        .or_else(|| metadata.and_then(|m| child_text(m, "name")))
+        .map(str::to_string)
+        .unwrap_or_else(|| default_name(&sport_type, start, timezone));
+
+    Ok(Track {
+        name,
+        sport_type,
+        start,
+        points,
+    })
+}
+
+fn parse_point(node: Node) -> Result<TrackPoint> {
+    let lat: f64 = node
+        .attribute("lat")
+        .context("trkpt missing lat")?
+        .parse()
+        .context("Invalid trkpt lat")?;
+    let lon: f64 = node
+        .attribute("lon")
+        .context("trkpt missing lon")?
+        .parse()
+        .context("Invalid trkpt lon")?;
+
+    // Extension elements live under vendor namespaces (gpxtpx, ns3, ...), so match on local name
+    let extension_value = |name: &str| {
+        node.descendants()
+            .find(|n| n.has_tag_name_local(name))
+            .and_then(|n| n.text())
+            .and_then(|t| t.trim().parse::<f64>().ok())
+    };
+
+    Ok(TrackPoint {
+        position: Some((lat, lon)),
+        elevation: child_text(node, "ele").and_then(|t| t.parse().ok()),
+        time: child_text(node, "time").and_then(parse_time),
+        distance: None,
+        heartrate: extension_value("hr").map(|hr| hr as u32),
+        cadence: extension_value("cad").map(|cad| cad as u32),
+    })
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+/// First child element with the given local name
+pub(super) fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name_local(name))
+}
+
+/// Trimmed text of the first child element with the given local name
+pub(super) fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Match element names regardless of XML namespace
+pub(super) trait LocalName {
+    fn has_tag_name_local(&self, name: &str) -> bool;
+}
+
+impl LocalName for Node<'_, '_> {
+    fn has_tag_name_local(&self, name: &str) -> bool {
+        self.is_element() && self.tag_name().name() == name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="Garmin Connect" xmlns="http://www.topografix.com/GPX/1/1"
+     xmlns:ns3="http://www.garmin.com/xmlschemas/TrackPointExtension/v1">
+  <metadata><time>2026-01-15T07:30:00Z</time></metadata>
+  <trk>
+    <name>Lunch Run</name>
+    <type>running</type>
+    <trkseg>
+      <trkpt lat="52.5200000" lon="13.4050000">
+        <ele>34.0</ele>
+        <time>2026-01-15T07:30:00Z</time>
+        <extensions><ns3:TrackPointExtension><ns3:hr>120</ns3:hr><ns3:cad>84</ns3:cad></ns3:TrackPointExtension></extensions>
+      </trkpt>
+      <trkpt lat="52.5210000" lon="13.4060000">
+        <ele>36.0</ele>
+        <time>2026-01-15T07:30:30Z</time>
+      </trkpt>
+    </trkseg>
+  </trk>
+</gpx>"#;
+
+    #[test]
+    fn test_parse() {
+        let track = parse(SAMPLE, Tz::UTC).unwrap();
+
+        assert_eq!(track.name, "Lunch Run");
+        assert_eq!(track.sport_type, "Run");
+        assert_eq!(track.start.to_rfc3339(), "2026-01-15T07:30:00+00:00");
+        assert_eq!(track.points.len(), 2);
+        assert_eq!(track.points[0].position, Some((52.52, 13.405)));
+        assert_eq!(track.points[0].elevation, Some(34.0));
+        assert_eq!(track.points[0].heartrate, Some(120));
+        assert_eq!(track.points[0].cadence, Some(84));
+        assert_eq!(track.points[1].heartrate, None);
+    }
+
+    #[test]
+    fn test_round_trip_with_export() {
+        let exported = parse(SAMPLE, Tz::UTC).unwrap().to_gpx().unwrap();
+        let track = parse(&exported, Tz::UTC).unwrap();
+
+        assert_eq!(track.name, "Lunch Run");
+        assert_eq!(track.points.len(), 2);
+        assert_eq!(track.points[0].heartrate, Some(120));
+    }
+
+    #[test]
+    fn test_rejects_other_xml() {
+        assert!(parse("<html></html>", Tz::UTC).is_err());
+        assert!(parse("not xml", Tz::UTC).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use roxmltree::{Document, Node};
+
+use super::gpx::{child, child_text, LocalName};
+use super::{default_name, normalize_sport};
+use crate::export::{Track, TrackPoint};
+
+/// Parse the first activity in a Garmin Training Center (TCX) file
+pub fn parse(text: &str, timezone: Tz) -> Result<Track> {
+    let doc = Document::parse(text).context("Invalid TCX XML")?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "TrainingCenterDatabase" {
+        anyhow::bail!("Not a TCX file (root element is <{}>)", root.tag_name().name());
+    }
+
+    let activity = root
+        .descendants()
+        .find(|n| n.has_tag_name_local("Activity"))
+        .context("TCX file contains no activity")?;
+
+    let points: Vec<TrackPoint> = activity
+        .descendants()
+        .filter(|n| n.has_tag_name_local("Trackpoint"))
+        .map(parse_point)
+        .collect();
+
+    let start = child_text(activity, "Id")
+        .and_then(parse_time)
+        .or_else(|| points.iter().find_map(|p| p.time))
+        .context("TCX activity has no start time")?;
+
+    let sport_type = normalize_sport(activity.attribute("Sport").unwrap_or("Running"));
+
+    let name = child_text(activity, "Notes")
+        .map(str::to_string)
+        .unwrap_or_else(|| default_name(&sport_type, start, timezone));
+
+    Ok(Track {
+        name,
+        sport_type,
+        start,
+        points,
+    })
+}
+
+fn parse_point(node: Node) -> TrackPoint {
+    let number = |parent: Node, name: &str| child_text(parent, name).and_then(|t| t.parse::<f64>().ok());
+
+    let position = child(node, "Position").and_then(|p| {
+        number(p, "LatitudeDegrees").zip(number(p, "LongitudeDegrees"))
+    });
+
+    // Running cadence is usually stored in the TPX extension rather than <Cadence>
+    let cadence = number(node, "Cadence").or_else(|| {
+        node.descendants()
+            .find(|n| n.has_tag_name_local("RunCadence"))
+            .and_then(|n| n.text())
+            .and_then(|t| t.trim().parse().ok())
+    });
+
+    TrackPoint {
+        position,
+        elevation: number(node, "AltitudeMeters"),
+        time: child_text(node, "Time").and_then(parse_time),
+        distance: number(node, "DistanceMeters"),
+        heartrate: child(node, "HeartRateBpm")
+            .and_then(|hr| number(hr, "Value"))
+            .map(|hr| hr as u32),
+        cadence: cadence.map(|cad| cad as u32),
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2"
+    xmlns:ns3="http://www.garmin.com/xmlschemas/ActivityExtension/v2">
+  <Activities>
+    <Activity Sport="Running">
+      <Id>2026-01-15T07:30:00Z</Id>
+      <Lap StartTime="2026-01-15T07:30:00Z">
+        <Track>
+          <Trackpoint>
+            <Time>2026-01-15T07:30:00Z</Time>
+            <DistanceMeters>0.0</DistanceMeters>
+            <HeartRateBpm><Value>118</Value></HeartRateBpm>
+            <Extensions><ns3:TPX><ns3:RunCadence>86</ns3:RunCadence></ns3:TPX></Extensions>
+          </Trackpoint>
+          <Trackpoint>
+            <Time>2026-01-15T07:31:00Z</Time>
+            <Position><LatitudeDegrees>52.52</LatitudeDegrees><LongitudeDegrees>13.405</LongitudeDegrees></Position>
+            <AltitudeMeters>35.2</AltitudeMeters>
+            <DistanceMeters>210.5</DistanceMeters>
+          </Trackpoint>
+        </Track>
+      </Lap>
+    </Activity>
+  </Activities>
+</TrainingCenterDatabase>"#;
+
+        let track = parse(text, Tz::UTC).unwrap();
+
+        assert_eq!(track.sport_type, "Run");
+        assert_eq!(track.start, Utc.with_ymd_and_hms(2026, 1, 15, 7, 30, 0).unwrap());
+        assert_eq!(track.points.len(), 2);
+        assert_eq!(track.points[0].heartrate, Some(118));
+        assert_eq!(track.points[0].cadence, Some(86));
+        assert_eq!(track.points[0].position, None);
+        assert_eq!(track.points[1].position, Some((52.52, 13.405)));
+        assert_eq!(track.points[1].distance, Some(210.5));
+    }
+
+    #[test]
+    fn test_round_trip_with_export() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap();
+        let original = Track {
+            name: "Treadmill".to_string(),
+            sport_type: "Ride".to_string(),
+            start,
+            points: vec![TrackPoint {
+                time: Some(start),
+                distance: Some(0.0),
+                heartrate: Some(100),
+                ..Default::default()
+            }],
+        };
+
+        let track = parse(&original.to_tcx().unwrap(), Tz::UTC).unwrap();
+
+        assert_eq!(track.sport_type, "Ride");
+        assert_eq!(track.start, start);
+        assert_eq!(track.points, original.points);
+    }
+}
@@ -0,0 +1,3956 @@
+//! Strava MCP server. The binary parses [`Cli`] and hands it to [`run`]; the integration tests in `tests/`
+//! build a [`StravaMcpServer`] directly against a local Strava stand-in.
+
+mod activity;
+mod auth;
+mod cli;
+pub mod config;
+mod description;
+mod error;
+mod export;
+pub mod gear;
+mod import;
+mod logging;
+mod plan;
+mod period;
+mod progress;
+pub mod profiles;
+mod routes;
+mod segments;
+mod source;
+mod store;
+pub mod token_file;
+mod totals;
+mod update;
+mod upload;
+mod utils;
+pub mod webhook;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use clap::Parser;
+use rmcp::{
+    handler::server::tool::{ToolCallContext, ToolRouter},
+    model::{
+        AnnotateAble, CallToolRequestParams, CallToolResult, Content, ListResourcesResult, ListToolsResult,
+        PaginatedRequestParams, RawResource, ReadResourceRequestParams, ReadResourceResult, ResourceContents,
+        ResourceUpdatedNotificationParam, ServerCapabilities, ServerInfo, SetLevelRequestParams, SubscribeRequestParams,
+        Tool, UnsubscribeRequestParams,
+    },
+    service::{Peer, RequestContext},
+    tool, tool_router, ErrorData as McpError, RoleServer, ServerHandler, ServiceExt,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use strava_api::{AuthenticatedClient, OAuthConfig};
+use tokio::io::{stdin, stdout};
+use tokio::sync::RwLock;
+use tracing::Instrument;
+use activity::Activity;
+use auth::PendingAuthorization;
+use config::Config;
+use error::ToolError;
+use export::{Track, EXPORT_STREAM_KEYS};
+use gear::{GearKind, GearUsage, RetirementThresholds};
+use period::{Period, PeriodKind};
+use profiles::{ProfilePaths, ProfileSettings, Units};
+use routes::Terrain;
+use segments::{EffortPoint, EffortTrend};
+use source::ActivitySource;
+use store::LocalStore;
+use token_file::{TokenFile, TokenKey};
+use upload::UploadOutcome;
+use totals::Totals;
+use utils::format_duration;
+use webhook::ObjectType;
+
+/// One athlete's token, locally imported activities and settings
+pub struct Profile {
+    name: String,
+    /// Strava app credentials, also used to manage webhook subscriptions
+    oauth: OAuthConfig,
+    auth_client: AuthenticatedClient,
+    /// Serves Strava data in place of the API when set (test fixtures)
+    source: Option<Arc<dyn ActivitySource>>,
+    token_file: TokenFile,
+    /// Access token last written to the token file, to detect refreshes
+    saved_access_token: tokio::sync::Mutex<Option<String>>,
+    /// Headless authorization started by `authorize` and finished by `complete_authorization`
+    pending_authorization: tokio::sync::Mutex<Option<PendingAuthorization>>,
+    store: RwLock<LocalStore>,
+    settings: ProfileSettings,
+}
+
+/// The MCP server: its tools and resources, and the profiles they act for
+#[derive(Clone)]
+pub struct StravaMcpServer {
+    config: Arc<Config>,
+    base_dir: PathBuf,
+    default_profile: String,
+    /// Encrypts token files when configured
+    token_key: Option<TokenKey>,
+    /// Profiles are loaded on first use
+    profiles: Arc<RwLock<HashMap<String, Arc<Profile>>>>,
+    gear_thresholds: RetirementThresholds,
+    /// Profile names by Strava athlete id, learned while routing webhook events
+    athletes: Arc<RwLock<HashMap<i64, String>>>,
+    /// Clients subscribed to resource updates, by resource URI
+    resource_subscribers: Arc<RwLock<HashMap<String, Peer<RoleServer>>>>,
+    tool_router: ToolRouter<Self>,
+}
+
+#[tool_router]
+impl StravaMcpServer {
+    pub fn new(
+        config: Config,
+        default_profile: Profile,
+        token_key: Option<TokenKey>,
+        gear_thresholds: RetirementThresholds,
+    ) -> Self {
+        let default_name = default_profile.name.clone();
+        let profiles = HashMap::from([(default_name.clone(), Arc::new(default_profile))]);
+
+        // Only register the tools the config enables
+        let mut tool_router = Self::tool_router();
+        for tool in tool_router.list_all() {
+            if !config.tool_enabled(&tool.name) {
+                tool_router.remove_route(&tool.name);
+            }
+        }
+
+        Self {
+            base_dir: config.cache_dir.clone(),
+            config: Arc::new(config),
+            default_profile: default_name,
+            token_key,
+            profiles: Arc::new(RwLock::new(profiles)),
+            gear_thresholds,
+            athletes: Arc::new(RwLock::new(HashMap::new())),
+            resource_subscribers: Arc::new(RwLock::new(HashMap::new())),
+            tool_router,
+        }
+    }
+
+
+    #[tool(description = "Get running activities for a specific date (YYYY-MM-DD format)")]
+    async fn get_runs_for_date(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetRunsForDateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0; // Extract inner value
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Day boundaries in the profile's timezone (UTC unless configured)
+        let period = profile.period(&params.date)?;
+        let (start, end) = profile.bounds(&period)?;
+
+        // Fetch activities
+        let activities = profile.fetch_activities(Some(start), Some(end)).await?;
+
+        // Filter for runs, or the configured sport
+        let runs: Vec<_> = activities.iter().filter(|a| profile.settings.matches_sport(a)).collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No runs found for {}",
+                period
+            ))]));
+        }
+
+        // Format output
+        let mut output = format!("# Runs for {}\n\n", period);
+
+        let mut total_distance = 0.0;
+        let mut total_time = 0i32;
+
+        for run in &runs {
+            output.push_str(&format!("## {}\n", run.name));
+            output.push_str(&format_run(run, profile.settings.units));
+            output.push('\n');
+
+            total_distance += run.distance;
+            total_time += run.moving_time;
+        }
+
+        // Add totals if multiple runs
+        if runs.len() > 1 {
+            output.push_str("## Totals\n");
+            output.push_str(&format!("- **Runs:** {}\n", runs.len()));
+            output.push_str(&format!(
+                "- **Total Distance:** {}\n",
+                profile.settings.units.distance(total_distance)
+            ));
+            output.push_str(&format!(
+                "- **Total Time:** {}\n",
+                format_duration(total_time)
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get the most recent running activities")]
+    async fn get_recent_runs(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetRecentRunsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Validate and bound the limit parameter (prevent DoS)
+        let max_limit = self.config.max_limit;
+        let limit = params.limit.unwrap_or(self.config.default_limit);
+
+        if limit == 0 {
+            return Err(McpError::invalid_params_no_data("limit must be greater than 0"));
+        }
+
+        if limit > max_limit {
+            return Err(McpError::invalid_params_no_data(format!(
+                "limit cannot exceed {} (requested: {})",
+                max_limit, limit
+            )));
+        }
+
+        // Fetch only as many pages as it takes to find `limit` runs
+        let activities = profile.fetch_recent_activities(limit).await?;
+
+        // Filter for runs and take limit, newest first
+        let runs: Vec<_> = activities
+            .iter()
+            .rev()
+            .filter(|a| profile.settings.matches_sport(a))
+            .take(limit)
+            .collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No recent runs found".to_string(),
+            )]));
+        }
+
+        // Format output
+        let mut output = format!("# {} Most Recent Runs\n\n", runs.len());
+
+        for run in runs {
+            // Parse local date for display
+            let date = run.date();
+
+            output.push_str(&format!("## {} ({})\n", run.name, date));
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(run.distance)
+            ));
+            output.push_str(&format!(
+                "- **Duration:** {}\n",
+                format_duration(run.moving_time)
+            ));
+            if let Some(avg_speed) = run.average_speed {
+                output.push_str(&format!(
+                    "- **Pace:** {}\n",
+                    profile.settings.units.pace(avg_speed)
+                ));
+            }
+
+            if let Some(hr) = run.average_heartrate {
+                output.push_str(&format!("- **Average HR:** {:.0} bpm\n", hr));
+            }
+
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get weekly running summary (defaults to current week)")]
+    async fn get_weekly_summary(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetWeeklySummaryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // The current week unless given; a date starts a seven-day week on that day
+        let week = match params.week_start.as_deref() {
+            Some(input) => match profile.period(input)? {
+                period if period.kind == PeriodKind::Week => period,
+                period if period.kind == PeriodKind::Day => Period::week_starting(period.start),
+                period => {
+                    return Err(ToolError::InvalidInput(format!(
+                        "week_start must be a date or a week such as 2026-W07 or last week (got: {})",
+                        period
+                    ))
+                    .into())
+                }
+            },
+            None => Period::week_of(profile.settings.today()),
+        };
+        let week_start = week.start;
+
+        // Week boundaries in the profile's timezone (UTC unless configured)
+        let (week_start_timestamp, week_end_timestamp) = profile.bounds(&week)?;
+
+        // Fetch activities
+        let activities = profile
+            .fetch_activities(Some(week_start_timestamp), Some(week_end_timestamp))
+            .await?;
+
+        // Filter for runs, or the configured sport
+        let runs: Vec<_> = activities.iter().filter(|a| profile.settings.matches_sport(a)).collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No runs found for week starting {}",
+                week_start
+            ))]));
+        }
+
+        // Calculate aggregates
+        let totals: Totals = runs.iter().copied().collect();
+
+        // Format output
+        let week_end = week_start + Duration::days(6);
+        let mut output = format!("# Weekly Summary: {} to {}\n\n", week_start, week_end);
+
+        output.push_str(&format_totals(&totals, profile.settings.units));
+
+        // Flag worn-out shoes unless turned off; gear is optional context, so lookup failures are ignored
+        if params.gear_warnings.unwrap_or(true) {
+            if let Ok(gear) = profile.fetch_gear(&self.gear_thresholds).await {
+                for warning in gear::retirement_warnings(&gear, profile.settings.units) {
+                    output.push_str(&format!("- **Warning:** {}\n", warning));
+                }
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get the athlete's shoes and bikes with total distance, recent use and retirement alerts")]
+    async fn get_gear_report(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetGearReportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Validate the recent-use window
+        const MAX_DAYS: u32 = 365;
+        const DEFAULT_DAYS: u32 = 30;
+        let days = params.days.unwrap_or(DEFAULT_DAYS);
+
+        if days == 0 || days > MAX_DAYS {
+            return Err(McpError::invalid_params_no_data(format!(
+                "days must be between 1 and {} (requested: {})",
+                MAX_DAYS, days
+            )));
+        }
+
+        let mut thresholds = self.gear_thresholds;
+        if let Some(km) = params.shoe_threshold_km {
+            if km <= 0.0 {
+                return Err(McpError::invalid_params_no_data(format!(
+                    "shoe_threshold_km must be greater than 0 (requested: {})",
+                    km
+                )));
+            }
+            thresholds.shoe_km = km;
+        }
+
+        let mut gear = profile.fetch_gear(&thresholds).await?;
+
+        if gear.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No gear found on your Strava profile".to_string(),
+            )]));
+        }
+
+        // Recent use comes from the activities' gear_id
+        let now = Utc::now().timestamp();
+        let recent = profile
+            .fetch_activities(Some(now - days as i64 * 86400), Some(now))
+            .await?;
+        gear::add_recent_usage(&mut gear, &recent);
+
+        // Format output
+        let mut output = "# Gear Report\n\n".to_string();
+
+        for (kind, heading) in [(GearKind::Shoe, "Shoes"), (GearKind::Bike, "Bikes")] {
+            let items: Vec<_> = gear.iter().filter(|g| g.kind == kind).collect();
+            if items.is_empty() {
+                continue;
+            }
+
+            output.push_str(&format!("## {}\n\n", heading));
+            for item in items {
+                let primary = if item.primary { " (primary)" } else { "" };
+                let retired = if item.retired { " (retired)" } else { "" };
+                output.push_str(&format!("### {}{}{}\n", item.name, primary, retired));
+                output.push_str(&format!(
+                    "- **Total Distance:** {}\n",
+                    profile.settings.units.distance(item.total_distance)
+                ));
+                output.push_str(&format!(
+                    "- **Last {} Days:** {} over {} activities\n",
+                    days,
+                    profile.settings.units.distance(item.recent_distance),
+                    item.recent_activities
+                ));
+                if let (false, Some(threshold), Some(remaining)) =
+                    (item.retired, item.threshold_km, item.remaining_km())
+                {
+                    if item.needs_retirement() {
+                        output.push_str(&format!(
+                            "- **Retirement:** past the {} threshold by {}, consider replacing\n",
+                            profile.settings.units.whole_distance(threshold * 1000.0),
+                            profile.settings.units.whole_distance(-remaining * 1000.0)
+                        ));
+                    } else {
+                        output.push_str(&format!(
+                            "- **Retirement:** {} left of {}\n",
+                            profile.settings.units.whole_distance(remaining * 1000.0),
+                            profile.settings.units.whole_distance(threshold * 1000.0)
+                        ));
+                    }
+                }
+                output.push('\n');
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get running activities for a specific month (YYYY-MM format)")]
+    async fn get_runs_for_month(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetRunsForMonthParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Month boundaries in the profile's timezone (UTC unless configured)
+        let period = profile.period(&params.month)?;
+        let (start_timestamp, end_timestamp) = profile.bounds(&period)?;
+
+        // Fetch activities for the month
+        let activities = profile
+            .fetch_activities(Some(start_timestamp), Some(end_timestamp))
+            .await?;
+
+        // Filter for runs, or the configured sport
+        let runs: Vec<_> = activities.iter().filter(|a| profile.settings.matches_sport(a)).collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No runs found for {}",
+                period
+            ))]));
+        }
+
+        // Format output
+        let mut output = format!("# Runs for {}\n\n", period);
+        output.push_str(&format!("Found {} runs\n\n", runs.len()));
+
+        for run in &runs {
+            // Parse local date for display
+            let date = run.date();
+
+            output.push_str(&format!("## {} ({})\n", run.name, date));
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(run.distance)
+            ));
+            output.push_str(&format!(
+                "- **Duration:** {}\n",
+                format_duration(run.moving_time)
+            ));
+            if let Some(avg_speed) = run.average_speed {
+                output.push_str(&format!(
+                    "- **Pace:** {}\n",
+                    profile.settings.units.pace(avg_speed)
+                ));
+            }
+            output.push_str(&format!(
+                "- **Elevation Gain:** {}\n",
+                profile.settings.units.elevation(run.total_elevation_gain)
+            ));
+
+            if let Some(hr) = run.average_heartrate {
+                output.push_str(&format!("- **Average Heart Rate:** {:.0} bpm\n", hr));
+            }
+
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get monthly running summary with aggregated statistics")]
+    async fn get_monthly_running_summary(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetMonthlyRunningSummaryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Month boundaries in the profile's timezone (UTC unless configured)
+        let period = profile.period(&params.month)?;
+        let (start_timestamp, end_timestamp) = profile.bounds(&period)?;
+
+        // Fetch activities for the month
+        let activities = profile
+            .fetch_activities(Some(start_timestamp), Some(end_timestamp))
+            .await?;
+
+        // Filter for runs, or the configured sport
+        let runs: Vec<_> = activities.iter().filter(|a| profile.settings.matches_sport(a)).collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No runs found for {}",
+                period
+            ))]));
+        }
+
+        // Calculate aggregates
+        let totals: Totals = runs.iter().copied().collect();
+
+        // Format output
+        let mut output = match period.kind {
+            PeriodKind::Month => format!("# Monthly Running Summary: {}\n\n", period),
+            _ => format!("# Running Summary: {}\n\n", period),
+        };
+
+        output.push_str(&format_totals(&totals, profile.settings.units));
+
+        // Calculate some additional statistics
+        let avg_distance = totals.distance / totals.count as f64;
+        let avg_time = totals.moving_time / totals.count as i32;
+
+        output.push_str("\n## Averages per Run\n");
+        output.push_str(&format!(
+            "- **Average Distance:** {}\n",
+            profile.settings.units.distance(avg_distance)
+        ));
+        output.push_str(&format!(
+            "- **Average Duration:** {}\n",
+            format_duration(avg_time)
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get the authorized athlete's Strava profile")]
+    async fn get_athlete_profile(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.athlete.as_deref()).await?;
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let athlete = client.get_logged_in_athlete().await.map_err(ToolError::from)?;
+
+        // Format output
+        let mut output = format!("# {}\n\n", athlete_display_name(&athlete));
+
+        output.push_str(&format!("- **Athlete ID:** {}\n", athlete.id));
+        if let Some(username) = &athlete.username {
+            output.push_str(&format!("- **Username:** {}\n", username));
+        }
+
+        let location: Vec<&str> = [&athlete.city, &athlete.state, &athlete.country]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if !location.is_empty() {
+            output.push_str(&format!("- **Location:** {}\n", location.join(", ")));
+        }
+
+        if let Some(created_at) = &athlete.created_at {
+            let since = created_at.split('T').next().unwrap_or(created_at);
+            output.push_str(&format!("- **Member Since:** {}\n", since));
+        }
+        if let Some(premium) = athlete.premium {
+            output.push_str(&format!(
+                "- **Subscription:** {}\n",
+                if premium { "Premium" } else { "Free" }
+            ));
+        }
+        if let Some(preference) = &athlete.measurement_preference {
+            output.push_str(&format!("- **Units:** {}\n", preference));
+        }
+        if let Some(weight) = athlete.weight.filter(|w| *w > 0.0) {
+            output.push_str(&format!("- **Weight:** {:.1} kg\n", weight));
+        }
+
+        let shoes = athlete.shoes.as_ref().map_or(0, Vec::len);
+        let bikes = athlete.bikes.as_ref().map_or(0, Vec::len);
+        output.push_str(&format!("- **Gear:** {} shoes, {} bikes\n", shoes, bikes));
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get the athlete's recent (last 4 weeks), year-to-date and all-time run, ride and swim totals")]
+    async fn get_athlete_stats(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.athlete.as_deref()).await?;
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        // Stats are looked up by athlete ID
+        let athlete = client.get_logged_in_athlete().await.map_err(ToolError::from)?;
+        let stats = client
+            .get_athlete_stats(athlete.id)
+            .await
+            .map_err(ToolError::from)?;
+
+        // Format output
+        let mut output = format!("# Stats for {}\n\n", athlete_display_name(&athlete));
+
+        let periods = [
+            (
+                "Last 4 Weeks",
+                [&stats.recent_run_totals, &stats.recent_ride_totals, &stats.recent_swim_totals],
+            ),
+            (
+                "Year to Date",
+                [&stats.ytd_run_totals, &stats.ytd_ride_totals, &stats.ytd_swim_totals],
+            ),
+            (
+                "All Time",
+                [&stats.all_run_totals, &stats.all_ride_totals, &stats.all_swim_totals],
+            ),
+        ];
+
+        for (period, totals) in periods {
+            output.push_str(&format!("## {}\n", period));
+            for (sport, total) in ["Runs", "Rides", "Swims"].into_iter().zip(totals) {
+                output.push_str(&format_activity_total(sport, total, profile.settings.units));
+            }
+            output.push('\n');
+        }
+
+        output.push_str("## Records\n");
+        if let Some(distance) = stats.biggest_ride_distance.filter(|d| *d > 0.0) {
+            output.push_str(&format!(
+                "- **Biggest Ride:** {}\n",
+                profile.settings.units.distance(distance)
+            ));
+        }
+        if let Some(elevation) = stats.biggest_climb_elevation_gain.filter(|e| *e > 0.0) {
+            output.push_str(&format!("- **Biggest Climb:** {}\n", profile.settings.units.elevation(elevation)));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "List the athlete's starred segments with their personal records")]
+    async fn list_starred_segments(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ListStarredSegmentsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Validate and bound the limit parameter (prevent DoS)
+        const MAX_LIMIT: u32 = 200;
+        const DEFAULT_LIMIT: u32 = 30;
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(McpError::invalid_params_no_data(format!(
+                "limit must be between 1 and {} (requested: {})",
+                MAX_LIMIT, limit
+            )));
+        }
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let segments = client
+            .get_logged_in_athlete_starred_segments(1, limit)
+            .await
+            .map_err(ToolError::from)?;
+
+        if segments.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No starred segments found".to_string(),
+            )]));
+        }
+
+        // Format output
+        let mut output = format!("# {} Starred Segments\n\n", segments.len());
+
+        for segment in &segments {
+            output.push_str(&format!("## {} (ID: {})\n", segment.name, segment.id));
+            output.push_str(&format!(
+                "- **Type:** {}\n",
+                segment.activity_type
+            ));
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(segment.distance)
+            ));
+            output.push_str(&format!(
+                "- **Average Grade:** {:.1}%\n",
+                segment.average_grade
+            ));
+            output.push_str(&format!(
+                "- **Elevation Difference:** {}\n",
+                profile.settings.units.elevation(segment.elevation_high - segment.elevation_low)
+            ));
+
+            if let Some(pr) = &segment.athlete_pr_effort {
+                if let Some(time) = pr.pr_elapsed_time {
+                    output.push_str(&format!("- **Your PR:** {}\n", format_duration(time)));
+                }
+                if let Some(count) = pr.effort_count {
+                    output.push_str(&format!("- **Your Efforts:** {}\n", count));
+                }
+            }
+
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get a segment's details and the athlete's personal record on it")]
+    async fn get_segment(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetSegmentParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let segment_id = params.segment_id;
+        validate_id("segment_id", segment_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let segment = client
+            .get_segment_by_id(segment_id)
+            .await
+            .map_err(ToolError::from)?;
+
+        // Format output
+        let mut output = format!("# {}\n\n", segment.name);
+
+        output.push_str(&format!("- **Type:** {}\n", segment.activity_type));
+        output.push_str(&format!(
+            "- **Distance:** {}\n",
+            profile.settings.units.distance(segment.distance)
+        ));
+        output.push_str(&format!(
+            "- **Average Grade:** {:.1}% (max {:.1}%)\n",
+            segment.average_grade, segment.maximum_grade
+        ));
+        output.push_str(&format!(
+            "- **Elevation:** {} to {}\n",
+            profile.settings.units.elevation(segment.elevation_low),
+            profile.settings.units.elevation(segment.elevation_high)
+        ));
+        if let Some(gain) = segment.total_elevation_gain {
+            output.push_str(&format!("- **Elevation Gain:** {}\n", profile.settings.units.elevation(gain)));
+        }
+        if segment.climb_category > 0 {
+            output.push_str(&format!("- **Climb Category:** {}\n", segment.climb_category));
+        }
+
+        let location: Vec<&str> = [&segment.city, &segment.state, &segment.country]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .filter(|part| !part.is_empty())
+            .collect();
+        if !location.is_empty() {
+            output.push_str(&format!("- **Location:** {}\n", location.join(", ")));
+        }
+        if let (Some(efforts), Some(athletes)) = (segment.effort_count, segment.athlete_count) {
+            output.push_str(&format!(
+                "- **Popularity:** {} efforts by {} athletes\n",
+                efforts, athletes
+            ));
+        }
+
+        output.push_str("\n## Your Record\n");
+        match &segment.athlete_segment_stats {
+            Some(stats) if stats.effort_count.unwrap_or(0) > 0 => {
+                if let Some(time) = stats.pr_elapsed_time {
+                    output.push_str(&format!("- **PR:** {}\n", format_duration(time)));
+                }
+                if let Some(date) = &stats.pr_date {
+                    output.push_str(&format!("- **PR Date:** {}\n", date));
+                }
+                if let Some(count) = stats.effort_count {
+                    output.push_str(&format!("- **Efforts:** {}\n", count));
+                }
+            }
+            _ => output.push_str("- No efforts yet\n"),
+        }
+
+        // Strava removed segment leaderboards from its public API
+        output.push_str("\nFull leaderboards are not available through the Strava API; ");
+        output.push_str("use `get_segment_efforts` for your KOM/QOM rank on recent efforts (top 10 only).\n");
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get the athlete's full effort history on a segment with the trend over time")]
+    async fn get_segment_efforts(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetSegmentEffortsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        validate_id("segment_id", params.segment_id)?;
+
+        // Optional start date bound
+        let start_date_local = match &params.since {
+            Some(since) => Some(format!("{}T00:00:00Z", profile.period(since)?.start)),
+            None => None,
+        };
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        // Page through the full history (bounded to prevent runaway requests)
+        const PER_PAGE: u32 = 200;
+        const MAX_PAGES: u32 = 10;
+        let mut efforts = Vec::new();
+        for page in 1..=MAX_PAGES {
+            let batch = client
+                .get_efforts_by_segment_id(
+                    params.segment_id,
+                    start_date_local.as_deref(),
+                    None,
+                    page,
+                    PER_PAGE,
+                )
+                .await
+                .map_err(ToolError::from)?;
+            let done = batch.len() < PER_PAGE as usize;
+            efforts.extend(batch);
+            progress::report(format!("Fetched page {} ({} efforts)", page, efforts.len())).await;
+            if done {
+                break;
+            }
+        }
+
+        if efforts.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No efforts found on segment {}",
+                params.segment_id
+            ))]));
+        }
+
+        let points: Vec<EffortPoint> = efforts
+            .iter()
+            .filter_map(|e| {
+                let start = DateTime::parse_from_rfc3339(&e.start_date_local).ok()?;
+                Some(EffortPoint {
+                    start_date_local: start.naive_local(),
+                    elapsed_time: e.elapsed_time,
+                })
+            })
+            .collect();
+
+        // Format output
+        let mut output = format!("# Effort History: {}\n\n", efforts[0].name);
+
+        if let Some(trend) = EffortTrend::from_efforts(&points) {
+            output.push_str(&format!("- **Efforts:** {}\n", trend.count));
+            output.push_str(&format!(
+                "- **Best:** {} ({})\n",
+                format_duration(trend.best.elapsed_time),
+                trend.best.start_date_local.date()
+            ));
+            output.push_str(&format!(
+                "- **Latest:** {} ({})\n",
+                format_duration(trend.latest.elapsed_time),
+                trend.latest.start_date_local.date()
+            ));
+            output.push_str(&format!(
+                "- **Average:** {}\n",
+                format_duration(trend.average.round() as i32)
+            ));
+            output.push_str(&format!("- **Trend:** {}\n", trend.describe()));
+            if let Some(change) = trend.recent_vs_early {
+                let direction = if change <= 0.0 { "faster" } else { "slower" };
+                output.push_str(&format!(
+                    "- **Recent vs Early:** last 3 efforts average {} {} than the first 3\n",
+                    format_duration(change.abs().round() as i32),
+                    direction
+                ));
+            }
+        }
+
+        // Most recent first, capped to keep output readable
+        const MAX_LISTED: usize = 50;
+        let mut listed: Vec<_> = efforts.iter().collect();
+        listed.sort_by(|a, b| b.start_date_local.cmp(&a.start_date_local));
+
+        output.push_str(&format!("\n## Efforts (most recent {})\n", listed.len().min(MAX_LISTED)));
+        for effort in listed.into_iter().take(MAX_LISTED) {
+            let date = effort.start_date_local.split('T').next().unwrap_or("Unknown");
+            output.push_str(&format!("- {}: {}", date, format_duration(effort.elapsed_time)));
+            if effort.elapsed_time > 0 {
+                output.push_str(&format!(
+                    " ({})",
+                    profile.settings.units.pace(effort.distance / effort.elapsed_time as f64)
+                ));
+            }
+            if let Some(hr) = effort.average_heartrate {
+                output.push_str(&format!(", {:.0} bpm", hr));
+            }
+            if let Some(rank) = effort.pr_rank {
+                output.push_str(&format!(", PR #{}", rank));
+            }
+            if let Some(rank) = effort.kom_rank {
+                output.push_str(&format!(", KOM/QOM #{}", rank));
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "List the segment efforts recorded in an activity")]
+    async fn get_activity_segment_efforts(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ActivityIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let activity_id = params.activity_id;
+        validate_id("activity_id", activity_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        // Segment efforts are only included with the detailed activity
+        let activity = client
+            .get_activity(activity_id, true)
+            .await
+            .map_err(ToolError::from)?;
+
+        let efforts = activity.segment_efforts.unwrap_or_default();
+
+        if efforts.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No segment efforts found in {}",
+                activity.name
+            ))]));
+        }
+
+        // Format output
+        let mut output = format!("# Segment Efforts: {}\n\n", activity.name);
+
+        for effort in &efforts {
+            match &effort.segment {
+                Some(segment) => output.push_str(&format!("## {} (ID: {})\n", effort.name, segment.id)),
+                None => output.push_str(&format!("## {}\n", effort.name)),
+            }
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(effort.distance)
+            ));
+            output.push_str(&format!(
+                "- **Time:** {}\n",
+                format_duration(effort.elapsed_time)
+            ));
+            if effort.elapsed_time > 0 {
+                output.push_str(&format!(
+                    "- **Pace:** {}\n",
+                    profile.settings.units.pace(effort.distance / effort.elapsed_time as f64)
+                ));
+            }
+            if let Some(segment) = &effort.segment {
+                output.push_str(&format!("- **Average Grade:** {:.1}%\n", segment.average_grade));
+            }
+            if let Some(hr) = effort.average_heartrate {
+                output.push_str(&format!("- **Average Heart Rate:** {:.0} bpm\n", hr));
+            }
+            if let Some(rank) = effort.pr_rank {
+                output.push_str(&format!("- **Personal Rank:** #{}\n", rank));
+            }
+            if let Some(rank) = effort.kom_rank {
+                output.push_str(&format!("- **KOM/QOM Rank:** #{}\n", rank));
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "List the athlete's saved routes")]
+    async fn list_routes(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ListRoutesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Validate and bound the limit parameter (prevent DoS)
+        const MAX_LIMIT: u32 = 200;
+        const DEFAULT_LIMIT: u32 = 30;
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(McpError::invalid_params_no_data(format!(
+                "limit must be between 1 and {} (requested: {})",
+                MAX_LIMIT, limit
+            )));
+        }
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        // Routes are listed by athlete ID
+        let athlete = client.get_logged_in_athlete().await.map_err(ToolError::from)?;
+        let routes = client
+            .get_routes_by_athlete_id(athlete.id, 1, limit)
+            .await
+            .map_err(ToolError::from)?;
+
+        if routes.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No saved routes found".to_string(),
+            )]));
+        }
+
+        // Format output
+        let mut output = format!("# {} Saved Routes\n\n", routes.len());
+
+        for route in &routes {
+            let terrain = Terrain::from_route(route.route_type, route.sub_type);
+            output.push_str(&format!("## {} (ID: {})\n", route.name, route.id));
+            output.push_str(&format!("- **Type:** {}\n", terrain.label()));
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(route.distance)
+            ));
+            if let Some(gain) = route.elevation_gain {
+                output.push_str(&format!("- **Elevation Gain:** {}\n", profile.settings.units.elevation(gain)));
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Describe a saved route with distance, elevation and an estimated time based on the athlete's recent pace on similar terrain")]
+    async fn get_route(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<RouteIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let route_id = params.route_id;
+        validate_id("route_id", route_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let route = client
+            .get_route_by_id(route_id)
+            .await
+            .map_err(ToolError::from)?;
+
+        let terrain = Terrain::from_route(route.route_type, route.sub_type);
+        let elevation_gain = route.elevation_gain.unwrap_or(0.0);
+
+        // Recent pace comes from the last 90 days
+        const PACE_WINDOW_DAYS: i64 = 90;
+        let now = Utc::now().timestamp();
+        let recent = profile
+            .fetch_activities(Some(now - PACE_WINDOW_DAYS * 86400), Some(now))
+            .await?;
+        let estimate = routes::estimate_moving_time(route.distance, elevation_gain, terrain, &recent);
+
+        // Format output
+        let mut output = format!("# {}\n\n", route.name);
+
+        if let Some(description) = route.description.as_deref().filter(|d| !d.is_empty()) {
+            output.push_str(&format!("{}\n\n", description));
+        }
+
+        output.push_str(&format!("- **Type:** {}\n", terrain.label()));
+        output.push_str(&format!(
+            "- **Distance:** {}\n",
+            profile.settings.units.distance(route.distance)
+        ));
+        output.push_str(&format!("- **Elevation Gain:** {}\n", profile.settings.units.elevation(elevation_gain)));
+
+        match estimate {
+            Some(estimate) => {
+                let basis = if estimate.terrain_matched {
+                    format!("{} recent {} activities", estimate.based_on, terrain.label().to_lowercase())
+                } else {
+                    format!("{} recent runs (none on this terrain)", estimate.based_on)
+                };
+                output.push_str(&format!(
+                    "- **Estimated Time:** {} (based on {})\n",
+                    format_duration(estimate.moving_time),
+                    basis
+                ));
+                if terrain != Terrain::Ride && route.distance > 0.0 {
+                    output.push_str(&format!(
+                        "- **Estimated Pace:** {}\n",
+                        profile.settings.units.pace(route.distance / estimate.moving_time as f64)
+                    ));
+                }
+            }
+            None => {
+                if let Some(time) = route.estimated_moving_time {
+                    output.push_str(&format!(
+                        "- **Estimated Time:** {} (Strava estimate; no recent activities to base your pace on)\n",
+                        format_duration(time)
+                    ));
+                }
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Export a saved route as a GPX file")]
+    async fn export_route_gpx(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<RouteIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let route_id = params.route_id;
+        validate_id("route_id", route_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let gpx = client
+            .get_route_as_gpx(route_id)
+            .await
+            .map_err(ToolError::from)?;
+
+        Ok(CallToolResult::success(vec![Content::text(gpx)]))
+    }
+
+    #[tool(description = "List the clubs the athlete is a member of")]
+    async fn list_clubs(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.athlete.as_deref()).await?;
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let clubs = client
+            .get_logged_in_athlete_clubs(1, 200)
+            .await
+            .map_err(ToolError::from)?;
+
+        if clubs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "Not a member of any clubs".to_string(),
+            )]));
+        }
+
+        // Format output
+        let mut output = format!("# {} Clubs\n\n", clubs.len());
+
+        for club in &clubs {
+            output.push_str(&format!("## {} (ID: {})\n", club.name, club.id));
+            if let Some(sport) = &club.sport_type {
+                output.push_str(&format!("- **Sport:** {}\n", sport));
+            }
+            if let Some(members) = club.member_count {
+                output.push_str(&format!("- **Members:** {}\n", members));
+            }
+            let location = [&club.city, &club.state, &club.country]
+                .into_iter()
+                .filter_map(|part| part.as_deref())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(", ");
+            if !location.is_empty() {
+                output.push_str(&format!("- **Location:** {}\n", location));
+            }
+            if club.private == Some(true) {
+                output.push_str("- **Private:** yes\n");
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Get recent activities by members of a club")]
+    async fn get_club_activities(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetClubActivitiesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        validate_id("club_id", params.club_id)?;
+
+        // Validate and bound the limit parameter (prevent DoS)
+        const MAX_LIMIT: u32 = 200;
+        const DEFAULT_LIMIT: u32 = 30;
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(McpError::invalid_params_no_data(format!(
+                "limit must be between 1 and {} (requested: {})",
+                MAX_LIMIT, limit
+            )));
+        }
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let activities = client
+            .get_club_activities_by_id(params.club_id, 1, limit)
+            .await
+            .map_err(ToolError::from)?;
+
+        if activities.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No recent club activities found".to_string(),
+            )]));
+        }
+
+        // Format output
+        let mut output = format!("# {} Recent Club Activities\n\n", activities.len());
+
+        for activity in &activities {
+            output.push_str(&format!(
+                "## {} — {}\n",
+                club_athlete_name(&activity.athlete),
+                activity.name
+            ));
+            output.push_str(&format!("- **Type:** {}\n", activity.sport_type));
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(activity.distance)
+            ));
+            output.push_str(&format!(
+                "- **Moving Time:** {}\n",
+                format_duration(activity.moving_time)
+            ));
+            output.push_str(&format!(
+                "- **Elevation Gain:** {}\n",
+                profile.settings.units.elevation(activity.total_elevation_gain)
+            ));
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Rank club members by running distance and time over the club's 200 most recent feed activities. Strava's club feed has no dates, so this is not a weekly ranking, and members sharing a first name and last initial are counted as one")]
+    async fn get_club_recent_leaderboard(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<GetClubRecentLeaderboardParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let club_id = params.club_id;
+        validate_id("club_id", club_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        // Strava's club feed has no dates, so only the most recent page can be ranked
+        const FEED_SIZE: u32 = 200;
+        let activities = client
+            .get_club_activities_by_id(club_id, 1, FEED_SIZE)
+            .await
+            .map_err(ToolError::from)?;
+
+        let runs: Vec<_> = activities
+            .iter()
+            .filter(|a| activity::is_run_type(&a.sport_type))
+            .collect();
+
+        if runs.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No recent runs found for this club".to_string(),
+            )]));
+        }
+
+        // Calculate aggregates per athlete
+        let by_distance = totals::leaderboard(
+            runs.iter().copied(),
+            |a| club_athlete_name(&a.athlete),
+            |a| (a.distance, a.moving_time, a.total_elevation_gain),
+        );
+        let mut by_time = by_distance.clone();
+        by_time.sort_by(|a, b| b.1.moving_time.cmp(&a.1.moving_time).then_with(|| a.0.cmp(&b.0)));
+
+        let mut club_totals = Totals::default();
+        for run in &runs {
+            club_totals.add(run.distance, run.moving_time, run.total_elevation_gain);
+        }
+
+        // Format output
+        let mut output = "# Club Recent Leaderboard\n\n".to_string();
+        output.push_str(&format!(
+            "Based on the club's {} most recent activities (Strava's club feed does not include dates). Members are named by first name and last initial, so two members with the same name are counted together.\n\n",
+            activities.len()
+        ));
+        output.push_str(&format_totals(&club_totals, profile.settings.units));
+        output.push_str(&format!("- **Athletes:** {}\n", by_distance.len()));
+
+        output.push_str("\n## By Distance\n");
+        for (rank, (name, totals)) in by_distance.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. **{}** — {} ({} runs, {})\n",
+                rank + 1,
+                name,
+                profile.settings.units.distance(totals.distance),
+                totals.count,
+                profile.settings.units.pace(totals.average_speed())
+            ));
+        }
+
+        output.push_str("\n## By Time\n");
+        for (rank, (name, totals)) in by_time.iter().enumerate() {
+            output.push_str(&format!(
+                "{}. **{}** — {} ({} elevation)\n",
+                rank + 1,
+                name,
+                format_duration(totals.moving_time),
+                profile.settings.units.elevation(totals.elevation_gain)
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Update an activity's name, description, sport type, workout type, gear or commute/trainer/hide-from-home flags. Use dry_run to preview the changes. Requires the activity:write scope")]
+    async fn update_activity(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<UpdateActivityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        validate_id("activity_id", params.activity_id)?;
+        let dry_run = params.dry_run.unwrap_or(false);
+
+        // Validate text fields (prevent DoS)
+        const MAX_NAME_LENGTH: usize = 255;
+        const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+        if let Some(name) = &params.name {
+            if name.trim().is_empty() || name.len() > MAX_NAME_LENGTH {
+                return Err(McpError::invalid_params_no_data(format!(
+                    "name must be between 1 and {} characters",
+                    MAX_NAME_LENGTH
+                )));
+            }
+        }
+        if params.description.as_ref().is_some_and(|d| d.len() > MAX_DESCRIPTION_LENGTH) {
+            return Err(McpError::invalid_params_no_data(format!(
+                "description must be at most {} characters",
+                MAX_DESCRIPTION_LENGTH
+            )));
+        }
+        if let Some(sport_type) = &params.sport_type {
+            if sport_type.is_empty() || !sport_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(McpError::invalid_params_no_data(format!(
+                    "Invalid sport_type '{}' (expected a Strava sport type such as Run, TrailRun or Ride)",
+                    sport_type
+                )));
+            }
+        }
+
+        // Check the token can write before touching anything
+        let write_scope = profile.check_write_scope().await;
+        if !dry_run {
+            write_scope.clone()?;
+        }
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let current = client
+            .get_activity(params.activity_id, false)
+            .await
+            .map_err(ToolError::from)?;
+
+        // Workout type codes depend on the sport, so use the new one if it is changing
+        let sport_type = params.sport_type.as_deref().unwrap_or(&current.sport_type);
+        let workout_type = params
+            .workout_type
+            .as_deref()
+            .map(|name| update::workout_type_code(name, sport_type))
+            .transpose()
+            .map_err(McpError::invalid_params_no_data)?;
+
+        let changes_requested = strava_api::UpdatableActivity {
+            name: params.name.map(|n| n.trim().to_string()),
+            description: params.description,
+            sport_type: params.sport_type,
+            workout_type,
+            gear_id: params.gear_id,
+            commute: params.commute,
+            trainer: params.trainer,
+            hide_from_home: params.hide_from_home,
+        };
+
+        let changes = update::changes(&current, &changes_requested);
+
+        if changes.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "No changes to make to {} (ID: {})",
+                current.name, current.id
+            ))]));
+        }
+
+        // Format output
+        let mut output = if dry_run {
+            format!("# Dry Run: {} (ID: {})\n\n", current.name, current.id)
+        } else {
+            client
+                .update_activity_by_id(params.activity_id, &changes_requested)
+                .await
+                .map_err(ToolError::from)?;
+            format!("# Updated {} (ID: {})\n\n", current.name, current.id)
+        };
+
+        for change in &changes {
+            output.push_str(&format!(
+                "- **{}:** {} → {}\n",
+                change.field, change.from, change.to
+            ));
+        }
+
+        if dry_run {
+            output.push_str("\nNo changes were made. Run again without dry_run to apply them.\n");
+            if let Err(e) = write_scope {
+                output.push_str(&format!("- **Warning:** {}\n", e.message));
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Compose a description for an activity from its splits, heart rate zones, weekly context, PRs and training plan, and optionally save it to Strava (requires the activity:write scope)")]
+    async fn describe_activity(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<DescribeActivityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        validate_id("activity_id", params.activity_id)?;
+        let write = params.write.unwrap_or(false);
+
+        if write {
+            if self.config.read_only {
+                return Err(McpError::invalid_params_no_data(
+                    "The server is in read-only mode; descriptions can be composed but not saved to Strava",
+                ));
+            }
+            profile.check_write_scope().await?;
+        }
+
+        // Training plan is optional context; an explicit path must exist
+        let plan_path = params
+            .plan_path
+            .map(PathBuf::from)
+            .or_else(|| std::env::var("STRAVA_TRAINING_PLAN").ok().map(PathBuf::from));
+        let sessions = match &plan_path {
+            Some(path) => plan::load(path).map_err(|e| McpError::invalid_params_no_data(format!("{:#}", e)))?,
+            None => Vec::new(),
+        };
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let detailed = client
+            .get_activity(params.activity_id, true)
+            .await
+            .map_err(ToolError::from)?;
+        let activity = Activity::try_from(&detailed).map_err(McpError::internal)?;
+
+        let mut parts = description::DescriptionParts {
+            units: profile.settings.units,
+            ..Default::default()
+        };
+
+        // Heart rate zones are optional context, so lookup failures are ignored
+        if activity.average_heartrate.is_some() {
+            // Zones from the profile's settings take precedence over Strava's
+            let ranges = match profile.settings.heart_rate_zones() {
+                Some(zones) => Some(zones),
+                None => client
+                    .get_logged_in_athlete_zones()
+                    .await
+                    .ok()
+                    .and_then(|z| z.heart_rate)
+                    .map(|hr| hr.zones.iter().map(|z| (z.min, z.max)).collect::<Vec<_>>()),
+            };
+            let streams = client
+                .get_activity_streams(params.activity_id, &["time", "heartrate"])
+                .await
+                .ok();
+            if let (Some(ranges), Some(streams)) = (ranges, streams) {
+                if let (Some(time), Some(heartrate)) = (streams.time, streams.heartrate) {
+                    let seconds = description::time_in_zones(&time.data, &heartrate.data, &ranges);
+                    parts.zones = seconds
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, s)| (format!("Z{}", i + 1), s))
+                        .collect();
+                }
+            }
+        }
+
+        // PRs and top-3 efforts from best efforts (5k, 10k, ...) and segments
+        let rank_label = |rank: i32| match rank {
+            1 => "PR".to_string(),
+            2 => "2nd best".to_string(),
+            _ => "3rd best".to_string(),
+        };
+        for effort in detailed.best_efforts.iter().flatten() {
+            if let Some(rank) = effort.pr_rank.filter(|r| (1..=3).contains(r)) {
+                parts.achievements.push(format!(
+                    "{}: {} in {}",
+                    rank_label(rank),
+                    effort.name,
+                    format_duration(effort.elapsed_time)
+                ));
+            }
+        }
+        for effort in detailed.segment_efforts.iter().flatten() {
+            if effort.pr_rank == Some(1) {
+                parts.achievements.push(format!(
+                    "Segment PR: {} in {}",
+                    effort.name,
+                    format_duration(effort.elapsed_time)
+                ));
+            }
+        }
+
+        // Week so far, Monday to this activity
+        if activity.is_run() {
+            // Week boundaries in the profile's timezone, as the other period tools use
+            let (week_start, _) = profile.bounds(&Period::week_of(activity.date()))?;
+            let week = profile
+                .fetch_activities(Some(week_start), Some(activity.start_date.timestamp() + 1))
+                .await?;
+            parts.week = Some(description::WeekContext::new(&activity, &week));
+        }
+
+        parts.adherence = plan::Adherence::for_activity(&activity, &sessions);
+
+        let generated = description::compose(&activity, &parts);
+
+        // Keep anything the athlete wrote, replacing text generated by an earlier run
+        let existing = detailed.description.as_deref().unwrap_or("").trim();
+        let new_description = description::merge(existing, &generated);
+
+        // Format output
+        let mut output = format!("# {} (ID: {})\n\n", activity.name, detailed.id);
+        output.push_str(&format_run(&activity, profile.settings.units));
+        output.push_str(&format!("\n## Description\n\n{}\n\n", generated));
+
+        if write {
+            client
+                .update_activity_by_id(
+                    params.activity_id,
+                    &strava_api::UpdatableActivity {
+                        description: Some(new_description),
+                        ..Default::default()
+                    },
+                )
+                .await
+                .map_err(ToolError::from)?;
+            if existing.contains(description::BLOCK_START) {
+                output.push_str("Saved to Strava, replacing the previously generated text.\n");
+            } else if existing.is_empty() {
+                output.push_str("Saved to Strava.\n");
+            } else {
+                output.push_str("Saved to Strava below the existing description.\n");
+            }
+        } else {
+            output.push_str("Not saved. Run again with write set to true to save it to Strava.\n");
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Create a manual activity without a GPS file, e.g. a treadmill run or strength session. Requires the activity:write scope")]
+    async fn create_manual_activity(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<CreateManualActivityParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // Validate text fields (prevent DoS)
+        const MAX_NAME_LENGTH: usize = 255;
+        const MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+        let name = params.name.trim();
+        if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+            return Err(McpError::invalid_params_no_data(format!(
+                "name must be between 1 and {} characters",
+                MAX_NAME_LENGTH
+            )));
+        }
+        if params.description.as_ref().is_some_and(|d| d.len() > MAX_DESCRIPTION_LENGTH) {
+            return Err(McpError::invalid_params_no_data(format!(
+                "description must be at most {} characters",
+                MAX_DESCRIPTION_LENGTH
+            )));
+        }
+        if params.sport_type.is_empty() || !params.sport_type.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(McpError::invalid_params_no_data(format!(
+                "Invalid sport_type '{}' (expected a Strava sport type such as Run or WeightTraining)",
+                params.sport_type
+            )));
+        }
+
+        let start = upload::parse_start(&params.start_date_local).map_err(McpError::invalid_params_no_data)?;
+        let elapsed_time = upload::parse_duration(&params.duration).map_err(McpError::invalid_params_no_data)?;
+        if elapsed_time == 0 {
+            return Err(McpError::invalid_params_no_data("duration must be greater than 0"));
+        }
+
+        let distance = match params.distance_km {
+            Some(km) if !(0.0..=1000.0).contains(&km) => {
+                return Err(McpError::invalid_params_no_data(format!(
+                    "distance_km must be between 0 and 1000 (got: {})",
+                    km
+                )));
+            }
+            Some(km) => Some(km * 1000.0),
+            None => None,
+        };
+
+        profile.check_write_scope().await?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let activity = client
+            .create_activity(&strava_api::NewActivity {
+                name: name.to_string(),
+                sport_type: params.sport_type,
+                start_date_local: start.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+                elapsed_time,
+                description: params.description,
+                distance,
+                trainer: params.trainer,
+                commute: None,
+            })
+            .await
+            .map_err(ToolError::from)?;
+
+        // Format output
+        let mut output = format!("# Created {} (ID: {})\n\n", activity.name, activity.id);
+        output.push_str(&format!("- **Type:** {}\n", activity.sport_type));
+        output.push_str(&format!("- **Start:** {}\n", start.format("%Y-%m-%d %H:%M")));
+        output.push_str(&format!(
+            "- **Duration:** {}\n",
+            format_duration(activity.elapsed_time)
+        ));
+        if activity.distance > 0.0 {
+            output.push_str(&format!(
+                "- **Distance:** {}\n",
+                profile.settings.units.distance(activity.distance)
+            ));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Upload a FIT, GPX or TCX file (optionally .gz) to Strava and wait for it to be processed. Requires the activity:write scope")]
+    async fn upload_activity_file(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<UploadActivityFileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let path = PathBuf::from(&params.path);
+
+        let data_type = upload::data_type(&path).ok_or_else(|| {
+            McpError::invalid_params_no_data(format!(
+                "Unsupported file type: {} (expected .fit, .gpx or .tcx, optionally gzipped)",
+                path.display()
+            ))
+        })?;
+
+        // Check file size before reading (prevent DoS)
+        let size = std::fs::metadata(&path)
+            .map_err(|e| McpError::invalid_params_no_data(format!("Cannot read {}: {}", path.display(), e)))?
+            .len();
+        if size > import::MAX_FILE_SIZE {
+            return Err(McpError::invalid_params_no_data(format!(
+                "File is too large ({} bytes, max {})",
+                size,
+                import::MAX_FILE_SIZE
+            )));
+        }
+
+        profile.check_write_scope().await?;
+
+        let file = tokio::fs::read(&path).await.map_err(McpError::internal)?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = profile.client().await?;
+
+        let mut status = client
+            .upload_activity(strava_api::UploadRequest {
+                file,
+                file_name: file_name.clone(),
+                data_type,
+                name: params.name,
+                description: params.description,
+                trainer: params.trainer,
+                commute: None,
+                external_id: Some(file_name.clone()),
+            })
+            .await
+            .map_err(ToolError::from)?;
+
+        // Strava processes uploads asynchronously; poll until done
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+        const MAX_POLLS: u32 = 30;
+
+        let mut outcome = UploadOutcome::from_upload(&status);
+        let mut polls = 0;
+        while outcome == UploadOutcome::Pending && polls < MAX_POLLS {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            status = client
+                .get_upload_by_id(status.id)
+                .await
+                .map_err(ToolError::from)?;
+            outcome = UploadOutcome::from_upload(&status);
+            polls += 1;
+        }
+
+        let output = match outcome {
+            UploadOutcome::Ready(activity_id) => format!(
+                "# Uploaded {}\n\n- **Activity ID:** {}\n- **Upload ID:** {}\n",
+                file_name, activity_id, status.id
+            ),
+            UploadOutcome::Duplicate { activity_id, message } => {
+                let mut output = format!("# {} is already on Strava\n\n", file_name);
+                if let Some(id) = activity_id {
+                    output.push_str(&format!("- **Existing Activity ID:** {}\n", id));
+                }
+                output.push_str(&format!("- **Strava says:** {}\n", message));
+                output
+            }
+            UploadOutcome::Failed(message) => format!(
+                "# Upload of {} failed\n\n- **Strava says:** {}\n",
+                file_name, message
+            ),
+            UploadOutcome::Pending => format!(
+                "# {} is still processing\n\n- **Upload ID:** {}\n- Strava had not finished after {} seconds; the activity should appear on Strava shortly.\n",
+                file_name,
+                status.id,
+                POLL_INTERVAL.as_secs() * MAX_POLLS as u64
+            ),
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "List athlete profiles with their authorization status and settings")]
+    async fn list_profiles(&self) -> Result<CallToolResult, McpError> {
+        let names = profiles::list(&self.base_dir).map_err(McpError::internal)?;
+        let loaded = self.profiles.read().await;
+
+        // Format output
+        let mut output = format!("# {} Profiles\n\n", names.len());
+
+        for name in &names {
+            let paths = ProfilePaths::new(&self.base_dir, name);
+            if *name == self.default_profile {
+                output.push_str(&format!("## {} (default)\n", name));
+            } else {
+                output.push_str(&format!("## {}\n", name));
+            }
+            output.push_str(&format!(
+                "- **Authorized:** {}\n",
+                if paths.token.exists() { "yes" } else { "no" }
+            ));
+
+            // Profiles in use show their loaded settings; others are read from disk
+            let settings = match loaded.get(name) {
+                Some(profile) => Ok(profile.settings.clone()),
+                None => self.config.profile_settings(&paths.settings),
+            };
+            match settings {
+                Ok(settings) => {
+                    let units = match settings.units {
+                        Units::Metric => "metric",
+                        Units::Imperial => "imperial",
+                    };
+                    output.push_str(&format!("- **Units:** {}\n", units));
+                    output.push_str(&format!(
+                        "- **Timezone:** {}\n",
+                        settings.timezone.as_deref().unwrap_or("UTC")
+                    ));
+                    if let Some(zones) = &settings.hr_zones {
+                        output.push_str(&format!("- **HR Zones:** {} custom zones\n", zones.len()));
+                    } else if let Some(max_hr) = settings.max_hr {
+                        output.push_str(&format!("- **HR Zones:** from max HR {} bpm\n", max_hr));
+                    }
+                }
+                Err(e) => output.push_str(&format!("- **Settings Error:** {:#}\n", e)),
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Import FIT, GPX and TCX files from a local directory so they are included in run listings and summaries")]
+    async fn import_activity_files(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ImportActivityFilesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let directory = PathBuf::from(&params.directory);
+
+        if !directory.is_dir() {
+            return Err(McpError::invalid_params_no_data(format!(
+                "Directory not found: {}",
+                directory.display()
+            )));
+        }
+
+        // Files record UTC only; local dates follow the profile's timezone, as period bounds do
+        let timezone = profile.settings.timezone().ok().flatten().unwrap_or(chrono_tz::UTC);
+
+        // Parsing is blocking file I/O
+        let report = tokio::task::spawn_blocking(move || import::import_directory(&directory, timezone))
+            .await
+            .map_err(McpError::internal)?
+            .map_err(McpError::internal)?;
+
+        let output = profile.store_import_report(report).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Import a Strava \"download your data\" export ZIP (activities.csv plus activity files) into the local store")]
+    async fn import_strava_archive(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ImportStravaArchiveParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let path = PathBuf::from(&params.path);
+
+        if !path.is_file() {
+            return Err(McpError::invalid_params_no_data(format!(
+                "Archive not found: {}",
+                path.display()
+            )));
+        }
+
+        // The export's times are UTC; local dates follow the profile's timezone, as period bounds do
+        let timezone = profile.settings.timezone().ok().flatten().unwrap_or(chrono_tz::UTC);
+
+        // Unzipping and parsing thousands of files is blocking work
+        let report = tokio::task::spawn_blocking(move || import::import_archive(&path, timezone))
+            .await
+            .map_err(McpError::internal)?
+            .map_err(McpError::internal)?;
+
+        let output = profile.store_import_report(report).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Export an activity as a GPX 1.1 file built from its GPS, altitude, time, heart rate and cadence streams")]
+    async fn export_activity_gpx(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ActivityIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let track = profile.fetch_track(params.activity_id).await?;
+
+        let gpx = track.to_gpx().map_err(McpError::invalid_params_no_data)?;
+
+        Ok(CallToolResult::success(vec![Content::text(gpx)]))
+    }
+
+    #[tool(description = "Export an activity as a TCX (Garmin Training Center) file built from its streams")]
+    async fn export_activity_tcx(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<ActivityIdParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+        let track = profile.fetch_track(params.activity_id).await?;
+
+        let tcx = track.to_tcx().map_err(McpError::invalid_params_no_data)?;
+
+        Ok(CallToolResult::success(vec![Content::text(tcx)]))
+    }
+
+    #[tool(description = "Authorize the MCP with your Strava account. Use headless mode when the server runs on another machine")]
+    async fn authorize(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AuthorizeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        // First, try to get a client with the existing token (will auto-refresh and auto-save if needed)
+        match profile.client().await {
+            Ok(_) => {
+                // Token exists and is valid (or was successfully refreshed and saved)
+                return Ok(CallToolResult::success(vec![Content::text(
+                    "Already authorized! Your token is valid and has been refreshed if needed. Use get_auth_status for details.".to_string(),
+                )]));
+            }
+            Err(_) => {
+                // Token doesn't exist or refresh failed - need full OAuth flow
+            }
+        }
+
+        // Validate port parameter (prevent privilege escalation)
+        const MIN_PORT: u16 = 1024; // Avoid privileged ports
+
+        let port = params.port.unwrap_or(self.config.oauth_port);
+
+        if port < MIN_PORT {
+            return Err(McpError::invalid_params_no_data(format!(
+                "port must be >= {} (requested: {}). Ports below 1024 require elevated privileges.",
+                MIN_PORT, port
+            )));
+        }
+
+        // Validate scope parameter (whitelist allowed scopes)
+        const ALLOWED_SCOPES: &[&str] = &[
+            "read",
+            "read_all",
+            "profile:read_all",
+            "profile:write",
+            "activity:read",
+            "activity:read_all",
+            "activity:write",
+        ];
+
+        let scope = params.scope.as_deref().unwrap_or(&self.config.oauth_scope);
+
+        for requested in scope.split(',') {
+            if !ALLOWED_SCOPES.contains(&requested.trim()) {
+                return Err(McpError::invalid_params_no_data(format!(
+                    "Invalid scope '{}'. Allowed scopes: {}",
+                    requested,
+                    ALLOWED_SCOPES.join(", ")
+                )));
+            }
+        }
+
+        // Headless: hand the URL to the user instead of opening a browser and listening locally
+        if params.headless.unwrap_or(false) {
+            let pending = PendingAuthorization::new(scope);
+            let redirect_uri = format!("http://localhost:{}", port);
+            let url = profile.auth_client.authorization_url(&redirect_uri, scope, &pending.state);
+            *profile.pending_authorization.lock().await = Some(pending);
+
+            let mut output = String::from("# Authorize on Strava\n\n");
+            output.push_str(&format!("- **Profile:** {}\n", profile.name));
+            output.push_str(&format!("- **Requested Scopes:** {}\n\n", scope.replace(',', ", ")));
+            output.push_str("1. Open this URL in a browser on any machine and approve access:\n\n");
+            output.push_str(&format!("   {}\n\n", url));
+            output.push_str(&format!(
+                "2. Strava then redirects to {}, which will fail to load. Copy the full URL from the address bar.\n",
+                redirect_uri
+            ));
+            output.push_str("3. Pass it to `complete_authorization` within 10 minutes.\n");
+
+            return Ok(CallToolResult::success(vec![Content::text(output)]));
+        }
+
+        // Perform full OAuth authorization flow (opens browser)
+        let token = profile
+            .auth_client
+            .authorize(port, scope)
+            .await
+            .map_err(McpError::internal)?;
+
+        // The athlete may have unticked some scopes on Strava's consent page
+        let granted_scopes = token.scope.as_deref().unwrap_or(scope).to_string();
+        let output = profile.authorized(&granted_scopes).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Finish a headless authorization by pasting the full URL Strava redirected to")]
+    async fn complete_authorization(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<CompleteAuthorizationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.profile.athlete.as_deref()).await?;
+
+        let response = auth::parse_callback(&params.response)
+            .map_err(|e| McpError::invalid_params_no_data(e.to_string()))?;
+
+        // The state check ties the redirect to the authorization this server started
+        let (code, requested_scope) = {
+            let mut pending = profile.pending_authorization.lock().await;
+            let authorization = pending.as_ref().ok_or_else(|| {
+                McpError::invalid_params_no_data(format!(
+                    "No authorization in progress for profile '{}'. Run authorize with headless: true first.",
+                    profile.name
+                ))
+            })?;
+            let code = authorization
+                .verify(&response)
+                .map_err(|e| McpError::invalid_params_no_data(e.to_string()))?;
+            let scope = authorization.scope.clone();
+            *pending = None;
+            (code, scope)
+        };
+
+        let token = profile
+            .auth_client
+            .exchange_code(&code)
+            .await
+            .map_err(|e| ToolError::from_strava("POST /oauth/token", &e.to_string()))?;
+
+        let granted_scopes = response
+            .scope
+            .or(token.scope)
+            .unwrap_or(requested_scope);
+        let output = profile.authorized(&granted_scopes).await?;
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Show who is authorized, the granted scopes, when the access token expires and where it is stored")]
+    async fn get_auth_status(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.athlete.as_deref()).await?;
+
+        let mut output = String::from("# Authorization Status\n\n");
+        output.push_str(&format!("- **Profile:** {}\n", profile.name));
+
+        if profile.auth_client.token().await.is_none() {
+            output.push_str("- **Authorized:** no\n\nRun `authorize` to connect a Strava account.\n");
+            return Ok(CallToolResult::success(vec![Content::text(output)]));
+        }
+        output.push_str("- **Authorized:** yes\n");
+
+        // Looking up the athlete refreshes the token if it is about to expire
+        match profile.client().await {
+            Ok(client) => {
+                if let Ok(athlete) = client.get_logged_in_athlete().await {
+                    output.push_str(&format!("- **Athlete:** {}\n", athlete_display_name(&athlete)));
+                }
+            }
+            Err(e) => output.push_str(&format!("- **Refresh Error:** {}\n", e.message)),
+        }
+
+        if let Some(token) = profile.auth_client.token().await {
+            output.push_str(&format!(
+                "- **Granted Scopes:** {}\n",
+                token.scope.as_deref().unwrap_or("unknown").replace(',', ", ")
+            ));
+
+            let remaining = token.expires_at - Utc::now().timestamp();
+            let expires = DateTime::from_timestamp(token.expires_at, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| token.expires_at.to_string());
+            if remaining > 0 {
+                output.push_str(&format!(
+                    "- **Access Token Expires:** {} (in {})\n",
+                    expires,
+                    format_duration(remaining.min(i32::MAX as i64) as i32)
+                ));
+            } else {
+                output.push_str(&format!(
+                    "- **Access Token Expires:** {} (expired; refreshed on next use)\n",
+                    expires
+                ));
+            }
+        }
+
+        let encryption = if profile.token_file.is_encrypted() { "encrypted" } else { "plaintext" };
+        output.push_str(&format!(
+            "- **Token File:** {} ({})\n",
+            profile.token_file.path().display(),
+            encryption
+        ));
+        if let Some(modified) = profile.token_file.modified() {
+            let modified: DateTime<Utc> = modified.into();
+            output.push_str(&format!("- **Last Refresh:** {}\n", modified.format("%Y-%m-%d %H:%M UTC")));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Revoke this server's access to the Strava account and delete the saved token")]
+    async fn deauthorize(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let profile = self.profile(params.athlete.as_deref()).await?;
+
+        if profile.auth_client.token().await.is_none() {
+            return Ok(CallToolResult::success(vec![Content::text(format!(
+                "Profile '{}' is not authorized; nothing to revoke.",
+                profile.name
+            ))]));
+        }
+
+        // Delete the local token even if Strava cannot be reached, so the server stops using it
+        let revoked = profile.auth_client.deauthorize().await;
+        profile
+            .token_file
+            .delete()
+            .map_err(|e| McpError::internal(format!("{:#}", e)))?;
+
+        // Drop the loaded profile so the next call starts without a token
+        self.profiles.write().await.remove(&profile.name);
+
+        let mut output = String::from("# Deauthorized\n\n");
+        output.push_str(&format!("- **Profile:** {}\n", profile.name));
+        output.push_str(&format!("- **Deleted:** {}\n", profile.token_file.path().display()));
+        match revoked {
+            Ok(()) => output.push_str("- **Strava Access:** revoked\n"),
+            Err(e) => output.push_str(&format!(
+                "- **Strava Access:** not revoked ({}). Revoke it at https://www.strava.com/settings/apps\n",
+                e
+            )),
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Show the effective configuration and where each value comes from: command line, environment, profile settings, config file or default")]
+    async fn show_config(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<AthleteParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let name = params.athlete.as_deref().unwrap_or(&self.default_profile);
+        profiles::validate_name(name).map_err(|e| McpError::invalid_params_no_data(e.to_string()))?;
+
+        let paths = ProfilePaths::new(&self.base_dir, name);
+        let values = self
+            .config
+            .describe(&paths.settings)
+            .map_err(|e| McpError::internal(format!("{:#}", e)))?;
+
+        let status = |path: &Path| if path.exists() { "loaded" } else { "not found" };
+        let mut output = String::from("# Configuration\n\n");
+        output.push_str(&format!(
+            "- **Config File:** {} ({})\n",
+            self.config.path.display(),
+            status(&self.config.path)
+        ));
+        output.push_str(&format!("- **Profile:** {}\n", name));
+        output.push_str(&format!(
+            "- **Profile Settings:** {} ({})\n",
+            paths.settings.display(),
+            status(&paths.settings)
+        ));
+        let all_tools = Self::tool_router().list_all();
+        output.push_str(&format!(
+            "- **Enabled Tools:** {} of {}\n",
+            self.tool_router.list_all().len(),
+            all_tools.len()
+        ));
+        let mut disabled: Vec<_> = all_tools
+            .iter()
+            .filter(|tool| !self.tool_router.has_route(&tool.name))
+            .map(|tool| tool.name.to_string())
+            .collect();
+        if !disabled.is_empty() {
+            disabled.sort();
+            output.push_str(&format!("- **Disabled Tools:** {}\n", disabled.join(", ")));
+        }
+        output.push('\n');
+        output.push_str("Precedence: command line > environment > profile settings > config file > default\n\n");
+
+        output.push_str("## Values\n");
+        for (key, value, source) in values {
+            output.push_str(&format!("- **{}:** {} ({})\n", key, value, source));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Subscribe this Strava app to activity and athlete events. Strava validates the callback URL straight away, so the --webhook receiver must be running and reachable there")]
+    async fn create_webhook_subscription(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<CreateWebhookSubscriptionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        let verify_token = params
+            .verify_token
+            .or_else(|| self.config.webhook_verify_token.clone())
+            .ok_or_else(|| {
+                McpError::invalid_params_no_data("Pass verify_token, or set webhook_verify_token in the config")
+            })?;
+        if !params.callback_url.starts_with("https://") && !params.callback_url.starts_with("http://") {
+            return Err(McpError::invalid_params_no_data(format!(
+                "callback_url must be an http(s) URL (got: {})",
+                params.callback_url
+            )));
+        }
+
+        let profile = self.profile(None).await?;
+        let id = webhook::Subscriptions::new(&profile.oauth)
+            .create(&params.callback_url, &verify_token)
+            .await
+            .map_err(|e| ToolError::from_strava("POST /push_subscriptions", &format!("{:#}", e)))?;
+
+        let mut output = String::from("# Webhook Subscription Created\n\n");
+        output.push_str(&format!("- **Subscription ID:** {}\n", id));
+        output.push_str(&format!("- **Callback URL:** {}\n", params.callback_url));
+        output.push_str("- Strava now sends activity and athlete events for every athlete who authorized this app.\n");
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "List this Strava app's webhook subscriptions")]
+    async fn list_webhook_subscriptions(&self) -> Result<CallToolResult, McpError> {
+        let profile = self.profile(None).await?;
+        let subscriptions = webhook::Subscriptions::new(&profile.oauth)
+            .list()
+            .await
+            .map_err(|e| ToolError::from_strava("GET /push_subscriptions", &format!("{:#}", e)))?;
+
+        if subscriptions.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "No webhook subscriptions. Use create_webhook_subscription to add one.",
+            )]));
+        }
+
+        let mut output = format!("# {} Webhook Subscriptions\n\n", subscriptions.len());
+        for subscription in &subscriptions {
+            output.push_str(&format!("## {}\n", subscription.id));
+            output.push_str(&format!("- **Callback URL:** {}\n", subscription.callback_url));
+            if let Some(created_at) = &subscription.created_at {
+                output.push_str(&format!("- **Created:** {}\n", created_at));
+            }
+            output.push('\n');
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(description = "Delete one of this Strava app's webhook subscriptions")]
+    async fn delete_webhook_subscription(
+        &self,
+        params: rmcp::handler::server::wrapper::Parameters<DeleteWebhookSubscriptionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let params = params.0;
+        validate_id("subscription_id", params.subscription_id)?;
+
+        let profile = self.profile(None).await?;
+        webhook::Subscriptions::new(&profile.oauth)
+            .delete(params.subscription_id)
+            .await
+            .map_err(|e| ToolError::from_strava("DELETE /push_subscriptions/{id}", &format!("{:#}", e)))?;
+
+        Ok(CallToolResult::success(vec![Content::text(format!(
+            "Deleted webhook subscription {}.",
+            params.subscription_id
+        ))]))
+    }
+}
+
+impl StravaMcpServer {
+    /// Look up a profile by name (default: the server's --profile), loading it on first use
+    async fn profile(&self, name: Option<&str>) -> Result<Arc<Profile>, McpError> {
+        let name = name.unwrap_or(&self.default_profile);
+        profiles::validate_name(name).map_err(|e| McpError::invalid_params_no_data(e.to_string()))?;
+
+        if let Some(profile) = self.profiles.read().await.get(name) {
+            tracing::debug!(profile = name, "profile cache hit");
+            return Ok(profile.clone());
+        }
+
+        let mut loaded = self.profiles.write().await;
+        // Another call may have loaded it while we waited for the lock
+        if let Some(profile) = loaded.get(name) {
+            return Ok(profile.clone());
+        }
+
+        tracing::debug!(profile = name, "profile cache miss, loading from disk");
+        let profile = Arc::new(
+            Profile::load(&self.config, name, self.token_key.clone())
+                .map_err(|e| McpError::internal(format!("{:#}", e)))?,
+        );
+        loaded.insert(name.to_string(), profile.clone());
+        Ok(profile)
+    }
+
+    /// Apply webhook events one at a time, so events for the same activity stay in order
+    pub async fn apply_webhook_events(self, mut events: tokio::sync::mpsc::Receiver<webhook::Event>) {
+        while let Some(event) = events.recv().await {
+            // Events that queued up while the last ones were applied need only one look at Strava per object
+            let mut batch = vec![event];
+            while let Ok(event) = events.try_recv() {
+                batch.push(event);
+            }
+            for event in webhook::collapse(batch) {
+                if let Err(e) = self.handle_webhook_event(&event).await {
+                    tracing::warn!(object_id = event.object_id, error = %e.message, "failed to apply webhook event");
+                }
+            }
+        }
+    }
+
+    /// Bring the owner's local store up to date after a webhook event.
+    ///
+    /// Anyone who finds the receiver can post to it, so events only prompt a fresh look at Strava.
+    async fn handle_webhook_event(&self, event: &webhook::Event) -> Result<(), McpError> {
+        let Some(profile) = self.profile_for_athlete(event.owner_id).await? else {
+            tracing::warn!(owner_id = event.owner_id, "webhook event for an athlete without a profile");
+            return Ok(());
+        };
+
+        match event.object_type {
+            ObjectType::Activity => self.refresh_cached_activity(&profile, event.object_id).await,
+            ObjectType::Athlete if event.is_deauthorization() => self.forget_revoked_token(&profile).await,
+            ObjectType::Athlete => Ok(()),
+        }
+    }
+
+    /// The profile authorized as a Strava athlete, asking Strava who each profile is the first time
+    async fn profile_for_athlete(&self, athlete_id: i64) -> Result<Option<Arc<Profile>>, McpError> {
+        if let Some(name) = self.athletes.read().await.get(&athlete_id).cloned() {
+            return self.profile(Some(&name)).await.map(Some);
+        }
+
+        for name in profiles::list(&self.base_dir).map_err(McpError::internal)? {
+            if self.athletes.read().await.values().any(|known| *known == name) {
+                continue;
+            }
+            let profile = self.profile(Some(&name)).await?;
+            // Profiles without a token cannot receive events
+            let Ok(client) = profile.client().await else {
+                continue;
+            };
+            let athlete = match client.get_logged_in_athlete().await {
+                Ok(athlete) => athlete,
+                Err(e) => {
+                    tracing::warn!(profile = %name, error = %format!("{:#}", e), "could not identify the profile's athlete");
+                    continue;
+                }
+            };
+            self.athletes.write().await.insert(athlete.id, name);
+            if athlete.id == athlete_id {
+                return Ok(Some(profile));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Store the activity as Strava has it now, or drop it if Strava no longer has it
+    async fn refresh_cached_activity(&self, profile: &Profile, activity_id: i64) -> Result<(), McpError> {
+        let client = profile.client().await?;
+        let fetched = client.get_activity(activity_id, false).await.map_err(ToolError::from);
+
+        let mut store = profile.store.write().await;
+        let changed = match fetched {
+            Ok(detailed) => store.upsert(Activity::try_from(&detailed).map_err(McpError::internal)?),
+            Err(ToolError::NotFound { .. }) => store.remove(activity_id),
+            Err(e) => return Err(e.into()),
+        };
+        if !changed {
+            return Ok(());
+        }
+        store.save().map_err(McpError::internal)?;
+        drop(store);
+
+        tracing::info!(profile = %profile.name, activity_id, "updated cached activity");
+        self.notify_resource_updated(&activities_uri(&profile.name)).await;
+        Ok(())
+    }
+
+    /// Delete the saved token once Strava confirms the athlete revoked access
+    async fn forget_revoked_token(&self, profile: &Profile) -> Result<(), McpError> {
+        let check = async {
+            let client = profile.client().await?;
+            client.get_logged_in_athlete().await.map_err(ToolError::from)?;
+            Ok::<_, McpError>(())
+        };
+        match check.await {
+            Ok(()) => {
+                tracing::warn!(profile = %profile.name, "ignored deauthorization event: the token still works");
+                Ok(())
+            }
+            Err(e) if e.code == error::NOT_AUTHORIZED => {
+                profile.token_file.delete().map_err(McpError::internal)?;
+                self.profiles.write().await.remove(&profile.name);
+                self.athletes.write().await.retain(|_, name| *name != profile.name);
+                tracing::info!(profile = %profile.name, "athlete revoked access; deleted the saved token");
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn notify_resource_updated(&self, uri: &str) {
+        let Some(peer) = self.resource_subscribers.read().await.get(uri).cloned() else {
+            return;
+        };
+        let param = ResourceUpdatedNotificationParam { uri: uri.to_string() };
+        if let Err(e) = peer.notify_resource_updated(param).await {
+            tracing::debug!(uri, error = %e, "could not notify resource subscriber");
+        }
+    }
+
+    /// The profile a resource URI refers to
+    async fn resource_profile(&self, uri: &str) -> Result<Arc<Profile>, McpError> {
+        let name = uri
+            .strip_prefix(RESOURCE_PREFIX)
+            .and_then(|rest| rest.strip_suffix("/activities"))
+            .filter(|name| profiles::validate_name(name).is_ok())
+            .ok_or_else(|| McpError::resource_not_found(format!("Unknown resource '{}'", uri), None))?;
+        self.profile(Some(name)).await
+    }
+}
+
+const RESOURCE_PREFIX: &str = "strava://profiles/";
+
+/// URI of a profile's locally stored activities
+fn activities_uri(profile: &str) -> String {
+    format!("{}{}/activities", RESOURCE_PREFIX, profile)
+}
+
+impl Profile {
+    /// Load a profile's token, local store and settings from disk
+    fn load(config: &Config, name: &str, token_key: Option<TokenKey>) -> Result<Self> {
+        // Load OAuth config
+        let oauth = OAuthConfig::from_env()
+            .context("Failed to load OAuth configuration. Please set STRAVA_CLIENT_ID and STRAVA_CLIENT_SECRET environment variables.")?;
+
+        Self::load_with_oauth(config, name, token_key, oauth)
+    }
+
+    /// Load a profile that talks to Strava with the given OAuth config
+    pub fn load_with_oauth(config: &Config, name: &str, token_key: Option<TokenKey>, oauth: OAuthConfig) -> Result<Self> {
+        let paths = ProfilePaths::new(&config.cache_dir, name);
+
+        // Load or create authenticated client; the token file is kept up to date by Profile::client
+        let token_file = TokenFile::new(paths.token.clone(), token_key);
+        let (auth_client, saved_access_token) = if token_file.exists() {
+            let loaded = token_file
+                .load()
+                .with_context(|| format!("Failed to load saved token for profile '{}'", name))?;
+
+            // Encrypt tokens saved before a passphrase or key file was configured
+            if loaded.needs_migration {
+                token_file.save(&loaded.token).context("Failed to encrypt saved token")?;
+                tracing::info!(path = %token_file.path().display(), "encrypted plaintext token");
+            }
+
+            let access_token = loaded.token.access_token.clone();
+            (AuthenticatedClient::with_token(oauth.clone(), loaded.token), Some(access_token))
+        } else {
+            // No saved token, will need to authorize on first tool call
+            (AuthenticatedClient::new(oauth.clone()), None)
+        };
+
+        // Load activities imported from local files
+        let store = LocalStore::load(&paths.store).context("Failed to load local activity store")?;
+
+        let settings = config.profile_settings(&paths.settings)?;
+
+        Ok(Self {
+            name: name.to_string(),
+            oauth,
+            auth_client,
+            source: None,
+            token_file,
+            saved_access_token: tokio::sync::Mutex::new(saved_access_token),
+            pending_authorization: tokio::sync::Mutex::new(None),
+            store: RwLock::new(store),
+            settings,
+        })
+    }
+
+    /// Save a newly issued token and describe who was authorized
+    async fn authorized(&self, granted_scopes: &str) -> Result<String, McpError> {
+        self.save_token().await?;
+
+        let athlete_name = match self.client().await {
+            Ok(client) => client
+                .get_logged_in_athlete()
+                .await
+                .ok()
+                .map(|athlete| athlete_display_name(&athlete)),
+            Err(_) => None,
+        };
+
+        let mut output = match athlete_name {
+            Some(name) => format!("Authorization successful! Authorized as {}.\n\n", name),
+            None => "Authorization successful!\n\n".to_string(),
+        };
+        output.push_str(&format!("- **Profile:** {}\n", self.name));
+        output.push_str(&format!("- **Granted Scopes:** {}\n", granted_scopes.replace(',', ", ")));
+        output.push_str("- Token automatically saved for future use.\n");
+        Ok(output)
+    }
+
+    /// Get the Strava data source: an authenticated client (saving the token if it was refreshed)
+    async fn client(&self) -> Result<Arc<dyn ActivitySource>, McpError> {
+        if let Some(source) = &self.source {
+            return Ok(source.clone());
+        }
+
+        if self.auth_client.token().await.is_none() {
+            return Err(ToolError::NotAuthorized { profile: self.name.clone() }.into());
+        }
+        let client = self
+            .auth_client
+            .client()
+            .await
+            .map_err(|e| ToolError::refresh_failed(&e.to_string()))?;
+        self.save_token().await?;
+        Ok(Arc::new(client))
+    }
+
+    /// Write the current token to the token file if it changed since the last save
+    async fn save_token(&self) -> Result<(), McpError> {
+        let Some(token) = self.auth_client.token().await else {
+            return Ok(());
+        };
+
+        let mut saved = self.saved_access_token.lock().await;
+        if saved.as_deref() != Some(token.access_token.as_str()) {
+            self.token_file
+                .save(&token)
+                .map_err(|e| McpError::internal(format!("Failed to save token: {:#}", e)))?;
+            tracing::info!(path = %self.token_file.path().display(), "saved refreshed token");
+            *saved = Some(token.access_token);
+        }
+        Ok(())
+    }
+
+    /// Parse a date or period such as "2026-W07" or "last month", relative to today in the profile's timezone
+    fn period(&self, input: &str) -> Result<Period, McpError> {
+        Period::parse(input, self.settings.today()).map_err(|e| ToolError::InvalidInput(e.to_string()).into())
+    }
+
+    /// Start and end timestamps of a period in the profile's timezone
+    fn bounds(&self, period: &Period) -> Result<(i64, i64), McpError> {
+        self.settings
+            .bounds(period)
+            .ok_or_else(|| McpError::internal(format!("No local midnight for {}", period)))
+    }
+
+    /// Fetch activities in [after, before) from Strava, merged with locally imported files.
+    ///
+    /// Without a Strava token the local store is used on its own, as long as it has data.
+    /// A token that exists but can't be refreshed is an error, not a reason to leave Strava out.
+    async fn fetch_activities(&self, after: Option<i64>, before: Option<i64>) -> Result<Vec<Activity>, McpError> {
+        let local = self.store.read().await.activities(after, before).await.map_err(McpError::internal)?;
+
+        let activities = match self.strava_source().await? {
+            Some(client) => client.activities(after, before).await.map_err(ToolError::from)?,
+            None => Vec::new(),
+        };
+
+        tracing::debug!(after, before, strava = activities.len(), local = local.len(), "fetched activities");
+        Ok(merge_local(activities, local))
+    }
+
+    /// Fetch the latest activities from Strava until `limit` of them match the profile's sport, merged with
+    /// locally imported files, oldest first
+    async fn fetch_recent_activities(&self, limit: usize) -> Result<Vec<Activity>, McpError> {
+        let local = self.store.read().await.activities(None, None).await.map_err(McpError::internal)?;
+
+        let settings = &self.settings;
+        let enough = |activities: &[Activity]| activities.iter().filter(|a| settings.matches_sport(a)).count() >= limit;
+        let activities = match self.strava_source().await? {
+            Some(client) => client.recent_activities(&enough).await.map_err(ToolError::from)?,
+            None => Vec::new(),
+        };
+
+        tracing::debug!(limit, strava = activities.len(), local = local.len(), "fetched recent activities");
+        Ok(merge_local(activities, local))
+    }
+
+    /// Get authenticated client (will auto-refresh and auto-save token if needed), or None when the profile was
+    /// never authorized but has imported activities to work from
+    async fn strava_source(&self) -> Result<Option<Arc<dyn ActivitySource>>, McpError> {
+        let local_only =
+            !self.store.read().await.is_empty() && self.source.is_none() && self.auth_client.token().await.is_none();
+        if local_only {
+            return Ok(None);
+        }
+        self.client().await.map(Some)
+    }
+
+    /// Fail with instructions when the token is known to lack the activity:write scope.
+    ///
+    /// Tokens saved before scopes were recorded are let through; Strava rejects them if needed.
+    async fn check_write_scope(&self) -> Result<(), McpError> {
+        let granted = self.auth_client.token().await.and_then(|token| token.scope);
+        match granted {
+            Some(scopes) if !update::has_scope(&scopes, update::WRITE_SCOPE) => Err(ToolError::MissingScope {
+                scope: update::WRITE_SCOPE.to_string(),
+                granted: Some(scopes),
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fetch the athlete's shoes and bikes from their Strava profile
+    async fn fetch_gear(&self, thresholds: &RetirementThresholds) -> Result<Vec<GearUsage>, McpError> {
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = self.client().await?;
+
+        let athlete = client.get_logged_in_athlete().await.map_err(ToolError::from)?;
+
+        let shoes = athlete
+            .shoes
+            .iter()
+            .flatten()
+            .map(|g| GearUsage::from_summary(g, GearKind::Shoe, thresholds));
+        let bikes = athlete
+            .bikes
+            .iter()
+            .flatten()
+            .map(|g| GearUsage::from_summary(g, GearKind::Bike, thresholds));
+
+        Ok(shoes.chain(bikes).collect())
+    }
+
+    /// Add imported activities to the local store and describe the outcome
+    async fn store_import_report(&self, report: import::ImportReport) -> Result<String, McpError> {
+        let mut store = self.store.write().await;
+        let found = report.activities.len();
+        let imported = report
+            .activities
+            .into_iter()
+            .filter(|activity| store.insert(activity.clone()))
+            .count();
+        store.save().map_err(McpError::internal)?;
+
+        let mut output = format!("# Imported {} activities\n\n", imported);
+        output.push_str(&format!("- **Activities parsed:** {}\n", found));
+        output.push_str(&format!("- **Already imported:** {}\n", found - imported));
+        output.push_str(&format!("- **Failed:** {}\n", report.failures.len()));
+        output.push_str(&format!("- **Local store:** {} activities\n", store.len()));
+
+        if !report.failures.is_empty() {
+            output.push_str("\n## Failures\n");
+            for (path, error) in &report.failures {
+                output.push_str(&format!("- {}: {}\n", path.display(), error));
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Fetch an activity and its streams, zipped into a track ready for export
+    async fn fetch_track(&self, activity_id: i64) -> Result<Track, McpError> {
+        validate_id("activity_id", activity_id)?;
+
+        // Get authenticated client (will auto-refresh and auto-save token if needed)
+        let client = self.client().await?;
+
+        let activity = client
+            .get_activity(activity_id, false)
+            .await
+            .map_err(ToolError::from)?;
+
+        let streams = client
+            .get_activity_streams(activity_id, EXPORT_STREAM_KEYS)
+            .await
+            .map_err(ToolError::from)?;
+
+        let start = DateTime::parse_from_rfc3339(&activity.start_date)
+            .map_err(|e| McpError::internal(format!("Invalid activity start date: {}", e)))?
+            .with_timezone(&Utc);
+
+        Ok(Track::from_streams(
+            &activity.name,
+            &activity.sport_type,
+            start,
+            &streams,
+        ))
+    }
+}
+
+impl ServerHandler for StravaMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo {
+            instructions: Some("MCP server for Strava API integration. Provides tools to fetch and analyze running activity data from Strava.".into()),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_logging()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .build(),
+            ..Default::default()
+        }
+    }
+
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = request.name.to_string();
+        let span = logging::tool_span(&tool, request.arguments.as_ref());
+        let started = Instant::now();
+        let progress = progress::Progress::new(context.peer.clone(), context.meta.get_progress_token());
+        let cancelled = context.ct.clone();
+        let call = self.tool_router.call(ToolCallContext::new(self, request, context));
+        let result = tokio::select! {
+            result = progress.scope(call).instrument(span.clone()) => result,
+            _ = cancelled.cancelled() => {
+                // Dropping the call stops its Strava requests and upload polling. MCP clients ignore
+                // any response to a cancelled request, so return at once rather than keep this task alive.
+                span.in_scope(|| tracing::info!(tool = %tool, "tool call cancelled"));
+                return Err(McpError::internal_error("cancelled", None));
+            }
+        };
+        span.in_scope(|| logging::record_call(&tool, started, &result));
+        result
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
+    fn get_tool(&self, name: &str) -> Option<Tool> {
+        self.tool_router.get(name).cloned()
+    }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        logging::forward_to_client(context.peer, request.level);
+        Ok(())
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let names = profiles::list(&self.base_dir).map_err(McpError::internal)?;
+        let resources = names
+            .iter()
+            .map(|name| {
+                let mut resource = RawResource::new(activities_uri(name), format!("{} activities", name));
+                resource.description = Some(format!(
+                    "Activities stored locally for profile '{}': imported files and Strava activities kept current by webhook events",
+                    name
+                ));
+                resource.mime_type = Some("application/json".to_string());
+                resource.no_annotation()
+            })
+            .collect();
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        let profile = self.resource_profile(&request.uri).await?;
+        let activities = profile.store.read().await.between(None, None);
+        let text = serde_json::to_string_pretty(&activities).map_err(McpError::internal)?;
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::TextResourceContents {
+                uri: request.uri,
+                mime_type: Some("application/json".to_string()),
+                text,
+                meta: None,
+            }],
+        })
+    }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParams,
+        context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.resource_profile(&request.uri).await?;
+        self.resource_subscribers.write().await.insert(request.uri, context.peer);
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.resource_subscribers.write().await.remove(&request.uri);
+        Ok(())
+    }
+}
+
+// Tool parameter structs
+/// The profile a tool call acts for; flattened into every other params struct
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AthleteParams {
+    #[schemars(description = "Athlete profile to use (default: the server's --profile)")]
+    athlete: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetRunsForDateParams {
+    #[schemars(description = "Date in YYYY-MM-DD format, today or yesterday, or any period such as 2026-W07 or last 14 days")]
+    date: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetRecentRunsParams {
+    #[schemars(description = "Number of recent runs to retrieve (default: 5, or the configured default_limit)")]
+    limit: Option<usize>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetWeeklySummaryParams {
+    #[schemars(description = "ISO week (2026-W07), this week, last week, or a date in YYYY-MM-DD format to start a seven-day week on (defaults to the current week)")]
+    week_start: Option<String>,
+    #[schemars(description = "Add a warning for each pair of shoes past its retirement threshold; false skips the extra Strava request (default: true)")]
+    gear_warnings: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetGearReportParams {
+    #[schemars(description = "Window in days for recent distance and activity counts (default: 30)")]
+    days: Option<u32>,
+    #[schemars(description = "Shoe retirement threshold in km (default: STRAVA_SHOE_RETIREMENT_KM or 700)")]
+    shoe_threshold_km: Option<f64>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetRunsForMonthParams {
+    #[schemars(description = "Month in YYYY-MM format (e.g., 2026-01), this month or last month, or another period such as 2026-Q1, 2025 or last 30 days")]
+    month: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetMonthlyRunningSummaryParams {
+    #[schemars(description = "Month in YYYY-MM format (e.g., 2026-01), this month or last month, or another period such as 2026-Q1, 2025 or last 30 days")]
+    month: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ImportActivityFilesParams {
+    #[schemars(description = "Directory containing FIT, GPX or TCX files (searched recursively)")]
+    directory: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ImportStravaArchiveParams {
+    #[schemars(description = "Path to the export ZIP downloaded from Strava (Settings > My Account > Download or Delete Your Account)")]
+    path: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ListStarredSegmentsParams {
+    #[schemars(description = "Number of starred segments to retrieve (default: 30)")]
+    limit: Option<u32>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetSegmentParams {
+    #[schemars(description = "Strava segment ID")]
+    segment_id: i64,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetSegmentEffortsParams {
+    #[schemars(description = "Strava segment ID")]
+    segment_id: i64,
+    #[schemars(description = "Only include efforts on or after this date (YYYY-MM-DD), or since the start of a period such as 2025 or last month")]
+    since: Option<String>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ListRoutesParams {
+    #[schemars(description = "Number of routes to retrieve (default: 30)")]
+    limit: Option<u32>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct RouteIdParams {
+    #[schemars(description = "Strava route ID")]
+    route_id: i64,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetClubActivitiesParams {
+    #[schemars(description = "Strava club ID")]
+    club_id: i64,
+    #[schemars(description = "Number of activities to retrieve (default: 30)")]
+    limit: Option<u32>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetClubRecentLeaderboardParams {
+    #[schemars(description = "Strava club ID")]
+    club_id: i64,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct ActivityIdParams {
+    #[schemars(description = "Strava activity ID")]
+    activity_id: i64,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct UpdateActivityParams {
+    #[schemars(description = "Strava activity ID")]
+    activity_id: i64,
+    #[schemars(description = "New activity name")]
+    name: Option<String>,
+    #[schemars(description = "New description (empty string clears it)")]
+    description: Option<String>,
+    #[schemars(description = "New sport type, e.g. Run, TrailRun, Ride")]
+    sport_type: Option<String>,
+    #[schemars(description = "Workout type: default, race, long_run or workout")]
+    workout_type: Option<String>,
+    #[schemars(description = "Gear ID to assign, or 'none' to remove gear")]
+    gear_id: Option<String>,
+    #[schemars(description = "Mark as a commute")]
+    commute: Option<bool>,
+    #[schemars(description = "Mark as recorded on a trainer or treadmill")]
+    trainer: Option<bool>,
+    #[schemars(description = "Hide from followers' home feeds")]
+    hide_from_home: Option<bool>,
+    #[schemars(description = "Preview the changes without applying them (default: false)")]
+    dry_run: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DescribeActivityParams {
+    #[schemars(description = "Strava activity ID")]
+    activity_id: i64,
+    #[schemars(description = "Path to an .ics training plan to compare against (default: STRAVA_TRAINING_PLAN)")]
+    plan_path: Option<String>,
+    #[schemars(description = "Save the description to Strava (default: false)")]
+    write: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CreateManualActivityParams {
+    #[schemars(description = "Activity name, e.g. 'Strength: Core'")]
+    name: String,
+    #[schemars(description = "Strava sport type, e.g. Run, WeightTraining, Yoga")]
+    sport_type: String,
+    #[schemars(description = "Local start time in YYYY-MM-DDTHH:MM format")]
+    start_date_local: String,
+    #[schemars(description = "Duration as HH:MM:SS, MM:SS or whole minutes")]
+    duration: String,
+    #[schemars(description = "Distance in kilometers (optional)")]
+    distance_km: Option<f64>,
+    #[schemars(description = "Activity description")]
+    description: Option<String>,
+    #[schemars(description = "Mark as recorded on a trainer or treadmill")]
+    trainer: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct UploadActivityFileParams {
+    #[schemars(description = "Path to a .fit, .gpx or .tcx file (optionally .gz)")]
+    path: String,
+    #[schemars(description = "Activity name (defaults to the name in the file or Strava's default)")]
+    name: Option<String>,
+    #[schemars(description = "Activity description")]
+    description: Option<String>,
+    #[schemars(description = "Mark as recorded on a trainer or treadmill")]
+    trainer: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AuthorizeParams {
+    #[schemars(description = "Port for OAuth callback server (default: 8089, or the configured oauth_port)")]
+    port: Option<u16>,
+    #[schemars(description = "OAuth scope, or several separated by commas (default: 'activity:read_all', or the configured oauth_scope)")]
+    scope: Option<String>,
+    #[schemars(description = "Return the authorization URL instead of opening a browser, for servers on another machine (default: false)")]
+    headless: Option<bool>,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CompleteAuthorizationParams {
+    #[schemars(description = "The full URL Strava redirected to after approving access, including its state parameter")]
+    response: String,
+    #[serde(flatten)]
+    profile: AthleteParams,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct CreateWebhookSubscriptionParams {
+    #[schemars(description = "Public URL Strava calls, ending in /webhook, that reaches the --webhook receiver, e.g. https://example.com/webhook")]
+    callback_url: String,
+    #[schemars(description = "Secret Strava echoes back when validating the callback (default: the configured webhook_verify_token)")]
+    verify_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct DeleteWebhookSubscriptionParams {
+    #[schemars(description = "Subscription ID, as shown by list_webhook_subscriptions")]
+    subscription_id: i64,
+}
+
+/// Add locally imported activities to those from Strava, oldest first.
+///
+/// Files that were also synced to Strava are only counted once.
+fn merge_local(mut activities: Vec<Activity>, local: Vec<Activity>) -> Vec<Activity> {
+    for activity in local {
+        if !activities.iter().any(|a| a.is_same_workout(&activity)) {
+            activities.push(activity);
+        }
+    }
+    activities.sort_by_key(|a| a.start_date);
+    activities
+}
+
+/// Reject IDs that cannot be Strava object IDs
+fn validate_id(name: &str, id: i64) -> Result<(), McpError> {
+    if id <= 0 {
+        return Err(McpError::invalid_params_no_data(format!(
+            "{} must be a positive Strava ID (got: {})",
+            name, id
+        )));
+    }
+    Ok(())
+}
+
+/// "First Last", falling back to the username or athlete ID
+fn athlete_display_name(athlete: &strava_api::DetailedAthlete) -> String {
+    let name = [&athlete.firstname, &athlete.lastname]
+        .into_iter()
+        .filter_map(|part| part.as_deref())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if !name.trim().is_empty() {
+        name.trim().to_string()
+    } else if let Some(username) = &athlete.username {
+        username.clone()
+    } else {
+        format!("Athlete {}", athlete.id)
+    }
+}
+
+/// Distance, duration, pace, elevation and heart rate lines for one run
+fn format_run(run: &Activity, units: Units) -> String {
+    let mut output = String::new();
+    output.push_str(&format!(
+        "- **Distance:** {}\n",
+        units.distance(run.distance)
+    ));
+    output.push_str(&format!(
+        "- **Duration:** {}\n",
+        format_duration(run.moving_time)
+    ));
+    if let Some(avg_speed) = run.average_speed {
+        output.push_str(&format!(
+            "- **Pace:** {}\n",
+            units.pace(avg_speed)
+        ));
+    }
+    output.push_str(&format!(
+        "- **Elevation Gain:** {}\n",
+        units.elevation(run.total_elevation_gain)
+    ));
+
+    if let Some(hr) = run.average_heartrate {
+        output.push_str(&format!("- **Average Heart Rate:** {:.0} bpm\n", hr));
+    }
+    if let Some(max_hr) = run.max_heartrate {
+        output.push_str(&format!("- **Max Heart Rate:** {:.0} bpm\n", max_hr));
+    }
+    output
+}
+
+/// Club feeds only expose first name and last initial
+fn club_athlete_name(athlete: &strava_api::ClubAthlete) -> String {
+    format!("{} {}", athlete.firstname, athlete.lastname).trim().to_string()
+}
+
+/// Format run totals as the markdown list shared by the weekly, monthly and club summaries
+fn format_totals(totals: &Totals, units: Units) -> String {
+    let mut output = String::new();
+    output.push_str(&format!("- **Total Runs:** {}\n", totals.count));
+    output.push_str(&format!(
+        "- **Total Distance:** {}\n",
+        units.distance(totals.distance)
+    ));
+    output.push_str(&format!(
+        "- **Total Time:** {}\n",
+        format_duration(totals.moving_time)
+    ));
+    output.push_str(&format!(
+        "- **Average Pace:** {}\n",
+        units.pace(totals.average_speed())
+    ));
+    output.push_str(&format!(
+        "- **Total Elevation Gain:** {}\n",
+        units.elevation(totals.elevation_gain)
+    ));
+    output
+}
+
+/// One summary line for a sport's totals, e.g. "- **Runs:** 12 (84.20 km, 7h 2m 10s, 420m elevation)"
+fn format_activity_total(sport: &str, total: &strava_api::ActivityTotal, units: Units) -> String {
+    if total.count == 0 {
+        return format!("- **{}:** none\n", sport);
+    }
+
+    format!(
+        "- **{}:** {} ({}, {}, {} elevation)\n",
+        sport,
+        total.count,
+        units.distance(total.distance),
+        format_duration(total.moving_time),
+        units.elevation(total.elevation_gain)
+    )
+}
+
+// Helper methods for McpError
+trait McpErrorExt {
+    fn internal<E: std::fmt::Display>(error: E) -> Self;
+    fn invalid_params_no_data<S: Into<String>>(message: S) -> Self;
+}
+
+impl McpErrorExt for McpError {
+    fn internal<E: std::fmt::Display>(error: E) -> Self {
+        McpError::internal_error(format!("Internal error: {}", error), None)
+    }
+
+    fn invalid_params_no_data<S: Into<String>>(message: S) -> Self {
+        McpError::invalid_params(message.into(), None)
+    }
+}
+
+/// Strava MCP Server - Model Context Protocol server for Strava API integration
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+pub struct Cli {
+    /// Path to .env file containing STRAVA_CLIENT_ID and STRAVA_CLIENT_SECRET
+    #[arg(long)]
+    env_file: Option<String>,
+
+    /// Athlete profile used when a tool call does not name one
+    #[arg(long, default_value = profiles::DEFAULT_PROFILE)]
+    profile: String,
+
+    /// Config file (default: STRAVA_MCP_CONFIG or ~/.config/strava-mcp/config.toml)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Distance units: metric or imperial
+    #[arg(long, global = true)]
+    units: Option<String>,
+
+    /// IANA timezone for day boundaries, e.g. Europe/London
+    #[arg(long, global = true)]
+    timezone: Option<String>,
+
+    /// Sport the run tools list: run, all, or a Strava sport type such as TrailRun
+    #[arg(long, global = true)]
+    sport: Option<String>,
+
+    /// Maximum heart rate, used for zones when none are configured
+    #[arg(long, global = true)]
+    max_hr: Option<String>,
+
+    /// Runs get_recent_runs returns by default
+    #[arg(long, global = true)]
+    default_limit: Option<String>,
+
+    /// Most runs get_recent_runs returns
+    #[arg(long, global = true)]
+    max_limit: Option<String>,
+
+    /// Default port for the OAuth callback server
+    #[arg(long, global = true)]
+    oauth_port: Option<String>,
+
+    /// Default OAuth scope for authorize
+    #[arg(long, global = true)]
+    oauth_scope: Option<String>,
+
+    /// Directory for tokens, imported activities and profiles (default: ~/.strava)
+    #[arg(long, global = true)]
+    cache_dir: Option<String>,
+
+    /// Comma-separated tools to enable (default: all)
+    #[arg(long, global = true)]
+    tools: Option<String>,
+
+    /// Comma-separated tools to disable
+    #[arg(long, global = true)]
+    deny_tools: Option<String>,
+
+    /// Disable tools that change Strava data or start an OAuth flow
+    #[arg(long, global = true)]
+    read_only: bool,
+
+    /// Run the webhook receiver for Strava push subscription events
+    #[arg(long, global = true)]
+    webhook: bool,
+
+    /// Address the webhook receiver listens on (default: 127.0.0.1:8090)
+    #[arg(long, global = true)]
+    webhook_addr: Option<String>,
+
+    /// Log level, or filter directives such as info,rmcp=debug (default: STRAVA_LOG or info)
+    #[arg(long, global = true)]
+    log_level: Option<String>,
+
+    /// Append logs to this file instead of stderr (default: STRAVA_LOG_FILE)
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Log format: text or json (default: STRAVA_LOG_FORMAT or text)
+    #[arg(long, global = true)]
+    log_format: Option<String>,
+
+    /// Print the raw tool result as JSON instead of markdown
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(subcommand)]
+    command: Option<cli::Command>,
+}
+
+/// Run the MCP server, or the single tool a subcommand asks for
+pub async fn run(cli: Cli) -> Result<()> {
+    // Load environment variables from specified file or default location
+    let env_file = match cli.env_file {
+        Some(env_file) => {
+            dotenvy::from_filename(&env_file)
+                .with_context(|| format!("Failed to load .env file from: {}", env_file))?;
+            Some(env_file)
+        }
+        None => {
+            // Try to load from current directory, but don't fail if not found
+            dotenvy::dotenv().ok();
+            None
+        }
+    };
+
+    // Logging settings may come from the .env file, so start logging once it is loaded
+    let log_level = cli.log_level.or_else(|| std::env::var("STRAVA_LOG").ok());
+    let log_file = cli.log_file.or_else(|| std::env::var("STRAVA_LOG_FILE").ok().map(PathBuf::from));
+    let log_format = match cli.log_format.or_else(|| std::env::var("STRAVA_LOG_FORMAT").ok()) {
+        Some(format) => format.parse()?,
+        None => logging::LogFormat::Text,
+    };
+    logging::init(log_level.as_deref().unwrap_or(logging::DEFAULT_LEVEL), log_format, log_file.as_deref())?;
+    if let Some(env_file) = env_file {
+        tracing::info!(path = %env_file, "loaded environment");
+    }
+
+    // Layer the config file, environment and command line
+    let (config_path, required) = match cli.config.or_else(|| std::env::var("STRAVA_MCP_CONFIG").ok().map(PathBuf::from)) {
+        Some(path) => (path, true),
+        None => (Config::default_path()?, false),
+    };
+    let cli_layer = config::layer(&[
+        ("units", cli.units),
+        ("timezone", cli.timezone),
+        ("sport", cli.sport),
+        ("max_hr", cli.max_hr),
+        ("default_limit", cli.default_limit),
+        ("max_limit", cli.max_limit),
+        ("oauth_port", cli.oauth_port),
+        ("oauth_scope", cli.oauth_scope),
+        ("cache_dir", cli.cache_dir),
+        ("tools", cli.tools),
+        ("deny_tools", cli.deny_tools),
+        // Only an explicit flag overrides the environment and config file
+        ("read_only", cli.read_only.then(|| "true".to_string())),
+        ("webhook", cli.webhook.then(|| "true".to_string())),
+        ("webhook_addr", cli.webhook_addr),
+    ]);
+    let config = Config::load(config_path, required, config::env_layer(), cli_layer)?;
+    if config.path.exists() {
+        tracing::info!(path = %config.path.display(), "loaded config");
+    }
+
+    // Catch misspelled tool names rather than silently serving fewer tools
+    let router = StravaMcpServer::tool_router();
+    let mut listed = config.tools.iter().flatten().chain(&config.deny_tools);
+    if let Some(unknown) = listed.find(|name| !router.has_route(name)) {
+        anyhow::bail!("Unknown tool '{}' in tools or deny_tools. Run `strava-mcp tools` to list them.", unknown);
+    }
+    if config.read_only {
+        tracing::info!(disabled = %config::WRITE_TOOLS.join(", "), "read-only mode");
+    }
+
+    // Load the default profile up front so configuration errors show at startup
+    profiles::validate_name(&cli.profile)?;
+    let paths = ProfilePaths::new(&config.cache_dir, &cli.profile);
+    let token_key = TokenKey::from_env()?;
+    let profile = Profile::load(&config, &cli.profile, token_key.clone())?;
+
+    if paths.token.exists() {
+        let encryption = if token_key.is_some() { "encrypted" } else { "plaintext" };
+        tracing::info!(encryption, path = %paths.token.display(), "loaded saved authentication token");
+    } else {
+        tracing::warn!(profile = %cli.profile, "no saved token found; use the 'authorize' tool to authenticate");
+    }
+    let store_len = profile.store.read().await.len();
+    if store_len > 0 {
+        tracing::info!(activities = store_len, path = %paths.store.display(), "loaded locally imported activities");
+    }
+
+    // Load gear retirement thresholds
+    let gear_thresholds = RetirementThresholds::from_env()
+        .context("Invalid gear retirement threshold")?;
+
+    // Create MCP server
+    let server = StravaMcpServer::new(config, profile, token_key, gear_thresholds);
+
+    // Subcommands other than serve run a single tool and exit
+    match cli.command {
+        None | Some(cli::Command::Serve) => {}
+        Some(command) => return cli::run(&server, command, cli.json).await,
+    }
+
+    // Events are applied to the local stores while the MCP server runs
+    if server.config.webhook {
+        let listener = tokio::net::TcpListener::bind(server.config.webhook_addr)
+            .await
+            .with_context(|| format!("Failed to bind webhook receiver to {}", server.config.webhook_addr))?;
+        let verify_token = server.config.webhook_verify_token.clone().unwrap_or_default();
+        let (sender, events) = tokio::sync::mpsc::channel(webhook::QUEUE_LEN);
+        tracing::info!(addr = %server.config.webhook_addr, path = webhook::PATH, "webhook receiver listening");
+        tokio::spawn(async move {
+            if let Err(e) = webhook::serve(listener, verify_token, sender).await {
+                tracing::error!(error = %format!("{:#}", e), "webhook receiver stopped");
+            }
+        });
+        tokio::spawn(server.clone().apply_webhook_events(events));
+    }
+
+    // Create stdio transport
+    let transport = (stdin(), stdout());
+
+    // Serve
+    tracing::info!("starting Strava MCP server");
+    let service = server.serve(transport).await.map_err(|e| {
+        tracing::error!(error = %e, "error starting server");
+        e
+    })?;
+
+    service.waiting().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::handler::server::wrapper::Parameters;
+    use serde_json::json;
+    use source::FixtureSource;
+
+    /// A test server whose base directory is removed when it is dropped
+    struct TestServer(StravaMcpServer);
+
+    impl std::ops::Deref for TestServer {
+        type Target = StravaMcpServer;
+
+        fn deref(&self) -> &StravaMcpServer {
+            &self.0
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0.base_dir);
+        }
+    }
+
+    /// A server whose default profile reads Strava data from tests/fixtures/athlete.json
+    fn server(name: &str) -> TestServer {
+        server_with_config(name, &[])
+    }
+
+    /// As `server`, with config values as if given on the command line
+    fn server_with_config(name: &str, values: &[(&str, &str)]) -> TestServer {
+        server_with(name, values, ProfileSettings::default())
+    }
+
+    /// As `server`, with the default profile's settings
+    fn server_with_settings(name: &str, settings: ProfileSettings) -> TestServer {
+        server_with(name, &[], settings)
+    }
+
+    fn server_with(name: &str, values: &[(&str, &str)], settings: ProfileSettings) -> TestServer {
+        let base_dir = std::env::temp_dir().join(format!("strava-mcp-tools-{}-{}", name, std::process::id()));
+        let mut values: Vec<_> = values.iter().map(|(key, value)| (*key, Some(value.to_string()))).collect();
+        values.push(("cache_dir", Some(base_dir.display().to_string())));
+        let config = Config::load(base_dir.join("config.toml"), false, toml::Table::new(), config::layer(&values)).unwrap();
+        let paths = ProfilePaths::new(&base_dir, profiles::DEFAULT_PROFILE);
+        let profile = Profile {
+            name: profiles::DEFAULT_PROFILE.to_string(),
+            oauth: OAuthConfig::new("client", "secret"),
+            auth_client: AuthenticatedClient::new(OAuthConfig::new("client", "secret")),
+            source: Some(Arc::new(FixtureSource::load("athlete.json"))),
+            token_file: TokenFile::new(paths.token, None),
+            saved_access_token: tokio::sync::Mutex::new(None),
+            pending_authorization: tokio::sync::Mutex::new(None),
+            store: RwLock::new(LocalStore::load(&paths.store).unwrap()),
+            settings,
+        };
+        TestServer(StravaMcpServer::new(config, profile, None, RetirementThresholds::default()))
+    }
+
+    fn params<T: serde::de::DeserializeOwned>(args: serde_json::Value) -> Parameters<T> {
+        Parameters(serde_json::from_value(args).unwrap())
+    }
+
+    fn text(result: Result<CallToolResult, McpError>) -> String {
+        let result = result.unwrap_or_else(|e| panic!("tool failed: {}", e.message));
+        result
+            .content
+            .iter()
+            .filter_map(|c| c.as_text())
+            .map(|t| t.text.as_str())
+            .collect()
+    }
+
+    fn fixture_path(name: &str) -> String {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+            .display()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_get_runs_for_date() {
+        let server = server("runs-for-date");
+
+        let output = text(server.get_runs_for_date(params(json!({ "date": "2026-02-11" }))).await);
+        assert!(output.starts_with("# Runs for 2026-02-11\n\n## Tempo Run\n"));
+        assert!(output.contains("- **Pace:** 5:00/km\n"));
+        assert!(!output.contains("Commute Ride"));
+
+        let output = text(server.get_runs_for_date(params(json!({ "date": "2026-02-10" }))).await);
+        assert_eq!(output, "No runs found for 2026-02-10");
+
+        assert!(server.get_runs_for_date(params(json!({ "date": "11/02/2026" }))).await.is_err());
+        let error = server.get_runs_for_date(params(json!({ "date": "2008-06-01" }))).await.unwrap_err();
+        assert_eq!(error.message, "2008-06-01 is before Strava existed (min: 2009-01-01)");
+    }
+
+    #[tokio::test]
+    async fn test_get_recent_runs() {
+        let server = server("recent-runs");
+
+        let output = text(server.get_recent_runs(params(json!({ "limit": 2 }))).await);
+        assert!(output.starts_with("# 2 Most Recent Runs\n\n## Parkrun (2026-03-07)\n"));
+        assert!(output.contains("## Long Run (2026-02-15)\n"));
+        assert!(!output.contains("Tempo Run"));
+
+        assert!(server.get_recent_runs(params(json!({ "limit": 0 }))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_weekly_summary() {
+        let server = server("weekly-summary");
+
+        let output = text(server.get_weekly_summary(params(json!({ "week_start": "2026-02-09" }))).await);
+        assert!(output.starts_with("# Weekly Summary: 2026-02-09 to 2026-02-15\n\n- **Total Runs:** 3\n"));
+        assert!(output.contains("- **Total Distance:** 29.00 km\n"));
+        assert!(output.contains("- **Warning:** Pegasus 40 has 712 km, past its 700 km retirement threshold\n"));
+        // Retired shoes are not flagged again
+        assert!(!output.contains("Pegasus 38"));
+
+        let output = text(server.get_weekly_summary(params(json!({ "week_start": "2026-W07", "gear_warnings": false }))).await);
+        assert!(output.starts_with("# Weekly Summary: 2026-02-09 to 2026-02-15\n\n- **Total Runs:** 3\n"));
+        assert!(!output.contains("- **Warning:**"));
+
+        assert!(server.get_weekly_summary(params(json!({ "week_start": "2026-02" }))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_gear_report() {
+        let server = server("gear-report");
+
+        let output = text(server.get_gear_report(params(json!({}))).await);
+        assert!(output.contains("## Shoes\n\n### Pegasus 40 (primary)\n- **Total Distance:** 712.00 km\n"));
+        assert!(output.contains("past the 700 km threshold by 12 km"));
+        assert!(output.contains("### Speedgoat 5\n"));
+        assert!(output.contains("### Pegasus 38 (retired)\n- **Total Distance:** 950.00 km\n- **Last 30 Days:**"));
+        assert!(!output.contains("past the 700 km threshold by 250 km"));
+        assert!(output.contains("## Bikes\n\n### Commuter (primary)\n"));
+
+        assert!(server.get_gear_report(params(json!({ "days": 0 }))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_runs_for_month() {
+        let server = server("runs-for-month");
+
+        let output = text(server.get_runs_for_month(params(json!({ "month": "2026-02" }))).await);
+        assert!(output.starts_with("# Runs for 2026-02\n\nFound 3 runs\n\n## Easy Run (2026-02-09)\n"));
+        assert!(!output.contains("Parkrun"));
+
+        assert!(server.get_runs_for_month(params(json!({ "month": "2026-13" }))).await.is_err());
+
+        let output = text(server.get_runs_for_month(params(json!({ "month": "2026-Q1" }))).await);
+        assert!(output.starts_with("# Runs for 2026-Q1\n\nFound 4 runs\n"));
+        assert!(output.contains("Parkrun"));
+    }
+
+    #[tokio::test]
+    async fn test_get_monthly_running_summary() {
+        let server = server("monthly-summary");
+
+        let output = text(server.get_monthly_running_summary(params(json!({ "month": "2026-02" }))).await);
+        assert!(output.contains("- **Total Runs:** 3\n"));
+        assert!(output.contains("## Averages per Run\n- **Average Distance:** 9.67 km\n"));
+
+        let output = text(server.get_monthly_running_summary(params(json!({ "month": "2026-01" }))).await);
+        assert_eq!(output, "No runs found for 2026-01");
+
+        let output = text(server.get_monthly_running_summary(params(json!({ "month": "2026-W07" }))).await);
+        assert!(output.starts_with("# Running Summary: 2026-W07\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_athlete_profile() {
+        let server = server("athlete-profile");
+
+        let output = text(server.get_athlete_profile(params(json!({}))).await);
+        assert!(output.starts_with("# Sam Runner\n\n- **Athlete ID:** 1001\n- **Username:** samruns\n"));
+        assert!(output.contains("- **Location:** Leeds, United Kingdom\n"));
+        assert!(output.contains("- **Member Since:** 2018-04-01\n"));
+        assert!(output.contains("- **Gear:** 3 shoes, 1 bikes\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_athlete_stats() {
+        let server = server("athlete-stats");
+
+        let output = text(server.get_athlete_stats(params(json!({}))).await);
+        assert!(output.starts_with("# Stats for Sam Runner\n\n## Last 4 Weeks\n"));
+        assert!(output.contains("## All Time\n"));
+        assert!(output.contains("- **Biggest Ride:** 120.50 km\n"));
+        assert!(output.contains("- **Biggest Climb:** 850m\n"));
+    }
+
+    #[tokio::test]
+    async fn test_list_starred_segments() {
+        let server = server("starred-segments");
+
+        let output = text(server.list_starred_segments(params(json!({}))).await);
+        assert!(output.starts_with("# 1 Starred Segments\n\n## Hill Climb (ID: 201)\n"));
+        assert!(output.contains("- **Elevation Difference:** 42m\n"));
+        assert!(output.contains("- **Your PR:** 4m 5s\n- **Your Efforts:** 3\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_segment() {
+        let server = server("segment");
+
+        let output = text(server.get_segment(params(json!({ "segment_id": 201 }))).await);
+        assert!(output.starts_with("# Hill Climb\n\n"));
+        assert!(output.contains("- **Popularity:** 5400 efforts by 1200 athletes\n"));
+        assert!(output.contains("## Your Record\n- **PR:** 4m 5s\n- **PR Date:** 2026-02-11\n"));
+
+        assert!(server.get_segment(params(json!({ "segment_id": 999 }))).await.is_err());
+        assert!(server.get_segment(params(json!({ "segment_id": 0 }))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_segment_efforts() {
+        let server = server("segment-efforts");
+
+        let output = text(server.get_segment_efforts(params(json!({ "segment_id": 201 }))).await);
+        assert!(output.starts_with("# Effort History: Hill Climb\n\n- **Efforts:** 3\n- **Best:** 4m 5s (2026-02-11)\n"));
+        assert!(output.contains("## Efforts (most recent 3)\n- 2026-02-11: 4m 5s"));
+        assert!(output.contains(", PR #1, KOM/QOM #8\n"));
+
+        let output = text(server.get_segment_efforts(params(json!({ "segment_id": 201, "since": "2026-01-01" }))).await);
+        assert!(output.contains("- **Efforts:** 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_activity_segment_efforts() {
+        let server = server("activity-segment-efforts");
+
+        let output = text(server.get_activity_segment_efforts(params(json!({ "activity_id": 102 }))).await);
+        assert!(output.starts_with("# Segment Efforts: Tempo Run\n\n## Hill Climb (ID: 201)\n"));
+        assert!(output.contains("- **Personal Rank:** #1\n- **KOM/QOM Rank:** #8\n"));
+    }
+
+    #[tokio::test]
+    async fn test_list_routes() {
+        let server = server("routes");
+
+        let output = text(server.list_routes(params(json!({}))).await);
+        assert!(output.starts_with("# 2 Saved Routes\n\n## Canal Loop (ID: 301)\n- **Type:** Road run\n"));
+        assert!(output.contains("## Moor Trail (ID: 302)\n- **Type:** Trail run\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_route() {
+        let server = server("route");
+
+        // The fixture's activities are too old for a pace estimate, so Strava's is used
+        let output = text(server.get_route(params(json!({ "route_id": 301 }))).await);
+        assert!(output.starts_with("# Canal Loop\n\nFlat towpath loop\n\n- **Type:** Road run\n"));
+        assert!(output.contains("- **Estimated Time:** 1h 6m 40s (Strava estimate"));
+
+        let error = server.get_route(params(json!({ "route_id": 999 }))).await.unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+        assert!(error.message.starts_with("Strava has no route with that ID"));
+    }
+
+    #[tokio::test]
+    async fn test_export_route_gpx() {
+        let server = server("route-gpx");
+
+        let output = text(server.export_route_gpx(params(json!({ "route_id": 301 }))).await);
+        assert!(output.contains("<name>Canal Loop</name>"));
+    }
+
+    #[tokio::test]
+    async fn test_list_clubs() {
+        let server = server("clubs");
+
+        let output = text(server.list_clubs(params(json!({}))).await);
+        assert!(output.starts_with("# 1 Clubs\n\n## Leeds Harriers (ID: 401)\n- **Sport:** running\n- **Members:** 250\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_club_activities() {
+        let server = server("club-activities");
+
+        let output = text(server.get_club_activities(params(json!({ "club_id": 401, "limit": 2 }))).await);
+        assert!(output.starts_with("# 2 Recent Club Activities\n\n## Alex B. — Morning Run\n"));
+        assert!(output.contains("## Jo C. — Hill Reps\n"));
+    }
+
+    #[tokio::test]
+    async fn test_get_club_recent_leaderboard() {
+        let server = server("club-leaderboard");
+
+        let output = text(server.get_club_recent_leaderboard(params(json!({ "club_id": 401 }))).await);
+        assert!(output.starts_with("# Club Recent Leaderboard\n\n"));
+        assert!(output.contains("- **Total Runs:** 3\n"));
+        assert!(output.contains("## By Distance\n1. **Alex B.** — 15.00 km (2 runs, 5:20/km)\n2. **Jo C.** — 6.00 km (1 runs, 5:50/km)\n"));
+        assert!(output.contains("## By Time\n1. **Alex B.** — 1h 20m 0s (60m elevation)\n"));
+
+        // Rankings use the profile's units, like the totals above them
+        let imperial = ProfileSettings { units: Units::Imperial, ..Default::default() };
+        let server = server_with_settings("club-leaderboard-imperial", imperial);
+        let output = text(server.get_club_recent_leaderboard(params(json!({ "club_id": 401 }))).await);
+        assert!(output.contains("- **Total Distance:** 13.05 mi\n"));
+        assert!(output.contains("## By Distance\n1. **Alex B.** — 9.32 mi (2 runs, 8:35/mi)\n"));
+        assert!(output.contains("## By Time\n1. **Alex B.** — 1h 20m 0s (197ft elevation)\n"));
+        assert!(!output.contains(" km"));
+    }
+
+    #[tokio::test]
+    async fn test_update_activity() {
+        let server = server("update-activity");
+
+        let output = text(
+            server
+                .update_activity(params(json!({ "activity_id": 102, "name": "Tempo Tuesday", "workout_type": "workout", "dry_run": true })))
+                .await,
+        );
+        assert!(output.starts_with("# Dry Run: Tempo Run (ID: 102)\n\n"));
+        assert!(output.contains("- **Name:** Tempo Run → Tempo Tuesday\n- **Workout Type:** Default → Workout\n"));
+
+        let output = text(server.update_activity(params(json!({ "activity_id": 102, "name": "Tempo Tuesday" }))).await);
+        assert!(output.starts_with("# Updated Tempo Run (ID: 102)\n"));
+
+        let output = text(server.update_activity(params(json!({ "activity_id": 102, "name": "Tempo Tuesday" }))).await);
+        assert_eq!(output, "No changes to make to Tempo Tuesday (ID: 102)");
+    }
+
+    #[tokio::test]
+    async fn test_describe_activity() {
+        let server = server("describe-activity");
+
+        let output = text(server.describe_activity(params(json!({ "activity_id": 102 }))).await);
+        assert!(output.contains(
+            "## Description\n\n\
+             3.00 km in 15m 0s (5:00/km), 12m climbing\n\
+             Splits: 5:10, 5:00, 4:50 (fastest km 3 at 4:50/km), negative split\n\
+             HR zones: Z1 33%, Z2 33%, Z3 33%\n\
+             PR: 1k in 4m 50s\n\
+             Segment PR: Hill Climb in 4m 5s\n\
+             2nd run this week, 11.00 km week so far\n"
+        ));
+        assert!(output.ends_with("Not saved. Run again with write set to true to save it to Strava.\n"));
+
+        // Writing again replaces the generated text instead of adding a second copy
+        let output = text(server.describe_activity(params(json!({ "activity_id": 102, "write": true }))).await);
+        assert!(output.ends_with("Saved to Strava.\n"), "{}", output);
+        let output = text(server.describe_activity(params(json!({ "activity_id": 102, "write": true }))).await);
+        assert!(output.ends_with("Saved to Strava, replacing the previously generated text.\n"), "{}", output);
+        let profile = server.profile(None).await.unwrap();
+        let saved = profile.client().await.unwrap().get_activity(102, false).await.unwrap().description.unwrap();
+        assert_eq!(saved.matches(description::BLOCK_START).count(), 1, "{}", saved);
+    }
+
+    #[tokio::test]
+    async fn test_create_manual_activity() {
+        let server = server("create-activity");
+
+        let output = text(
+            server
+                .create_manual_activity(params(json!({
+                    "name": "Strength: Core",
+                    "sport_type": "WeightTraining",
+                    "start_date_local": "2026-02-12T18:00",
+                    "duration": "45"
+                })))
+                .await,
+        );
+        assert!(output.starts_with("# Created Strength: Core (ID: 9000000001)\n\n- **Type:** WeightTraining\n"));
+        assert!(output.contains("- **Start:** 2026-02-12 18:00\n- **Duration:** 45m 0s\n"));
+        assert!(!output.contains("Distance"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_activity_file() {
+        let server = server("upload");
+
+        let output = text(server.upload_activity_file(params(json!({ "path": fixture_path("morning_run.gpx") }))).await);
+        assert_eq!(output, "# Uploaded morning_run.gpx\n\n- **Activity ID:** 9000000002\n- **Upload ID:** 42\n");
+
+        assert!(server.upload_activity_file(params(json!({ "path": fixture_path("athlete.json") }))).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_profiles() {
+        let server = server("list-profiles");
+
+        let output = text(server.list_profiles().await);
+        assert!(output.starts_with("# 1 Profiles\n\n## default (default)\n- **Authorized:** no\n- **Units:** metric\n"));
+    }
+
+    #[tokio::test]
+    async fn test_import_activity_files() {
+        let server = server("import-files");
+        let directory = server.base_dir.join("files");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::copy(fixture_path("morning_run.gpx"), directory.join("morning_run.gpx")).unwrap();
+
+        let output = text(server.import_activity_files(params(json!({ "directory": directory }))).await);
+        assert!(output.starts_with("# Imported 1 activities\n"));
+
+        // Imported files are listed alongside Strava activities
+        let output = text(server.get_runs_for_month(params(json!({ "month": "2026-02" }))).await);
+        assert!(output.contains("Found 4 runs\n"));
+        assert!(output.contains("## Morning Run (2026-02-13)\n"));
+
+        // Importing again finds the same workout
+        let output = text(server.import_activity_files(params(json!({ "directory": directory }))).await);
+        assert!(output.contains("- **Already imported:** 1\n"));
+    }
+
+    #[tokio::test]
+    async fn test_import_strava_archive() {
+        use std::io::Write;
+
+        let server = server("import-archive");
+        std::fs::create_dir_all(&server.base_dir).unwrap();
+        let archive = server.base_dir.join("export.zip");
+
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("activities.csv", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(
+            b"Activity ID,Activity Date,Activity Name,Activity Type,Elapsed Time,Distance\n\
+              900,\"Jan 20, 2026, 6:00:00 PM\",Gym,Weight Training,3600,0\n",
+        )
+        .unwrap();
+        zip.finish().unwrap();
+
+        let output = text(server.import_strava_archive(params(json!({ "path": archive }))).await);
+        assert!(output.starts_with("# Imported 1 activities\n"));
+        assert!(output.contains("- **Failed:** 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_export_activity_gpx_and_tcx() {
+        let server = server("export-activity");
+
+        let gpx = text(server.export_activity_gpx(params(json!({ "activity_id": 102 }))).await);
+        assert!(gpx.contains("<name>Tempo Run</name>"));
+        assert_eq!(gpx.matches("<trkpt ").count(), 4);
+
+        let tcx = text(server.export_activity_tcx(params(json!({ "activity_id": 102 }))).await);
+        assert!(tcx.contains("<TrainingCenterDatabase"));
+        assert_eq!(tcx.matches("<Trackpoint>").count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_show_config() {
+        let server = server("show-config");
+
+        let output = text(server.show_config(params(json!({}))).await);
+        assert!(output.starts_with("# Configuration\n\n- **Config File:** "));
+        assert!(output.contains("- **Enabled Tools:** 35 of 35\n"));
+        assert!(output.contains("- **default_limit:** 5 (default)\n"));
+        assert!(output.contains(&format!("- **cache_dir:** {} (command line)\n", server.base_dir.display())));
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let server = server_with_config("read-only", &[("read_only", "true"), ("deny_tools", "list_clubs")]);
+
+        for tool in config::WRITE_TOOLS.iter().chain(&["list_clubs"]) {
+            assert!(!server.tool_router.has_route(tool), "{} should be disabled", tool);
+        }
+        assert!(server.tool_router.has_route("describe_activity"));
+
+        // Descriptions can still be composed, but not saved
+        let output = text(server.describe_activity(params(json!({ "activity_id": 102 }))).await);
+        assert!(output.contains("PR: 1k in 4m 50s"));
+        let error = server
+            .describe_activity(params(json!({ "activity_id": 102, "write": true })))
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("read-only"));
+
+        let output = text(server.show_config(params(json!({}))).await);
+        assert!(output.contains("- **Enabled Tools:** 26 of 35\n- **Disabled Tools:** authorize, complete_authorization, create_manual_activity, "));
+        assert!(output.contains("- **read_only:** true (command line)\n"));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_events() {
+        let server = server("webhook");
+        let event = |object_type, object_id, aspect_type, owner_id, updates: serde_json::Value| {
+            serde_json::from_value::<webhook::Event>(json!({
+                "object_type": object_type,
+                "object_id": object_id,
+                "aspect_type": aspect_type,
+                "owner_id": owner_id,
+                "subscription_id": 120475,
+                "event_time": 1771318200,
+                "updates": updates,
+            }))
+            .unwrap()
+        };
+        let cached = || async { server.profile(None).await.unwrap().store.read().await.between(None, None) };
+
+        server.handle_webhook_event(&event("activity", 102, "create", 1001, json!({}))).await.unwrap();
+        let activities = cached().await;
+        assert_eq!(activities.len(), 1);
+        assert_eq!((activities[0].id, activities[0].name.as_str()), (Some(102), "Tempo Run"));
+
+        // Strava still has the activity, so a delete event changes nothing
+        server.handle_webhook_event(&event("activity", 102, "delete", 1001, json!({}))).await.unwrap();
+        assert_eq!(cached().await.len(), 1);
+
+        // Unknown activities and other athletes' events are ignored
+        server.handle_webhook_event(&event("activity", 999, "create", 1001, json!({}))).await.unwrap();
+        server.handle_webhook_event(&event("activity", 103, "create", 7, json!({}))).await.unwrap();
+        assert_eq!(cached().await.len(), 1);
+
+        // The profile keeps working until Strava rejects its token
+        let revoked = event("athlete", 1001, "update", 1001, json!({ "authorized": "false" }));
+        server.handle_webhook_event(&revoked).await.unwrap();
+        assert!(server.profiles.read().await.contains_key(profiles::DEFAULT_PROFILE));
+    }
+
+    #[tokio::test]
+    async fn test_auth_tools_without_token() {
+        let server = server("auth");
+
+        let output = text(server.get_auth_status(params(json!({}))).await);
+        assert!(output.contains("- **Authorized:** no\n"));
+
+        let output = text(server.deauthorize(params(json!({}))).await);
+        assert_eq!(output, "Profile 'default' is not authorized; nothing to revoke.");
+
+        let error = server
+            .complete_authorization(params(json!({ "response": "http://localhost:8089/?state=abc&code=def" })))
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("No authorization in progress"));
+    }
+}
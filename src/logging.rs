@@ -0,0 +1,241 @@
+use anyhow::{bail, Context, Result};
+use rmcp::model::{CallToolResult, LoggingLevel, LoggingMessageNotificationParam};
+use rmcp::service::{Peer, RoleServer};
+use rmcp::ErrorData as McpError;
+use serde_json::{Map, Value};
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::{Context as LayerContext, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+/// Log level used when neither --log-level nor STRAVA_LOG is set
+pub const DEFAULT_LEVEL: &str = "info";
+
+/// Tool arguments whose values are never logged: the authorization redirect and the webhook verify token
+const REDACTED: &[&str] = &["response", "verify_token"];
+
+/// Client that asked for log notifications with logging/setLevel, and the lowest level it wants
+static CLIENT: Mutex<Option<(Peer<RoleServer>, LoggingLevel)>> = Mutex::new(None);
+
+/// How log lines are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("Invalid log format '{}'. Use text or json.", s),
+        }
+    }
+}
+
+/// Send logs to stderr, or append them to `file`.
+///
+/// `level` is a level such as "debug", applied to this server only, or filter directives such as
+/// "info,rmcp=debug".
+pub fn init(level: &str, format: LogFormat, file: Option<&Path>) -> Result<()> {
+    let filter = filter(level)?;
+    let (writer, ansi) = match file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file {}", path.display()))?;
+            (BoxMakeWriter::new(Mutex::new(file)), false)
+        }
+        None => (BoxMakeWriter::new(std::io::stderr), std::io::stderr().is_terminal()),
+    };
+    let output = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().with_writer(writer).with_ansi(ansi).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer().json().with_writer(writer).boxed(),
+    };
+    tracing_subscriber::registry()
+        .with(output.with_filter(filter))
+        .with(ClientLayer)
+        .try_init()
+        .context("Failed to initialise logging")
+}
+
+fn filter(level: &str) -> Result<EnvFilter> {
+    // A bare level applies to this server; dependencies stay at warn unless named
+    let directives = if level.contains(['=', ',']) {
+        level.to_string()
+    } else {
+        format!("warn,strava_mcp={}", level)
+    };
+    EnvFilter::try_new(directives).with_context(|| format!("Invalid log level '{}'", level))
+}
+
+/// Forward this server's log events at `level` and above to the client, as MCP log notifications
+pub fn forward_to_client(peer: Peer<RoleServer>, level: LoggingLevel) {
+    if let Ok(mut client) = CLIENT.lock() {
+        *client = Some((peer, level));
+    }
+}
+
+/// Span covering one tool call
+pub fn tool_span(tool: &str, arguments: Option<&Map<String, Value>>) -> tracing::Span {
+    tracing::info_span!("tool_call", tool, params = %redact(arguments))
+}
+
+/// Log how a tool call ended and how long it took
+pub fn record_call(tool: &str, started: Instant, result: &Result<CallToolResult, McpError>) {
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(result) if result.is_error == Some(true) => tracing::warn!(tool, elapsed_ms, "tool returned an error"),
+        Ok(_) => tracing::info!(tool, elapsed_ms, "tool call finished"),
+        Err(e) => tracing::warn!(tool, elapsed_ms, error = %e.message, "tool call failed"),
+    }
+}
+
+/// Tool arguments as JSON, with secrets masked
+fn redact(arguments: Option<&Map<String, Value>>) -> String {
+    let mut arguments = arguments.cloned().unwrap_or_default();
+    for (key, value) in arguments.iter_mut() {
+        if REDACTED.contains(&key.as_str()) {
+            *value = Value::String("[redacted]".to_string());
+        }
+    }
+    Value::Object(arguments).to_string()
+}
+
+/// Sends this server's events to the client that enabled logging
+struct ClientLayer;
+
+impl<S: Subscriber> Layer<S> for ClientLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: LayerContext<'_, S>) {
+        // Only this server's events: forwarding rmcp's own would log every notification sent
+        let metadata = event.metadata();
+        if !metadata.target().starts_with("strava_mcp") {
+            return;
+        }
+        let Some((peer, min)) = CLIENT.lock().ok().and_then(|client| client.clone()) else {
+            return;
+        };
+        let level = logging_level(*metadata.level());
+        if rank(level) < rank(min) {
+            return;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let message = LoggingMessageNotificationParam {
+            level,
+            logger: Some(metadata.target().to_string()),
+            data: Value::Object(fields.0),
+        };
+        runtime.spawn(async move {
+            let _ = peer.notify_logging_message(message).await;
+        });
+    }
+}
+
+/// Event fields as a JSON object
+#[derive(Default)]
+struct JsonFields(Map<String, Value>);
+
+impl Visit for JsonFields {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+fn logging_level(level: Level) -> LoggingLevel {
+    match level {
+        Level::ERROR => LoggingLevel::Error,
+        Level::WARN => LoggingLevel::Warning,
+        Level::INFO => LoggingLevel::Info,
+        Level::DEBUG | Level::TRACE => LoggingLevel::Debug,
+    }
+}
+
+/// Severity order of MCP log levels, lowest first
+fn rank(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let directives = filter("debug").unwrap().to_string();
+        assert!(directives.contains("strava_mcp=debug"));
+        assert!(directives.split(',').any(|d| d == "warn"));
+        assert!(filter("info,rmcp=debug").unwrap().to_string().contains("rmcp=debug"));
+        assert!(filter("loud").is_err());
+    }
+
+    #[test]
+    fn test_log_format() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("Text".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("xml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn test_redact() {
+        let arguments = serde_json::json!({"response": "https://localhost/?code=secret", "athlete": "me"});
+        let logged = redact(arguments.as_object());
+        assert!(!logged.contains("secret"));
+        assert!(logged.contains("[redacted]"));
+        assert!(logged.contains("\"athlete\":\"me\""));
+        assert_eq!(redact(None), "{}");
+
+        let arguments = serde_json::json!({"callback_url": "https://example.com/webhook", "verify_token": "hunter2"});
+        let logged = redact(arguments.as_object());
+        assert!(!logged.contains("hunter2"));
+        assert!(logged.contains("\"verify_token\":\"[redacted]\""));
+        assert!(logged.contains("https://example.com/webhook"));
+    }
+
+    #[test]
+    fn test_rank() {
+        assert!(rank(logging_level(Level::ERROR)) > rank(LoggingLevel::Warning));
+        assert!(rank(logging_level(Level::TRACE)) < rank(LoggingLevel::Info));
+    }
+}
@@ -1,789 +1,7 @@
-mod utils;
-
-use anyhow::{Context, Result};
-use chrono::{Datelike, Duration, NaiveDate, Utc};
 use clap::Parser;
-use rmcp::{
-    handler::server::tool::ToolRouter,
-    model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
-    tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler, ServiceExt,
-};
-use schemars::JsonSchema;
-use serde::Deserialize;
-use std::sync::Arc;
-use strava_api::{AuthenticatedClient, OAuthConfig, TokenStorage};
-use tokio::io::{stdin, stdout};
-use utils::{format_distance, format_duration, format_pace};
-
-// Helper trait for checking if an activity is a run
-trait ActivityExt {
-    fn is_run(&self) -> bool;
-}
-
-impl ActivityExt for strava_api::SummaryActivity {
-    fn is_run(&self) -> bool {
-        self.activity_type == "Run" || self.sport_type == "Run" || self.sport_type == "TrailRun"
-    }
-}
-
-#[derive(Clone)]
-struct StravaMcpServer {
-    auth_client: Arc<AuthenticatedClient>,
-    tool_router: ToolRouter<Self>,
-}
-
-#[tool_router]
-impl StravaMcpServer {
-    fn new(auth_client: AuthenticatedClient) -> Self {
-        Self {
-            auth_client: Arc::new(auth_client),
-            tool_router: Self::tool_router(),
-        }
-    }
-
-
-    #[tool(description = "Get running activities for a specific date (YYYY-MM-DD format)")]
-    async fn get_runs_for_date(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<GetRunsForDateParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0; // Extract inner value
-        let date_str = &params.date;
-
-        // Validate date string length (prevent excessive parsing)
-        if date_str.len() > 10 {
-            return Err(McpError::invalid_params_no_data(
-                "Date must be in YYYY-MM-DD format (10 characters max)",
-            ));
-        }
-
-        // Get authenticated client (will auto-refresh and auto-save token if needed)
-        let client = self.auth_client.client().await.map_err(McpError::internal)?;
-
-        // Parse and validate date
-        let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .map_err(|e| McpError::invalid_params_no_data(format!("Invalid date format (expected YYYY-MM-DD): {}", e)))?;
-
-        // Validate date is within reasonable range (Strava founded in 2009)
-        let min_date = NaiveDate::from_ymd_opt(2009, 1, 1)
-            .ok_or_else(|| McpError::internal("Failed to create min date"))?;
-        let max_date = Utc::now().date_naive() + Duration::days(1); // Allow today + 1 day for timezone differences
-
-        if date < min_date {
-            return Err(McpError::invalid_params_no_data(format!(
-                "Date {} is before Strava existed (min: 2009-01-01)",
-                date
-            )));
-        }
-
-        if date > max_date {
-            return Err(McpError::invalid_params_no_data(format!(
-                "Date {} is in the future (max: {})",
-                date, max_date
-            )));
-        }
-
-        // Calculate day boundaries in UTC
-        let start_of_day = date
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-        let end_of_day = start_of_day + 86400; // 24 hours
-
-        // Fetch activities
-        let activities = client
-            .list_athlete_activities(Some(start_of_day), Some(end_of_day), 1, 200)
-            .await
-            .map_err(McpError::internal)?;
-
-        // Filter for runs
-        let runs: Vec<_> = activities.iter().filter(|a| a.is_run()).collect();
-
-        if runs.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "No runs found for {}",
-                date_str
-            ))]));
-        }
-
-        // Format output
-        let mut output = format!("# Runs for {}\n\n", date_str);
-
-        let mut total_distance = 0.0;
-        let mut total_time = 0i32;
-
-        for run in &runs {
-            output.push_str(&format!("## {}\n", run.name));
-            output.push_str(&format!(
-                "- **Distance:** {} km\n",
-                format_distance(run.distance)
-            ));
-            output.push_str(&format!(
-                "- **Duration:** {}\n",
-                format_duration(run.moving_time)
-            ));
-            if let Some(avg_speed) = run.average_speed {
-                output.push_str(&format!(
-                    "- **Pace:** {}/km\n",
-                    format_pace(avg_speed)
-                ));
-            }
-            output.push_str(&format!(
-                "- **Elevation Gain:** {:.0}m\n",
-                run.total_elevation_gain
-            ));
-
-            if let Some(hr) = run.average_heartrate {
-                output.push_str(&format!("- **Average Heart Rate:** {:.0} bpm\n", hr));
-            }
-            if let Some(max_hr) = run.max_heartrate {
-                output.push_str(&format!("- **Max Heart Rate:** {:.0} bpm\n", max_hr));
-            }
-
-            output.push('\n');
-
-            total_distance += run.distance;
-            total_time += run.moving_time;
-        }
-
-        // Add totals if multiple runs
-        if runs.len() > 1 {
-            output.push_str("## Totals\n");
-            output.push_str(&format!("- **Runs:** {}\n", runs.len()));
-            output.push_str(&format!(
-                "- **Total Distance:** {} km\n",
-                format_distance(total_distance)
-            ));
-            output.push_str(&format!(
-                "- **Total Time:** {}\n",
-                format_duration(total_time)
-            ));
-        }
-
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-
-    #[tool(description = "Get the most recent running activities")]
-    async fn get_recent_runs(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<GetRecentRunsParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0;
-
-        // Validate and bound the limit parameter (prevent DoS)
-        const MAX_LIMIT: usize = 100;
-        const DEFAULT_LIMIT: usize = 5;
-        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
-
-        if limit == 0 {
-            return Err(McpError::invalid_params_no_data("limit must be greater than 0"));
-        }
-
-        if limit > MAX_LIMIT {
-            return Err(McpError::invalid_params_no_data(format!(
-                "limit cannot exceed {} (requested: {})",
-                MAX_LIMIT, limit
-            )));
-        }
-
-        // Get authenticated client (will auto-refresh and auto-save token if needed)
-        let client = self.auth_client.client().await.map_err(McpError::internal)?;
-
-        // Fetch activities
-        let activities = client
-            .list_athlete_activities(None, None, 1, 200)
-            .await
-            .map_err(McpError::internal)?;
-
-        // Filter for runs and take limit
-        let runs: Vec<_> = activities
-            .iter()
-            .filter(|a| a.is_run())
-            .take(limit)
-            .collect();
-
-        if runs.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(
-                "No recent runs found".to_string(),
-            )]));
-        }
-
-        // Format output
-        let mut output = format!("# {} Most Recent Runs\n\n", runs.len());
-
-        for run in runs {
-            // Parse local date for display
-            let date = run.start_date_local.split('T').next().unwrap_or("Unknown");
-
-            output.push_str(&format!("## {} ({})\n", run.name, date));
-            output.push_str(&format!(
-                "- **Distance:** {} km\n",
-                format_distance(run.distance)
-            ));
-            output.push_str(&format!(
-                "- **Duration:** {}\n",
-                format_duration(run.moving_time)
-            ));
-            if let Some(avg_speed) = run.average_speed {
-                output.push_str(&format!(
-                    "- **Pace:** {}/km\n",
-                    format_pace(avg_speed)
-                ));
-            }
-
-            if let Some(hr) = run.average_heartrate {
-                output.push_str(&format!("- **Average HR:** {:.0} bpm\n", hr));
-            }
-
-            output.push('\n');
-        }
-
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-
-    #[tool(description = "Get weekly running summary (defaults to current week)")]
-    async fn get_weekly_summary(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<GetWeeklySummaryParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0;
-
-        // Get authenticated client (will auto-refresh and auto-save token if needed)
-        let client = self.auth_client.client().await.map_err(McpError::internal)?;
-
-        // Determine week start (Monday)
-        let week_start = match &params.week_start {
-            Some(date_str) => NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|e| {
-                McpError::invalid_params_no_data(format!("Invalid date format: {}", e))
-            })?,
-            None => {
-                let today = Utc::now().date_naive();
-                // Find the previous Monday
-                let days_since_monday = today.weekday().num_days_from_monday();
-                today - Duration::days(days_since_monday as i64)
-            }
-        };
-
-        // Calculate week boundaries
-        let week_start_timestamp = week_start
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-        let week_end_timestamp = week_start_timestamp + (7 * 86400); // 7 days
-
-        // Fetch activities
-        let activities = client
-            .list_athlete_activities(
-                Some(week_start_timestamp),
-                Some(week_end_timestamp),
-                1,
-                200,
-            )
-            .await
-            .map_err(McpError::internal)?;
-
-        // Filter for runs
-        let runs: Vec<_> = activities.iter().filter(|a| a.is_run()).collect();
-
-        if runs.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "No runs found for week starting {}",
-                week_start
-            ))]));
-        }
-
-        // Calculate aggregates
-        let total_runs = runs.len();
-        let total_distance: f64 = runs.iter().map(|r| r.distance).sum();
-        let total_time: i32 = runs.iter().map(|r| r.moving_time).sum();
-        let total_elevation: f64 = runs.iter().map(|r| r.total_elevation_gain).sum();
-
-        // Calculate average pace from total distance and time
-        let avg_pace = if total_time > 0 && total_distance > 0.0 {
-            total_distance / total_time as f64
-        } else {
-            0.0
-        };
-
-        // Format output
-        let week_end = week_start + Duration::days(6);
-        let mut output = format!("# Weekly Summary: {} to {}\n\n", week_start, week_end);
-
-        output.push_str(&format!("- **Total Runs:** {}\n", total_runs));
-        output.push_str(&format!(
-            "- **Total Distance:** {} km\n",
-            format_distance(total_distance)
-        ));
-        output.push_str(&format!(
-            "- **Total Time:** {}\n",
-            format_duration(total_time)
-        ));
-        output.push_str(&format!(
-            "- **Average Pace:** {}/km\n",
-            format_pace(avg_pace)
-        ));
-        output.push_str(&format!(
-            "- **Total Elevation Gain:** {:.0}m\n",
-            total_elevation
-        ));
-
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-
-    #[tool(description = "Get running activities for a specific month (YYYY-MM format)")]
-    async fn get_runs_for_month(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<GetRunsForMonthParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0;
-        let month_str = &params.month;
-
-        // Validate month string format
-        if month_str.len() != 7 {
-            return Err(McpError::invalid_params_no_data(
-                "Month must be in YYYY-MM format (7 characters)",
-            ));
-        }
-
-        // Get authenticated client
-        let client = self.auth_client.client().await.map_err(McpError::internal)?;
-
-        // Parse month (YYYY-MM format)
-        let parts: Vec<&str> = month_str.split('-').collect();
-        if parts.len() != 2 {
-            return Err(McpError::invalid_params_no_data(
-                "Invalid month format (expected YYYY-MM)",
-            ));
-        }
-
-        let year: i32 = parts[0].parse().map_err(|_| {
-            McpError::invalid_params_no_data("Invalid year in month string")
-        })?;
-        let month: u32 = parts[1].parse().map_err(|_| {
-            McpError::invalid_params_no_data("Invalid month in month string")
-        })?;
-
-        // Validate month range
-        if !(1..=12).contains(&month) {
-            return Err(McpError::invalid_params_no_data(format!(
-                "Month must be between 01 and 12 (got: {})",
-                month
-            )));
-        }
-
-        // Calculate first day of month
-        let month_start = NaiveDate::from_ymd_opt(year, month, 1)
-            .ok_or_else(|| McpError::invalid_params_no_data("Invalid year/month combination"))?;
-
-        // Calculate first day of next month
-        let month_end = if month == 12 {
-            NaiveDate::from_ymd_opt(year + 1, 1, 1)
-        } else {
-            NaiveDate::from_ymd_opt(year, month + 1, 1)
-        }
-        .ok_or_else(|| McpError::internal("Failed to calculate month end"))?;
-
-        // Convert to timestamps
-        let start_timestamp = month_start
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-        let end_timestamp = month_end
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-
-        // Fetch activities for the month
-        let activities = client
-            .list_athlete_activities(Some(start_timestamp), Some(end_timestamp), 1, 200)
-            .await
-            .map_err(McpError::internal)?;
-
-        // Filter for runs
-        let runs: Vec<_> = activities.iter().filter(|a| a.is_run()).collect();
-
-        if runs.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "No runs found for {}",
-                month_str
-            ))]));
-        }
-
-        // Format output
-        let mut output = format!("# Runs for {}\n\n", month_str);
-        output.push_str(&format!("Found {} runs\n\n", runs.len()));
-
-        for run in &runs {
-            // Parse local date for display
-            let date = run.start_date_local.split('T').next().unwrap_or("Unknown");
-
-            output.push_str(&format!("## {} ({})\n", run.name, date));
-            output.push_str(&format!(
-                "- **Distance:** {} km\n",
-                format_distance(run.distance)
-            ));
-            output.push_str(&format!(
-                "- **Duration:** {}\n",
-                format_duration(run.moving_time)
-            ));
-            if let Some(avg_speed) = run.average_speed {
-                output.push_str(&format!(
-                    "- **Pace:** {}/km\n",
-                    format_pace(avg_speed)
-                ));
-            }
-            output.push_str(&format!(
-                "- **Elevation Gain:** {:.0}m\n",
-                run.total_elevation_gain
-            ));
-
-            if let Some(hr) = run.average_heartrate {
-                output.push_str(&format!("- **Average Heart Rate:** {:.0} bpm\n", hr));
-            }
-
-            output.push('\n');
-        }
-
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-
-    #[tool(description = "Get monthly running summary with aggregated statistics")]
-    async fn get_monthly_running_summary(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<GetMonthlyRunningSummaryParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0;
-        let month_str = &params.month;
-
-        // Validate month string format
-        if month_str.len() != 7 {
-            return Err(McpError::invalid_params_no_data(
-                "Month must be in YYYY-MM format (7 characters)",
-            ));
-        }
-
-        // Get authenticated client
-        let client = self.auth_client.client().await.map_err(McpError::internal)?;
-
-        // Parse month (YYYY-MM format)
-        let parts: Vec<&str> = month_str.split('-').collect();
-        if parts.len() != 2 {
-            return Err(McpError::invalid_params_no_data(
-                "Invalid month format (expected YYYY-MM)",
-            ));
-        }
-
-        let year: i32 = parts[0].parse().map_err(|_| {
-            McpError::invalid_params_no_data("Invalid year in month string")
-        })?;
-        let month: u32 = parts[1].parse().map_err(|_| {
-            McpError::invalid_params_no_data("Invalid month in month string")
-        })?;
-
-        // Validate month range
-        if !(1..=12).contains(&month) {
-            return Err(McpError::invalid_params_no_data(format!(
-                "Month must be between 01 and 12 (got: {})",
-                month
-            )));
-        }
-
-        // Calculate first day of month
-        let month_start = NaiveDate::from_ymd_opt(year, month, 1)
-            .ok_or_else(|| McpError::invalid_params_no_data("Invalid year/month combination"))?;
-
-        // Calculate first day of next month
-        let month_end = if month == 12 {
-            NaiveDate::from_ymd_opt(year + 1, 1, 1)
-        } else {
-            NaiveDate::from_ymd_opt(year, month + 1, 1)
-        }
-        .ok_or_else(|| McpError::internal("Failed to calculate month end"))?;
-
-        // Convert to timestamps
-        let start_timestamp = month_start
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-        let end_timestamp = month_end
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| McpError::internal("Invalid date"))?
-            .and_utc()
-            .timestamp();
-
-        // Fetch activities for the month
-        let activities = client
-            .list_athlete_activities(Some(start_timestamp), Some(end_timestamp), 1, 200)
-            .await
-            .map_err(McpError::internal)?;
-
-        // Filter for runs
-        let runs: Vec<_> = activities.iter().filter(|a| a.is_run()).collect();
-
-        if runs.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(format!(
-                "No runs found for {}",
-                month_str
-            ))]));
-        }
-
-        // Calculate aggregates
-        let total_runs = runs.len();
-        let total_distance: f64 = runs.iter().map(|r| r.distance).sum();
-        let total_time: i32 = runs.iter().map(|r| r.moving_time).sum();
-        let total_elevation: f64 = runs.iter().map(|r| r.total_elevation_gain).sum();
-
-        // Calculate average pace from total distance and time
-        let avg_pace = if total_time > 0 && total_distance > 0.0 {
-            total_distance / total_time as f64
-        } else {
-            0.0
-        };
-
-        // Format output
-        let mut output = format!("# Monthly Running Summary: {}\n\n", month_str);
-
-        output.push_str(&format!("- **Total Runs:** {}\n", total_runs));
-        output.push_str(&format!(
-            "- **Total Distance:** {} km\n",
-            format_distance(total_distance)
-        ));
-        output.push_str(&format!(
-            "- **Total Time:** {}\n",
-            format_duration(total_time)
-        ));
-        output.push_str(&format!(
-            "- **Average Pace:** {}/km\n",
-            format_pace(avg_pace)
-        ));
-        output.push_str(&format!(
-            "- **Total Elevation Gain:** {:.0}m\n",
-            total_elevation
-        ));
-
-        // Calculate some additional statistics
-        let avg_distance = total_distance / total_runs as f64;
-        let avg_time = total_time / total_runs as i32;
-
-        output.push_str("\n## Averages per Run\n");
-        output.push_str(&format!(
-            "- **Average Distance:** {} km\n",
-            format_distance(avg_distance)
-        ));
-        output.push_str(&format!(
-            "- **Average Duration:** {}\n",
-            format_duration(avg_time)
-        ));
-
-        Ok(CallToolResult::success(vec![Content::text(output)]))
-    }
-
-    #[tool(description = "Authorize the MCP with your Strava account")]
-    async fn authorize(
-        &self,
-        params: rmcp::handler::server::wrapper::Parameters<AuthorizeParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let params = params.0;
-
-        // First, try to get a client with the existing token (will auto-refresh and auto-save if needed)
-        match self.auth_client.client().await {
-            Ok(_) => {
-                // Token exists and is valid (or was successfully refreshed and saved)
-                return Ok(CallToolResult::success(vec![Content::text(
-                    "Already authorized! Your token is valid and has been refreshed if needed.".to_string(),
-                )]));
-            }
-            Err(_) => {
-                // Token doesn't exist or refresh failed - need full OAuth flow
-            }
-        }
-
-        // Validate port parameter (prevent privilege escalation)
-        const MIN_PORT: u16 = 1024; // Avoid privileged ports
-        const MAX_PORT: u16 = 65535;
-        const DEFAULT_PORT: u16 = 8089;
-
-        let port = params.port.unwrap_or(DEFAULT_PORT);
-
-        if port < MIN_PORT {
-            return Err(McpError::invalid_params_no_data(format!(
-                "port must be >= {} (requested: {}). Ports below 1024 require elevated privileges.",
-                MIN_PORT, port
-            )));
-        }
-
-        if port > MAX_PORT {
-            return Err(McpError::invalid_params_no_data(format!(
-                "port must be <= {} (requested: {})",
-                MAX_PORT, port
-            )));
-        }
-
-        // Validate scope parameter (whitelist allowed scopes)
-        const ALLOWED_SCOPES: &[&str] = &[
-            "read",
-            "read_all",
-            "profile:read_all",
-            "profile:write",
-            "activity:read",
-            "activity:read_all",
-            "activity:write",
-        ];
-
-        let scope = params.scope.as_deref().unwrap_or("activity:read_all");
-
-        if !ALLOWED_SCOPES.contains(&scope) {
-            return Err(McpError::invalid_params_no_data(format!(
-                "Invalid scope '{}'. Allowed scopes: {}",
-                scope,
-                ALLOWED_SCOPES.join(", ")
-            )));
-        }
-
-        // Perform full OAuth authorization flow (opens browser)
-        // Token will be automatically saved to ~/.strava/token.json
-        self.auth_client
-            .authorize(port, scope)
-            .await
-            .map_err(McpError::internal)?;
-
-        Ok(CallToolResult::success(vec![Content::text(
-            "Authorization successful! Token automatically saved for future use.".to_string(),
-        )]))
-    }
-}
-
-#[tool_handler]
-impl ServerHandler for StravaMcpServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo {
-            instructions: Some("MCP server for Strava API integration. Provides tools to fetch and analyze running activity data from Strava.".into()),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
-            ..Default::default()
-        }
-    }
-}
-
-// Tool parameter structs
-#[derive(Debug, Deserialize, JsonSchema)]
-struct GetRunsForDateParams {
-    #[schemars(description = "Date in YYYY-MM-DD format")]
-    date: String,
-}
-
-#[derive(Debug, Deserialize, JsonSchema)]
-struct GetRecentRunsParams {
-    #[schemars(description = "Number of recent runs to retrieve (default: 5)")]
-    limit: Option<usize>,
-}
-
-#[derive(Debug, Deserialize, JsonSchema)]
-struct GetWeeklySummaryParams {
-    #[schemars(description = "Start of week in YYYY-MM-DD format (defaults to current Monday)")]
-    week_start: Option<String>,
-}
-
-#[derive(Debug, Deserialize, JsonSchema)]
-struct GetRunsForMonthParams {
-    #[schemars(description = "Month in YYYY-MM format (e.g., 2026-01)")]
-    month: String,
-}
-
-#[derive(Debug, Deserialize, JsonSchema)]
-struct GetMonthlyRunningSummaryParams {
-    #[schemars(description = "Month in YYYY-MM format (e.g., 2026-01)")]
-    month: String,
-}
-
-#[derive(Debug, Deserialize, JsonSchema)]
-struct AuthorizeParams {
-    #[schemars(description = "Port for OAuth callback server (default: 8089)")]
-    port: Option<u16>,
-    #[schemars(description = "OAuth scope (default: 'activity:read_all')")]
-    scope: Option<String>,
-}
-
-// Helper methods for McpError
-trait McpErrorExt {
-    fn internal<E: std::fmt::Display>(error: E) -> Self;
-    fn invalid_params_no_data<S: Into<String>>(message: S) -> Self;
-}
-
-impl McpErrorExt for McpError {
-    fn internal<E: std::fmt::Display>(error: E) -> Self {
-        McpError::internal_error(format!("Internal error: {}", error), None)
-    }
-
-    fn invalid_params_no_data<S: Into<String>>(message: S) -> Self {
-        McpError::invalid_params(message.into(), None)
-    }
-}
-
-/// Strava MCP Server - Model Context Protocol server for Strava API integration
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
-struct Cli {
-    /// Path to .env file containing STRAVA_CLIENT_ID and STRAVA_CLIENT_SECRET
-    #[arg(long)]
-    env_file: Option<String>,
-}
+use strava_mcp::Cli;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Parse command-line arguments
-    let cli = Cli::parse();
-
-    // Load environment variables from specified file or default location
-    if let Some(env_file) = cli.env_file {
-        dotenvy::from_filename(&env_file)
-            .with_context(|| format!("Failed to load .env file from: {}", env_file))?;
-        eprintln!("Loaded environment from: {}", env_file);
-    } else {
-        // Try to load from current directory, but don't fail if not found
-        dotenvy::dotenv().ok();
-    }
-
-    // Load OAuth config
-    let config = OAuthConfig::from_env()
-        .context("Failed to load OAuth configuration. Please set STRAVA_CLIENT_ID and STRAVA_CLIENT_SECRET environment variables.")?;
-
-    // Load or create authenticated client with automatic token persistence
-    let storage = TokenStorage::default_location()
-        .context("Failed to get token storage location")?;
-
-    let auth_client = if storage.exists() {
-        // Load existing token from storage with auto-persistence enabled
-        let token = storage.load()
-            .context("Failed to load saved token")?;
-        eprintln!("Loaded saved authentication token from ~/.strava/token.json");
-        AuthenticatedClient::with_token_and_persistence(config, token)
-            .context("Failed to enable token persistence")?
-    } else {
-        // No saved token, will need to authorize on first tool call
-        eprintln!("No saved token found. Use the 'authorize' tool to authenticate.");
-        AuthenticatedClient::new_with_persistence(config)
-            .context("Failed to enable token persistence")?
-    };
-
-    // Create MCP server
-    let server = StravaMcpServer::new(auth_client);
-
-    // Create stdio transport
-    let transport = (stdin(), stdout());
-
-    // Serve
-    eprintln!("Starting Strava MCP server...");
-    let service = server.serve(transport).await.map_err(|e| {
-        eprintln!("Error starting server: {}", e);
-        e
-    })?;
-
-    service.waiting().await?;
-
-    Ok(())
+async fn main() -> anyhow::Result<()> {
+    strava_mcp::run(Cli::parse()).await
 }
@@ -0,0 +1,272 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::fmt;
+
+/// Longest input accepted, to keep parsing cheap
+const MAX_INPUT_LEN: usize = 32;
+
+/// Longest "last N days" span accepted
+const MAX_DAYS: i64 = 3660;
+
+/// Accepted forms, for error messages
+pub const FORMATS: &str = "YYYY-MM-DD, YYYY-Www, YYYY-MM, YYYY-Qn, YYYY, today, yesterday, this/last week, \
+    this/last month, this/last quarter, this/last year or last N days";
+
+/// What a period covers, which decides how it is labelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodKind {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// The last N days, ending today
+    Days(i64),
+}
+
+/// A run of whole local days, [start, end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// Parse an ISO date, week, month, quarter or year, or a phrase such as "last week", relative to `today`.
+    ///
+    /// Periods that end before Strava existed or start after tomorrow are rejected.
+    pub fn parse(input: &str, today: NaiveDate) -> Result<Self> {
+        if input.len() > MAX_INPUT_LEN {
+            bail!("Period is too long (at most {} characters)", MAX_INPUT_LEN);
+        }
+        let text = input.trim().to_lowercase();
+        let period = Self::relative(&text, today)
+            .or_else(|| Self::iso(&text))
+            .ok_or_else(|| anyhow!("Invalid period '{}' (expected {})", input.trim(), FORMATS))??;
+        period.validate(today)?;
+        Ok(period)
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Period { kind: PeriodKind::Day, start: date, end: date + Duration::days(1) }
+    }
+
+    /// The seven days from `start`, which need not be a Monday
+    pub fn week_starting(start: NaiveDate) -> Self {
+        Period { kind: PeriodKind::Week, start, end: start + Duration::days(7) }
+    }
+
+    /// The Monday-to-Sunday week containing `date`
+    pub fn week_of(date: NaiveDate) -> Self {
+        Self::week_starting(date.week(Weekday::Mon).first_day())
+    }
+
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let end = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        Some(Period { kind: PeriodKind::Month, start, end })
+    }
+
+    pub fn quarter(year: i32, quarter: u32) -> Option<Self> {
+        if !(1..=4).contains(&quarter) {
+            return None;
+        }
+        let months = Self::month(year, quarter * 3 - 2)?;
+        let last = Self::month(year, quarter * 3)?;
+        Some(Period { kind: PeriodKind::Quarter, start: months.start, end: last.end })
+    }
+
+    pub fn year(year: i32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, 1, 1)?;
+        let end = NaiveDate::from_ymd_opt(year + 1, 1, 1)?;
+        Some(Period { kind: PeriodKind::Year, start, end })
+    }
+
+    /// Last day of the period
+    pub fn last_day(&self) -> NaiveDate {
+        self.end - Duration::days(1)
+    }
+
+    /// Phrases relative to today; None if `text` is not one
+    fn relative(text: &str, today: NaiveDate) -> Option<Result<Self>> {
+        let quarter = (today.month() - 1) / 3 + 1;
+        let period = match text {
+            "today" => Some(Self::day(today)),
+            "yesterday" => Some(Self::day(today - Duration::days(1))),
+            "this week" => Some(Self::week_of(today)),
+            "last week" => Some(Self::week_of(today - Duration::days(7))),
+            "this month" => Self::month(today.year(), today.month()),
+            "last month" => Self::month(today.year(), today.month()).and_then(|m| {
+                let previous = m.start - Duration::days(1);
+                Self::month(previous.year(), previous.month())
+            }),
+            "this quarter" => Self::quarter(today.year(), quarter),
+            "last quarter" if quarter == 1 => Self::quarter(today.year() - 1, 4),
+            "last quarter" => Self::quarter(today.year(), quarter - 1),
+            "this year" => Self::year(today.year()),
+            "last year" => Self::year(today.year() - 1),
+            _ => return Self::last_days(text, today),
+        };
+        Some(period.ok_or_else(|| anyhow!("Invalid period '{}'", text)))
+    }
+
+    /// "last 14 days" or "past 14 days", ending today
+    fn last_days(text: &str, today: NaiveDate) -> Option<Result<Self>> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let ["last" | "past", count, "days" | "day"] = words.as_slice() else {
+            return None;
+        };
+        let days = match count.parse::<i64>() {
+            Ok(days) if (1..=MAX_DAYS).contains(&days) => days,
+            _ => return Some(Err(anyhow!("Number of days must be between 1 and {} (got: {})", MAX_DAYS, count))),
+        };
+        let end = today + Duration::days(1);
+        Some(Ok(Period { kind: PeriodKind::Days(days), start: end - Duration::days(days), end }))
+    }
+
+    /// ISO 8601 forms; None if `text` is not one
+    fn iso(text: &str) -> Option<Result<Self>> {
+        let invalid = || anyhow!("Invalid period '{}' (expected {})", text, FORMATS);
+
+        if let Some((year, rest)) = text.split_once('-') {
+            let year: i32 = year.parse().ok().filter(|_| year.len() == 4)?;
+            let period = if let Some(week) = rest.strip_prefix('w') {
+                let week: u32 = week.parse().ok().filter(|_| week.len() == 2)?;
+                NaiveDate::from_isoywd_opt(year, week, Weekday::Mon).map(Self::week_starting)
+            } else if let Some(quarter) = rest.strip_prefix('q') {
+                Self::quarter(year, quarter.parse().ok().filter(|_| quarter.len() == 1)?)
+            } else if rest.len() == 2 {
+                Self::month(year, rest.parse().ok()?)
+            } else {
+                let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().filter(|_| text.len() == 10);
+                date.map(Self::day)
+            };
+            return Some(period.ok_or_else(invalid));
+        }
+
+        if text.len() == 4 {
+            let year: i32 = text.parse().ok()?;
+            return Some(Self::year(year).ok_or_else(invalid));
+        }
+        None
+    }
+
+    fn validate(&self, today: NaiveDate) -> Result<()> {
+        // Strava was founded in 2009
+        let min_date = NaiveDate::from_ymd_opt(2009, 1, 1).ok_or_else(|| anyhow!("Invalid min date"))?;
+        // Allow tomorrow for timezone differences
+        let max_date = today + Duration::days(1);
+
+        if self.end <= min_date {
+            bail!("{} is before Strava existed (min: {})", self, min_date);
+        }
+        if self.start > max_date {
+            bail!("{} is in the future (max: {})", self, max_date);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            PeriodKind::Day => write!(f, "{}", self.start.format("%Y-%m-%d")),
+            PeriodKind::Week if self.start.weekday() == Weekday::Mon => {
+                let week = self.start.iso_week();
+                write!(f, "{}-W{:02}", week.year(), week.week())
+            }
+            PeriodKind::Week => write!(f, "week starting {}", self.start),
+            PeriodKind::Month => write!(f, "{}", self.start.format("%Y-%m")),
+            PeriodKind::Quarter => write!(f, "{}-Q{}", self.start.year(), self.start.month() / 3 + 1),
+            PeriodKind::Year => write!(f, "{}", self.start.year()),
+            PeriodKind::Days(days) => write!(f, "last {} days ({} to {})", days, self.start, self.last_day()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn parse(input: &str) -> Period {
+        // A Wednesday
+        Period::parse(input, date("2026-02-11")).unwrap()
+    }
+
+    fn span(period: Period) -> (String, String) {
+        (period.start.to_string(), period.last_day().to_string())
+    }
+
+    #[test]
+    fn test_iso_periods() {
+        assert_eq!(parse("2026-02-10"), Period::day(date("2026-02-10")));
+        assert_eq!(span(parse("2026-W07")), ("2026-02-09".into(), "2026-02-15".into()));
+        assert_eq!(span(parse("2025-W01")), ("2024-12-30".into(), "2025-01-05".into()));
+        assert_eq!(span(parse("2024-02")), ("2024-02-01".into(), "2024-02-29".into()));
+        assert_eq!(span(parse("2025-12")), ("2025-12-01".into(), "2025-12-31".into()));
+        assert_eq!(span(parse("2025-Q4")), ("2025-10-01".into(), "2025-12-31".into()));
+        assert_eq!(span(parse("2025")), ("2025-01-01".into(), "2025-12-31".into()));
+        assert_eq!(parse(" 2026-w07 ").kind, PeriodKind::Week);
+    }
+
+    #[test]
+    fn test_relative_periods() {
+        assert_eq!(parse("today"), Period::day(date("2026-02-11")));
+        assert_eq!(parse("Yesterday"), Period::day(date("2026-02-10")));
+        assert_eq!(span(parse("this week")), ("2026-02-09".into(), "2026-02-15".into()));
+        assert_eq!(span(parse("last week")), ("2026-02-02".into(), "2026-02-08".into()));
+        assert_eq!(span(parse("this month")), ("2026-02-01".into(), "2026-02-28".into()));
+        assert_eq!(span(parse("last month")), ("2026-01-01".into(), "2026-01-31".into()));
+        assert_eq!(span(parse("last quarter")), ("2025-10-01".into(), "2025-12-31".into()));
+        assert_eq!(span(parse("last year")), ("2025-01-01".into(), "2025-12-31".into()));
+        assert_eq!(span(parse("last 14 days")), ("2026-01-29".into(), "2026-02-11".into()));
+        assert_eq!(span(parse("past 1 day")), ("2026-02-11".into(), "2026-02-11".into()));
+    }
+
+    #[test]
+    fn test_labels() {
+        assert_eq!(parse("2026-02-10").to_string(), "2026-02-10");
+        assert_eq!(parse("last week").to_string(), "2026-W06");
+        assert_eq!(Period::week_starting(date("2026-02-11")).to_string(), "week starting 2026-02-11");
+        assert_eq!(parse("this month").to_string(), "2026-02");
+        assert_eq!(parse("2025-q3").to_string(), "2025-Q3");
+        assert_eq!(parse("this year").to_string(), "2026");
+        assert_eq!(parse("last 7 days").to_string(), "last 7 days (2026-02-05 to 2026-02-11)");
+    }
+
+    #[test]
+    fn test_invalid_periods() {
+        let today = date("2026-02-11");
+        for input in [
+            "11/02/2026",
+            "2026-13",
+            "2026-02-30",
+            "2026-W54",
+            "2026-Q5",
+            "2026-2",
+            "next week",
+            "last 0 days",
+            "last 9999 days",
+            "2026-02-10T00:00",
+            "a very long period description that goes on",
+        ] {
+            assert!(Period::parse(input, today).is_err(), "{}", input);
+        }
+        let error = Period::parse("2008-12-31", today).unwrap_err().to_string();
+        assert_eq!(error, "2008-12-31 is before Strava existed (min: 2009-01-01)");
+        let error = Period::parse("2026-02-13", today).unwrap_err().to_string();
+        assert_eq!(error, "2026-02-13 is in the future (max: 2026-02-12)");
+        // Periods that have started are allowed to run on past today
+        assert!(Period::parse("2026", today).is_ok());
+        assert!(Period::parse("2009", today).is_ok());
+    }
+}
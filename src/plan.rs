@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::path::Path;
+
+use crate::activity::Activity;
+
+/// One session from an iCalendar training plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedSession {
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: String,
+    /// Planned distance in meters, when the session names one (e.g. "8.5km")
+    pub distance: Option<f64>,
+    /// Planned pace range in seconds per km as (fastest, slowest)
+    pub pace_range: Option<(u32, u32)>,
+}
+
+impl PlannedSession {
+    /// Whether the session is a run rather than strength or mobility work
+    pub fn is_run(&self) -> bool {
+        let summary = self.summary.to_lowercase();
+        self.distance.is_some() || ["run", "interval", "tempo", "fartlek"].iter().any(|k| summary.contains(k))
+    }
+}
+
+/// Load every dated session from an .ics file
+pub fn load(path: &Path) -> Result<Vec<PlannedSession>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read training plan: {}", path.display()))?;
+    Ok(parse_calendar(&text))
+}
+
+/// Parse the VEVENTs of an iCalendar file; events without a start date are skipped
+pub fn parse_calendar(text: &str) -> Vec<PlannedSession> {
+    // Long lines are folded onto continuation lines that start with a space or tab
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut sessions = Vec::new();
+    let mut event: Option<(Option<NaiveDate>, String, String)> = None;
+
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Parameters such as DTSTART;VALUE=DATE come after the property name
+        let name = key.split(';').next().unwrap_or(key);
+
+        match (name, event.as_mut()) {
+            ("BEGIN", _) if value == "VEVENT" => event = Some((None, String::new(), String::new())),
+            ("END", Some(_)) if value == "VEVENT" => {
+                if let Some((Some(date), summary, description)) = event.take() {
+                    sessions.push(PlannedSession {
+                        date,
+                        distance: planned_distance(&summary).or_else(|| planned_distance(&description)),
+                        pace_range: pace_range(&description),
+                        summary,
+                        description,
+                    });
+                }
+            }
+            ("DTSTART", Some(current)) => {
+                current.0 = value.get(..8).and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok());
+            }
+            ("SUMMARY", Some(current)) => current.1 = unescape(value),
+            ("DESCRIPTION", Some(current)) => current.2 = unescape(value),
+            _ => {}
+        }
+    }
+
+    sessions
+}
+
+/// How an activity compared with the session planned for that day
+#[derive(Debug, Clone, PartialEq)]
+pub struct Adherence {
+    pub session: String,
+    /// Actual distance as a percentage of the planned distance
+    pub distance_percent: Option<f64>,
+    /// Whether the average pace fell inside the planned range
+    pub pace_in_range: Option<bool>,
+}
+
+impl Adherence {
+    /// Compare an activity with the first matching session on its date
+    pub fn for_activity(activity: &Activity, sessions: &[PlannedSession]) -> Option<Self> {
+        let session = sessions
+            .iter()
+            .find(|s| s.date == activity.date() && s.is_run() == activity.is_run())?;
+
+        let distance_percent = session
+            .distance
+            .filter(|d| *d > 0.0)
+            .map(|planned| activity.distance / planned * 100.0);
+
+        let pace_in_range = match (session.pace_range, activity.average_speed) {
+            (Some((fastest, slowest)), Some(speed)) if speed > 0.0 => {
+                let pace = 1000.0 / speed;
+                Some(pace >= fastest as f64 && pace <= slowest as f64)
+            }
+            _ => None,
+        };
+
+        Some(Self {
+            session: session.summary.clone(),
+            distance_percent,
+            pace_in_range,
+        })
+    }
+}
+
+/// First "<number>km" in the text, in meters
+fn planned_distance(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    let index = lower.find("km")?;
+    let number: String = lower[..index]
+        .trim_end()
+        .chars()
+        .rev()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    number.parse::<f64>().ok().map(|km| km * 1000.0)
+}
+
+/// Paces written as "M:SS/km"; two paces give a range, one gives an exact target
+fn pace_range(text: &str) -> Option<(u32, u32)> {
+    let paces: Vec<u32> = text
+        .split("/km")
+        .filter_map(|part| {
+            let token = part.rsplit(|c: char| !(c.is_ascii_digit() || c == ':')).next()?;
+            let (minutes, seconds) = token.split_once(':')?;
+            minutes.parse::<u32>().ok()?.checked_mul(60)?.checked_add(seconds.parse().ok()?)
+        })
+        .collect();
+
+    match paces.as_slice() {
+        [] => None,
+        [pace] => Some((*pace, *pace)),
+        [a, b, ..] => Some((*a.min(b), *a.max(b))),
+    }
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("\\n", "\n")
+        .replace("\\N", "\n")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+SUMMARY:🏃 Long Run 8.5km\r
+DTSTART;VALUE=DATE:20260208\r
+DESCRIPTION:8.5km @ Long Run pace (8:04/km - 7:00/km)\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:💪 Strength: Core\r
+DTSTART;VALUE=DATE:20260209\r
+DESCRIPTION:Strength training session\r
+  focusing on core\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+SUMMARY:🏃 Tempo Run\r
+DTSTART;VALUE=DATE:20260218\r
+DESCRIPTION:6.0km @ Tempo pace (6:40/km)\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn run(date: &str, distance: f64, moving_time: i32) -> Activity {
+        let start = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .unwrap()
+            .and_hms_opt(7, 0, 0)
+            .unwrap();
+        Activity::test_run(start, distance, moving_time)
+    }
+
+    #[test]
+    fn test_parse_calendar() {
+        let sessions = parse_calendar(CALENDAR);
+
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].date, NaiveDate::from_ymd_opt(2026, 2, 8).unwrap());
+        assert_eq!(sessions[0].distance, Some(8500.0));
+        assert_eq!(sessions[0].pace_range, Some((420, 484)));
+        assert!(sessions[0].is_run());
+
+        assert_eq!(sessions[1].description, "Strength training session focusing on core");
+        assert!(!sessions[1].is_run());
+
+        assert_eq!(sessions[2].pace_range, Some((400, 400)));
+
+        // Paces too large to count in seconds are skipped rather than overflowing
+        assert_eq!(pace_range("Easy (99999999:00/km - 6:00/km)"), Some((360, 360)));
+    }
+
+    #[test]
+    fn test_adherence() {
+        let sessions = parse_calendar(CALENDAR);
+
+        // 8.5 km at 7:30/km
+        let adherence = Adherence::for_activity(&run("2026-02-08", 8500.0, 3825), &sessions).unwrap();
+        assert_eq!(adherence.session, "🏃 Long Run 8.5km");
+        assert_eq!(adherence.distance_percent, Some(100.0));
+        assert_eq!(adherence.pace_in_range, Some(true));
+
+        // Strength day has no run planned
+        assert!(Adherence::for_activity(&run("2026-02-09", 5000.0, 1500), &sessions).is_none());
+    }
+}
@@ -0,0 +1,305 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::period::Period;
+
+/// Profile used when no name is given; keeps the original single-athlete file locations
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Base directory for tokens, stores and profiles: ~/.strava
+pub fn base_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    Ok(home.join(".strava"))
+}
+
+/// Profile names become directory names, so keep them to letters, digits, '-' and '_'
+pub fn validate_name(name: &str) -> Result<()> {
+    const MAX_NAME_LENGTH: usize = 64;
+
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        bail!("Profile name must be between 1 and {} characters", MAX_NAME_LENGTH);
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        bail!(
+            "Invalid profile name '{}' (use letters, digits, '-' and '_')",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Where a profile keeps its files
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfilePaths {
+    pub token: PathBuf,
+    pub store: PathBuf,
+    pub settings: PathBuf,
+}
+
+impl ProfilePaths {
+    /// The default profile uses ~/.strava directly; others live in ~/.strava/profiles/<name>
+    pub fn new(base: &Path, name: &str) -> Self {
+        let dir = if name == DEFAULT_PROFILE {
+            base.to_path_buf()
+        } else {
+            base.join("profiles").join(name)
+        };
+        Self {
+            token: dir.join("token.json"),
+            store: dir.join("activities.json"),
+            settings: dir.join("settings.toml"),
+        }
+    }
+}
+
+/// Names of all profiles on disk, default first
+pub fn list(base: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    let dir = base.join("profiles");
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read profiles directory: {}", dir.display()))?
+        {
+            let entry = entry?;
+            if entry.path().is_dir() {
+                if let Some(name) = entry.file_name().to_str().filter(|n| validate_name(n).is_ok()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+    }
+    names.sort();
+    names.retain(|n| n != DEFAULT_PROFILE);
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(names)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl Units {
+    const METERS_PER_MILE: f64 = 1609.344;
+    const FEET_PER_METER: f64 = 3.28084;
+
+    /// Distance with its unit, e.g. "5.00 km" or "3.11 mi"
+    pub fn distance(&self, meters: f64) -> String {
+        match self {
+            Self::Metric => format!("{} km", crate::utils::format_distance(meters)),
+            Self::Imperial => format!("{:.2} mi", meters / Self::METERS_PER_MILE),
+        }
+    }
+
+    /// Distance rounded to a whole unit, e.g. "712 km" or "442 mi"
+    pub fn whole_distance(&self, meters: f64) -> String {
+        match self {
+            Self::Metric => format!("{:.0} km", meters / 1000.0),
+            Self::Imperial => format!("{:.0} mi", meters / Self::METERS_PER_MILE),
+        }
+    }
+
+    /// Elevation with its unit, e.g. "120m" or "394ft"
+    pub fn elevation(&self, meters: f64) -> String {
+        match self {
+            Self::Metric => format!("{:.0}m", meters),
+            Self::Imperial => format!("{:.0}ft", meters * Self::FEET_PER_METER),
+        }
+    }
+
+    /// Pace with its unit, e.g. "5:00/km" or "8:03/mi"
+    pub fn pace(&self, meters_per_second: f64) -> String {
+        match self {
+            Self::Metric => format!("{}/km", crate::utils::format_pace(meters_per_second)),
+            Self::Imperial => format!(
+                "{}/mi",
+                crate::utils::format_pace(meters_per_second * 1000.0 / Self::METERS_PER_MILE)
+            ),
+        }
+    }
+}
+
+/// Per-profile settings from settings.toml over the server config; every field is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSettings {
+    pub units: Units,
+    /// IANA timezone name used for "today" and day boundaries (default: UTC)
+    pub timezone: Option<String>,
+    /// Sport type the run tools list: "run" for every kind of run (default), "all", or e.g. "TrailRun"
+    pub sport: Option<String>,
+    /// Heart rate zones as [min, max] bpm; -1 leaves the top zone open
+    pub hr_zones: Option<Vec<(i32, i32)>>,
+    /// Maximum heart rate, used for zones when hr_zones is not set
+    pub max_hr: Option<i32>,
+}
+
+impl ProfileSettings {
+    /// Settings from merged config layers, rejecting unknown timezones and implausible heart rates
+    pub fn from_table(table: toml::Table) -> Result<Self> {
+        let settings: Self = toml::Value::Table(table).try_into()?;
+        settings.timezone()?;
+        if let Some(max_hr) = settings.max_hr.filter(|hr| !(100..=250).contains(hr)) {
+            bail!("max_hr must be between 100 and 250 bpm (got: {})", max_hr);
+        }
+        Ok(settings)
+    }
+
+    /// Configured zones, or five zones at 60/70/80/90% of max HR
+    pub fn heart_rate_zones(&self) -> Option<Vec<(i32, i32)>> {
+        if let Some(zones) = &self.hr_zones {
+            return Some(zones.clone());
+        }
+        let max_hr = self.max_hr?;
+        let bound = |percent: i32| max_hr * percent / 100;
+        Some(vec![
+            (0, bound(60)),
+            (bound(60), bound(70)),
+            (bound(70), bound(80)),
+            (bound(80), bound(90)),
+            (bound(90), -1),
+        ])
+    }
+
+    /// Whether the run tools include this activity
+    pub fn matches_sport(&self, activity: &crate::activity::Activity) -> bool {
+        match self.sport.as_deref() {
+            None => activity.is_run(),
+            Some(sport) if sport.eq_ignore_ascii_case("run") => activity.is_run(),
+            Some(sport) if sport.eq_ignore_ascii_case("all") => true,
+            Some(sport) => activity.sport_type.eq_ignore_ascii_case(sport),
+        }
+    }
+
+    pub fn timezone(&self) -> Result<Option<Tz>> {
+        self.timezone
+            .as_deref()
+            .map(|name| {
+                name.parse::<Tz>()
+                    .map_err(|_| anyhow::anyhow!("Unknown timezone '{}' (expected e.g. Europe/London)", name))
+            })
+            .transpose()
+    }
+
+    /// Today's date in the profile's timezone
+    pub fn today(&self) -> NaiveDate {
+        match self.timezone().ok().flatten() {
+            Some(tz) => Utc::now().with_timezone(&tz).date_naive(),
+            None => Utc::now().date_naive(),
+        }
+    }
+
+    /// Unix timestamp of midnight at the start of `date` in the profile's timezone
+    pub fn midnight(&self, date: NaiveDate) -> Option<i64> {
+        let naive = date.and_hms_opt(0, 0, 0)?;
+        match self.timezone().ok().flatten() {
+            // When a DST change skips midnight (e.g. America/Santiago), the day starts at the end of the gap;
+            // gaps are whole quarter hours and never longer than a few hours
+            Some(tz) => (0..=16)
+                .find_map(|quarter| tz.from_local_datetime(&(naive + Duration::minutes(15 * quarter))).earliest())
+                .map(|t| t.timestamp()),
+            None => Some(naive.and_utc().timestamp()),
+        }
+    }
+
+    /// Unix timestamps of the start and end of `period` in the profile's timezone
+    pub fn bounds(&self, period: &Period) -> Option<(i64, i64)> {
+        Some((self.midnight(period.start)?, self.midnight(period.end)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("alice").is_ok());
+        assert!(validate_name("team_a-2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("a b").is_err());
+    }
+
+    #[test]
+    fn test_paths() {
+        let base = Path::new("/home/coach/.strava");
+
+        let default = ProfilePaths::new(base, DEFAULT_PROFILE);
+        assert_eq!(default.token, base.join("token.json"));
+
+        let alice = ProfilePaths::new(base, "alice");
+        assert_eq!(alice.token, base.join("profiles/alice/token.json"));
+        assert_eq!(alice.store, base.join("profiles/alice/activities.json"));
+    }
+
+    #[test]
+    fn test_settings() {
+        let settings: ProfileSettings = toml::from_str(
+            r#"
+            units = "imperial"
+            timezone = "America/New_York"
+            hr_zones = [[0, 130], [130, 150], [150, -1]]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.units, Units::Imperial);
+        assert_eq!(settings.hr_zones.as_ref().unwrap()[2], (150, -1));
+        assert_eq!(settings.heart_rate_zones(), settings.hr_zones);
+
+        // Midnight in New York in winter is 05:00 UTC
+        let date = NaiveDate::from_ymd_opt(2026, 1, 15).unwrap();
+        let midnight = settings.midnight(date).unwrap();
+        assert_eq!(midnight % 86400, 5 * 3600);
+
+        // Santiago skips from 00:00 to 01:00 when DST starts, so that day begins at 01:00 (04:00 UTC)
+        let santiago = ProfileSettings {
+            timezone: Some("America/Santiago".to_string()),
+            ..Default::default()
+        };
+        let date = NaiveDate::from_ymd_opt(2025, 9, 7).unwrap();
+        let start = date.and_hms_opt(4, 0, 0).unwrap().and_utc().timestamp();
+        assert_eq!(santiago.midnight(date), Some(start));
+        let (_, end) = santiago.bounds(&Period::day(date - Duration::days(1))).unwrap();
+        assert_eq!(end, start);
+
+        assert!(toml::from_str::<ProfileSettings>("units = \"furlongs\"").is_err());
+        let bad_timezone = ProfileSettings {
+            timezone: Some("Mars/Olympus".to_string()),
+            ..Default::default()
+        };
+        assert!(bad_timezone.timezone().is_err());
+    }
+
+    #[test]
+    fn test_max_hr_zones() {
+        let table: toml::Table = toml::from_str("max_hr = 190").unwrap();
+        let settings = ProfileSettings::from_table(table).unwrap();
+        let zones = settings.heart_rate_zones().unwrap();
+        assert_eq!(zones[0], (0, 114));
+        assert_eq!(zones[4], (171, -1));
+
+        let table: toml::Table = toml::from_str("max_hr = 40").unwrap();
+        assert!(ProfileSettings::from_table(table).is_err());
+        assert_eq!(ProfileSettings::default().heart_rate_zones(), None);
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(Units::Metric.distance(5000.0), "5.00 km");
+        assert_eq!(Units::Imperial.distance(1609.344), "1.00 mi");
+        assert_eq!(Units::Metric.pace(1000.0 / 300.0), "5:00/km");
+        assert_eq!(Units::Imperial.pace(1609.344 / 480.0), "8:00/mi");
+        assert_eq!(Units::Metric.whole_distance(711_600.0), "712 km");
+        assert_eq!(Units::Imperial.whole_distance(700_000.0), "435 mi");
+        assert_eq!(Units::Metric.elevation(120.4), "120m");
+        assert_eq!(Units::Imperial.elevation(120.0), "394ft");
+    }
+}
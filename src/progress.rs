@@ -0,0 +1,59 @@
+use rmcp::model::{ProgressNotificationParam, ProgressToken};
+use rmcp::service::{Peer, RoleServer};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+tokio::task_local! {
+    /// The MCP tool call being handled, if any
+    static CALL: Progress;
+}
+
+/// Progress notifications for one MCP tool call
+#[derive(Clone)]
+pub struct Progress {
+    peer: Peer<RoleServer>,
+    /// Present when the client asked for progress notifications
+    token: Option<ProgressToken>,
+    /// Notifications sent so far; MCP requires progress to increase with each one
+    sent: Arc<AtomicU64>,
+}
+
+impl Progress {
+    pub fn new(peer: Peer<RoleServer>, token: Option<ProgressToken>) -> Self {
+        Self {
+            peer,
+            token,
+            sent: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Run a tool call with progress reporting available to everything it calls
+    pub async fn scope<F: Future>(self, call: F) -> F::Output {
+        CALL.scope(self, call).await
+    }
+}
+
+/// Tell the client how far a long scan has got.
+///
+/// Does nothing outside an MCP tool call (e.g. on the command line) or when the client did not ask for progress.
+pub async fn report(message: String) {
+    let Some((peer, token, sent)) = CALL
+        .try_with(|call| call.token.clone().map(|token| (call.peer.clone(), token, call.sent.clone())))
+        .ok()
+        .flatten()
+    else {
+        return;
+    };
+    let progress = sent.fetch_add(1, Ordering::SeqCst) + 1;
+    tracing::debug!(progress, message = %message, "progress");
+    let param = ProgressNotificationParam {
+        progress_token: token,
+        progress: progress as f64,
+        total: None,
+        message: Some(message),
+    };
+    if let Err(e) = peer.notify_progress(param).await {
+        tracing::debug!(error = %e, "could not send progress notification");
+    }
+}
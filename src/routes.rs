@@ -0,0 +1,146 @@
+use crate::activity::Activity;
+
+/// Meters of flat running equivalent to one meter of climbing (Scarf's equivalence)
+const CLIMB_EQUIVALENT_METERS: f64 = 7.92;
+
+/// Terrain a route is planned for, from Strava's route type and sub type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Terrain {
+    Road,
+    Trail,
+    Ride,
+}
+
+impl Terrain {
+    /// Strava route `type` is 1 for rides and 2 for runs; `sub_type` 4 is trail
+    pub fn from_route(route_type: i32, sub_type: i32) -> Self {
+        match (route_type, sub_type) {
+            (1, _) => Self::Ride,
+            (_, 4) => Self::Trail,
+            _ => Self::Road,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Road => "Road run",
+            Self::Trail => "Trail run",
+            Self::Ride => "Ride",
+        }
+    }
+
+    /// Whether an activity was done on this kind of terrain
+    pub fn matches(&self, activity: &Activity) -> bool {
+        match self {
+            Self::Road => matches!(activity.sport_type.as_str(), "Run" | "VirtualRun"),
+            Self::Trail => activity.sport_type == "TrailRun",
+            Self::Ride => activity.sport_type.ends_with("Ride"),
+        }
+    }
+}
+
+/// Estimated moving time for a route, based on the athlete's own recent activities
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeEstimate {
+    /// Estimated moving time in seconds
+    pub moving_time: i32,
+    /// Number of activities the pace was derived from
+    pub based_on: usize,
+    /// Whether the activities matched the route's terrain (otherwise any runs were used)
+    pub terrain_matched: bool,
+}
+
+/// Estimate moving time from the athlete's climb-adjusted speed on recent activities.
+///
+/// Climbing is converted to equivalent flat distance, so a hilly route takes
+/// longer than a flat one of the same length at the same effort.
+pub fn estimate_moving_time(
+    distance: f64,
+    elevation_gain: f64,
+    terrain: Terrain,
+    recent: &[Activity],
+) -> Option<TimeEstimate> {
+    let matching: Vec<&Activity> = recent.iter().filter(|a| terrain.matches(a)).collect();
+    let (sample, terrain_matched) = if !matching.is_empty() {
+        (matching, true)
+    } else if terrain != Terrain::Ride {
+        // Fall back to any runs when there are none on this terrain
+        (recent.iter().filter(|a| a.is_run()).collect(), false)
+    } else {
+        (Vec::new(), false)
+    };
+
+    let equivalent_distance: f64 = sample
+        .iter()
+        .map(|a| a.distance + a.total_elevation_gain * CLIMB_EQUIVALENT_METERS)
+        .sum();
+    let moving_time: f64 = sample.iter().map(|a| a.moving_time as f64).sum();
+
+    if moving_time <= 0.0 || equivalent_distance <= 0.0 {
+        return None;
+    }
+
+    let speed = equivalent_distance / moving_time;
+    let route_equivalent = distance + elevation_gain * CLIMB_EQUIVALENT_METERS;
+
+    Some(TimeEstimate {
+        moving_time: (route_equivalent / speed).round() as i32,
+        based_on: sample.len(),
+        terrain_matched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn activity(sport_type: &str, distance: f64, elevation: f64, moving_time: i32) -> Activity {
+        Activity {
+            sport_type: sport_type.to_string(),
+            total_elevation_gain: elevation,
+            ..Activity::test_run(Utc::now().naive_utc(), distance, moving_time)
+        }
+    }
+
+    #[test]
+    fn test_terrain_from_route() {
+        assert_eq!(Terrain::from_route(1, 1), Terrain::Ride);
+        assert_eq!(Terrain::from_route(2, 1), Terrain::Road);
+        assert_eq!(Terrain::from_route(2, 4), Terrain::Trail);
+    }
+
+    #[test]
+    fn test_estimate_uses_matching_terrain() {
+        let recent = vec![
+            // Flat road runs at 5:00/km
+            activity("Run", 10_000.0, 0.0, 3000),
+            // Trail at 10000m + 100m climb equivalent in 4000s
+            activity("TrailRun", 10_000.0, 100.0, 4000),
+        ];
+
+        let flat = estimate_moving_time(5_000.0, 0.0, Terrain::Road, &recent).unwrap();
+        assert_eq!(flat.moving_time, 1500);
+        assert_eq!(flat.based_on, 1);
+        assert!(flat.terrain_matched);
+
+        // Climbing makes the same distance slower
+        let hilly = estimate_moving_time(5_000.0, 100.0, Terrain::Road, &recent).unwrap();
+        assert_eq!(hilly.moving_time, 1738);
+
+        let trail = estimate_moving_time(10_000.0, 100.0, Terrain::Trail, &recent).unwrap();
+        assert_eq!(trail.moving_time, 4000);
+    }
+
+    #[test]
+    fn test_estimate_falls_back_to_any_runs() {
+        let recent = vec![activity("Run", 10_000.0, 0.0, 3000)];
+
+        let estimate = estimate_moving_time(10_000.0, 0.0, Terrain::Trail, &recent).unwrap();
+        assert!(!estimate.terrain_matched);
+        assert_eq!(estimate.moving_time, 3000);
+
+        assert!(estimate_moving_time(10_000.0, 0.0, Terrain::Ride, &recent).is_none());
+        assert!(estimate_moving_time(10_000.0, 0.0, Terrain::Road, &[]).is_none());
+    }
+}
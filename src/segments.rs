@@ -0,0 +1,143 @@
+use chrono::NaiveDateTime;
+
+/// One attempt at a segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffortPoint {
+    pub start_date_local: NaiveDateTime,
+    /// Elapsed time in seconds
+    pub elapsed_time: i32,
+}
+
+/// How an athlete's times on a segment have developed
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffortTrend {
+    pub count: usize,
+    pub best: EffortPoint,
+    pub latest: EffortPoint,
+    /// Mean elapsed time in seconds
+    pub average: f64,
+    /// Least-squares change in elapsed time, in seconds per 30 days (negative = getting faster)
+    pub seconds_per_month: Option<f64>,
+    /// Mean of the most recent efforts minus mean of the earliest ones, in seconds
+    pub recent_vs_early: Option<f64>,
+}
+
+impl EffortTrend {
+    /// Efforts compared at each end of the history for `recent_vs_early`
+    const WINDOW: usize = 3;
+
+    /// Summarize efforts (any order); None when there are no efforts
+    pub fn from_efforts(efforts: &[EffortPoint]) -> Option<Self> {
+        let mut sorted = efforts.to_vec();
+        sorted.sort_by_key(|e| e.start_date_local);
+
+        let best = sorted.iter().min_by_key(|e| e.elapsed_time)?.clone();
+        let latest = sorted.last()?.clone();
+        let count = sorted.len();
+        let average = sorted.iter().map(|e| e.elapsed_time as f64).sum::<f64>() / count as f64;
+
+        let recent_vs_early = (count >= Self::WINDOW * 2).then(|| {
+            let mean = |slice: &[EffortPoint]| {
+                slice.iter().map(|e| e.elapsed_time as f64).sum::<f64>() / slice.len() as f64
+            };
+            mean(&sorted[count - Self::WINDOW..]) - mean(&sorted[..Self::WINDOW])
+        });
+
+        Some(Self {
+            count,
+            best,
+            latest,
+            average,
+            seconds_per_month: slope_per_month(&sorted),
+            recent_vs_early,
+        })
+    }
+
+    /// Plain-language description of the trend
+    pub fn describe(&self) -> String {
+        match self.seconds_per_month {
+            None => "Not enough efforts over time to show a trend".to_string(),
+            Some(slope) if slope.abs() < 1.0 => "Holding steady (less than 1s change per month)".to_string(),
+            Some(slope) if slope < 0.0 => format!("Getting faster by about {:.0}s per month", -slope),
+            Some(slope) => format!("Getting slower by about {:.0}s per month", slope),
+        }
+    }
+}
+
+/// Least-squares slope of elapsed time against date, in seconds per 30 days
+fn slope_per_month(efforts: &[EffortPoint]) -> Option<f64> {
+    let first = efforts.first()?.start_date_local;
+    let points: Vec<(f64, f64)> = efforts
+        .iter()
+        .map(|e| {
+            let days = (e.start_date_local - first).num_seconds() as f64 / 86400.0;
+            (days, e.elapsed_time as f64)
+        })
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+
+    // Needs at least two efforts on different days
+    if points.len() < 2 || variance < 1.0 {
+        return None;
+    }
+
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    Some(covariance / variance * 30.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    fn effort(day: i64, elapsed_time: i32) -> EffortPoint {
+        let start = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(7, 0, 0)
+            .unwrap();
+        EffortPoint {
+            start_date_local: start + Duration::days(day),
+            elapsed_time,
+        }
+    }
+
+    #[test]
+    fn test_improving_trend() {
+        // 10s faster every 30 days, given out of order
+        let efforts = vec![effort(60, 280), effort(0, 300), effort(30, 290), effort(90, 270)];
+
+        let trend = EffortTrend::from_efforts(&efforts).unwrap();
+
+        assert_eq!(trend.count, 4);
+        assert_eq!(trend.best, effort(90, 270));
+        assert_eq!(trend.latest, effort(90, 270));
+        assert_eq!(trend.average, 285.0);
+        assert!((trend.seconds_per_month.unwrap() + 10.0).abs() < 1e-9);
+        assert_eq!(trend.describe(), "Getting faster by about 10s per month");
+        assert_eq!(trend.recent_vs_early, None);
+    }
+
+    #[test]
+    fn test_recent_vs_early() {
+        let efforts: Vec<_> = (0..6).map(|i| effort(i * 7, 300 + i as i32 * 2)).collect();
+
+        let trend = EffortTrend::from_efforts(&efforts).unwrap();
+
+        // (306 + 308 + 310) / 3 - (300 + 302 + 304) / 3
+        assert_eq!(trend.recent_vs_early, Some(6.0));
+        assert!(trend.describe().starts_with("Getting slower"));
+    }
+
+    #[test]
+    fn test_single_effort() {
+        let trend = EffortTrend::from_efforts(&[effort(0, 300)]).unwrap();
+
+        assert_eq!(trend.seconds_per_month, None);
+        assert_eq!(trend.describe(), "Not enough efforts over time to show a trend");
+        assert!(EffortTrend::from_efforts(&[]).is_none());
+    }
+}
@@ -0,0 +1,457 @@
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::DateTime;
+use std::future::Future;
+use std::time::Instant;
+use strava_api::{
+    ActivityStats, ClubActivity, DetailedActivity, DetailedAthlete, DetailedSegment, DetailedSegmentEffort,
+    NewActivity, Route, StravaClient, StreamSet, SummaryClub, SummarySegment, UpdatableActivity, Upload,
+    UploadRequest, Zones,
+};
+
+use crate::activity::Activity;
+use crate::error::ToolError;
+use crate::progress;
+use crate::store::LocalStore;
+
+/// Where tools get Strava data from: the Strava API, locally imported files or test fixtures.
+///
+/// Only `activities` is required; sources without the rest (such as local files) report them as unavailable.
+#[async_trait]
+pub trait ActivitySource: Send + Sync {
+    /// Activities starting within [after, before), as Unix timestamps, oldest first
+    async fn activities(&self, after: Option<i64>, before: Option<i64>) -> Result<Vec<Activity>>;
+
+    /// The latest activities, newest first; sources that page stop once `enough` is satisfied
+    async fn recent_activities(&self, _enough: &(dyn for<'a> Fn(&'a [Activity]) -> bool + Sync)) -> Result<Vec<Activity>> {
+        let mut activities = self.activities(None, None).await?;
+        activities.reverse();
+        Ok(activities)
+    }
+
+    async fn get_activity(&self, _id: i64, _include_all_efforts: bool) -> Result<DetailedActivity> {
+        unsupported("Activity details")
+    }
+
+    async fn get_activity_streams(&self, _id: i64, _keys: &[&str]) -> Result<StreamSet> {
+        unsupported("Activity streams")
+    }
+
+    async fn get_logged_in_athlete(&self) -> Result<DetailedAthlete> {
+        unsupported("The athlete profile")
+    }
+
+    async fn get_athlete_stats(&self, _athlete_id: i64) -> Result<ActivityStats> {
+        unsupported("Athlete stats")
+    }
+
+    async fn get_logged_in_athlete_zones(&self) -> Result<Zones> {
+        unsupported("Heart rate zones")
+    }
+
+    async fn get_logged_in_athlete_starred_segments(&self, _page: u32, _per_page: u32) -> Result<Vec<SummarySegment>> {
+        unsupported("Starred segments")
+    }
+
+    async fn get_segment_by_id(&self, _id: i64) -> Result<DetailedSegment> {
+        unsupported("Segments")
+    }
+
+    async fn get_efforts_by_segment_id(
+        &self,
+        _segment_id: i64,
+        _start_date_local: Option<&str>,
+        _end_date_local: Option<&str>,
+        _page: u32,
+        _per_page: u32,
+    ) -> Result<Vec<DetailedSegmentEffort>> {
+        unsupported("Segment efforts")
+    }
+
+    async fn get_routes_by_athlete_id(&self, _athlete_id: i64, _page: u32, _per_page: u32) -> Result<Vec<Route>> {
+        unsupported("Routes")
+    }
+
+    async fn get_route_by_id(&self, _id: i64) -> Result<Route> {
+        unsupported("Routes")
+    }
+
+    async fn get_route_as_gpx(&self, _id: i64) -> Result<String> {
+        unsupported("Route GPX files")
+    }
+
+    async fn get_logged_in_athlete_clubs(&self, _page: u32, _per_page: u32) -> Result<Vec<SummaryClub>> {
+        unsupported("Clubs")
+    }
+
+    async fn get_club_activities_by_id(&self, _id: i64, _page: u32, _per_page: u32) -> Result<Vec<ClubActivity>> {
+        unsupported("Club activities")
+    }
+
+    async fn update_activity_by_id(&self, _id: i64, _update: &UpdatableActivity) -> Result<DetailedActivity> {
+        unsupported("Activity updates")
+    }
+
+    async fn create_activity(&self, _activity: &NewActivity) -> Result<DetailedActivity> {
+        unsupported("Manual activities")
+    }
+
+    async fn upload_activity(&self, _upload: UploadRequest) -> Result<Upload> {
+        unsupported("Uploads")
+    }
+
+    async fn get_upload_by_id(&self, _id: i64) -> Result<Upload> {
+        unsupported("Uploads")
+    }
+}
+
+fn unsupported<T>(what: &str) -> Result<T> {
+    bail!("{} are not available from this source", what)
+}
+
+/// Largest page of activities Strava returns
+const PER_PAGE: u32 = 200;
+
+/// Pages fetched at most for a range without a start: 10,000 activities, or 50 requests of the 15-minute limit
+const MAX_PAGES: u32 = 50;
+
+/// The Strava API
+#[async_trait]
+impl ActivitySource for StravaClient {
+    async fn activities(&self, after: Option<i64>, before: Option<i64>) -> Result<Vec<Activity>> {
+        // Strava pages results; a short page is the last one
+        let mut activities = Vec::new();
+        for page in 1.. {
+            if after.is_none() && page > MAX_PAGES {
+                return Err(anyhow::Error::new(ToolError::InvalidInput(format!(
+                    "More than {} activities without a start date; give a shorter period",
+                    MAX_PAGES * PER_PAGE
+                ))));
+            }
+            if activity_page(self, after, before, page, &mut activities).await? < PER_PAGE as usize {
+                break;
+            }
+        }
+        activities.sort_by_key(|a| a.start_date);
+        Ok(activities)
+    }
+
+    async fn recent_activities(&self, enough: &(dyn for<'a> Fn(&'a [Activity]) -> bool + Sync)) -> Result<Vec<Activity>> {
+        // Without `after`, Strava lists the newest activities first
+        let mut activities = Vec::new();
+        for page in 1..=MAX_PAGES {
+            if activity_page(self, None, None, page, &mut activities).await? < PER_PAGE as usize || enough(&activities) {
+                break;
+            }
+        }
+        Ok(activities)
+    }
+
+    async fn get_activity(&self, id: i64, include_all_efforts: bool) -> Result<DetailedActivity> {
+        strava("GET /activities/{id}", StravaClient::get_activity(self, id, include_all_efforts)).await
+    }
+
+    async fn get_activity_streams(&self, id: i64, keys: &[&str]) -> Result<StreamSet> {
+        strava("GET /activities/{id}/streams", StravaClient::get_activity_streams(self, id, keys)).await
+    }
+
+    async fn get_logged_in_athlete(&self) -> Result<DetailedAthlete> {
+        strava("GET /athlete", StravaClient::get_logged_in_athlete(self)).await
+    }
+
+    async fn get_athlete_stats(&self, athlete_id: i64) -> Result<ActivityStats> {
+        strava("GET /athletes/{id}/stats", StravaClient::get_athlete_stats(self, athlete_id)).await
+    }
+
+    async fn get_logged_in_athlete_zones(&self) -> Result<Zones> {
+        strava("GET /athlete/zones", StravaClient::get_logged_in_athlete_zones(self)).await
+    }
+
+    async fn get_logged_in_athlete_starred_segments(&self, page: u32, per_page: u32) -> Result<Vec<SummarySegment>> {
+        strava("GET /segments/starred", StravaClient::get_logged_in_athlete_starred_segments(self, page, per_page)).await
+    }
+
+    async fn get_segment_by_id(&self, id: i64) -> Result<DetailedSegment> {
+        strava("GET /segments/{id}", StravaClient::get_segment_by_id(self, id)).await
+    }
+
+    async fn get_efforts_by_segment_id(
+        &self,
+        segment_id: i64,
+        start_date_local: Option<&str>,
+        end_date_local: Option<&str>,
+        page: u32,
+        per_page: u32,
+    ) -> Result<Vec<DetailedSegmentEffort>> {
+        strava("GET /segment_efforts", StravaClient::get_efforts_by_segment_id(self, segment_id, start_date_local, end_date_local, page, per_page)).await
+    }
+
+    async fn get_routes_by_athlete_id(&self, athlete_id: i64, page: u32, per_page: u32) -> Result<Vec<Route>> {
+        strava("GET /athletes/{id}/routes", StravaClient::get_routes_by_athlete_id(self, athlete_id, page, per_page)).await
+    }
+
+    async fn get_route_by_id(&self, id: i64) -> Result<Route> {
+        strava("GET /routes/{id}", StravaClient::get_route_by_id(self, id)).await
+    }
+
+    async fn get_route_as_gpx(&self, id: i64) -> Result<String> {
+        strava("GET /routes/{id}/export_gpx", StravaClient::get_route_as_gpx(self, id)).await
+    }
+
+    async fn get_logged_in_athlete_clubs(&self, page: u32, per_page: u32) -> Result<Vec<SummaryClub>> {
+        strava("GET /athlete/clubs", StravaClient::get_logged_in_athlete_clubs(self, page, per_page)).await
+    }
+
+    async fn get_club_activities_by_id(&self, id: i64, page: u32, per_page: u32) -> Result<Vec<ClubActivity>> {
+        strava("GET /clubs/{id}/activities", StravaClient::get_club_activities_by_id(self, id, page, per_page)).await
+    }
+
+    async fn update_activity_by_id(&self, id: i64, update: &UpdatableActivity) -> Result<DetailedActivity> {
+        strava("PUT /activities/{id}", StravaClient::update_activity_by_id(self, id, update)).await
+    }
+
+    async fn create_activity(&self, activity: &NewActivity) -> Result<DetailedActivity> {
+        strava("POST /activities", StravaClient::create_activity(self, activity)).await
+    }
+
+    async fn upload_activity(&self, upload: UploadRequest) -> Result<Upload> {
+        strava("POST /uploads", StravaClient::upload_activity(self, upload)).await
+    }
+
+    async fn get_upload_by_id(&self, id: i64) -> Result<Upload> {
+        strava("GET /uploads/{id}", StravaClient::get_upload_by_id(self, id)).await
+    }
+}
+
+/// Fetch one page of activities onto `activities`, returning how many it had
+async fn activity_page(
+    client: &StravaClient,
+    after: Option<i64>,
+    before: Option<i64>,
+    page: u32,
+    activities: &mut Vec<Activity>,
+) -> Result<usize> {
+    let summaries =
+        strava("GET /athlete/activities", StravaClient::list_athlete_activities(client, after, before, page, PER_PAGE))
+            .await?;
+    for summary in &summaries {
+        activities.push(Activity::try_from(summary).map_err(|e| anyhow!(e))?);
+    }
+    progress::report(format!("Fetched page {} ({} activities)", page, activities.len())).await;
+    Ok(summaries.len())
+}
+
+/// Call a Strava endpoint, logging how long it took and classifying any failure
+async fn strava<T>(endpoint: &str, call: impl Future<Output = strava_api::Result<T>>) -> Result<T> {
+    let started = Instant::now();
+    let result = call.await;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    match &result {
+        Ok(_) => tracing::debug!(endpoint, elapsed_ms, "strava request"),
+        Err(e) => tracing::warn!(endpoint, elapsed_ms, error = %e, "strava request failed"),
+    }
+    result.map_err(|e| anyhow::Error::new(ToolError::from_strava(endpoint, &e.to_string())))
+}
+
+/// Activities imported from local files
+#[async_trait]
+impl ActivitySource for LocalStore {
+    async fn activities(&self, after: Option<i64>, before: Option<i64>) -> Result<Vec<Activity>> {
+        let to_utc = |timestamp: Option<i64>| timestamp.and_then(|t| DateTime::from_timestamp(t, 0));
+        Ok(self.between(to_utc(after), to_utc(before)))
+    }
+}
+
+#[cfg(test)]
+pub use fixture::FixtureSource;
+
+#[cfg(test)]
+mod fixture {
+    use super::*;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+    use strava_api::SummaryActivity;
+
+    /// Strava data read from a JSON file in tests/fixtures, for testing tools offline.
+    ///
+    /// Activity updates are kept; other writes are answered as Strava would but are not kept.
+    #[derive(Debug, Default, Deserialize)]
+    #[serde(default)]
+    pub struct FixtureSource {
+        athlete: Option<DetailedAthlete>,
+        stats: Option<ActivityStats>,
+        zones: Option<Zones>,
+        activities: Vec<SummaryActivity>,
+        details: std::sync::Mutex<Vec<DetailedActivity>>,
+        streams: HashMap<i64, StreamSet>,
+        starred_segments: Vec<SummarySegment>,
+        segments: Vec<DetailedSegment>,
+        segment_efforts: Vec<DetailedSegmentEffort>,
+        routes: Vec<Route>,
+        route_gpx: HashMap<i64, String>,
+        clubs: Vec<SummaryClub>,
+        club_activities: HashMap<i64, Vec<ClubActivity>>,
+    }
+
+    impl FixtureSource {
+        pub fn load(name: &str) -> Self {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures")
+                .join(name);
+            let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+            serde_json::from_str(&json).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+        }
+
+        fn detail(&self, id: i64) -> Result<DetailedActivity> {
+            self.details
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|a| a.id == id)
+                .cloned()
+                .ok_or_else(|| not_found("activity"))
+        }
+    }
+
+    /// Fails the way Strava does for unknown IDs
+    fn found<T>(value: Option<T>, resource: &str) -> Result<T> {
+        value.ok_or_else(|| not_found(resource))
+    }
+
+    fn not_found(resource: &str) -> anyhow::Error {
+        anyhow::Error::new(ToolError::NotFound { resource: resource.to_string() })
+    }
+
+    fn page<T: Clone>(items: &[T], page: u32, per_page: u32) -> Vec<T> {
+        let start = (page.saturating_sub(1) * per_page) as usize;
+        items.iter().skip(start).take(per_page as usize).cloned().collect()
+    }
+
+    #[async_trait]
+    impl ActivitySource for FixtureSource {
+        async fn activities(&self, after: Option<i64>, before: Option<i64>) -> Result<Vec<Activity>> {
+            let mut activities = self
+                .activities
+                .iter()
+                .map(Activity::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!(e))?;
+            activities.retain(|a| {
+                let start = a.start_date.timestamp();
+                after.map_or(true, |after| start >= after) && before.map_or(true, |before| start < before)
+            });
+            activities.sort_by_key(|a| a.start_date);
+            Ok(activities)
+        }
+
+        async fn get_activity(&self, id: i64, _include_all_efforts: bool) -> Result<DetailedActivity> {
+            self.detail(id)
+        }
+
+        async fn get_activity_streams(&self, id: i64, _keys: &[&str]) -> Result<StreamSet> {
+            found(self.streams.get(&id).cloned(), "activity")
+        }
+
+        async fn get_logged_in_athlete(&self) -> Result<DetailedAthlete> {
+            found(self.athlete.clone(), "athlete")
+        }
+
+        async fn get_athlete_stats(&self, _athlete_id: i64) -> Result<ActivityStats> {
+            found(self.stats.clone(), "athlete")
+        }
+
+        async fn get_logged_in_athlete_zones(&self) -> Result<Zones> {
+            found(self.zones.clone(), "athlete")
+        }
+
+        async fn get_logged_in_athlete_starred_segments(&self, page_number: u32, per_page: u32) -> Result<Vec<SummarySegment>> {
+            Ok(page(&self.starred_segments, page_number, per_page))
+        }
+
+        async fn get_segment_by_id(&self, id: i64) -> Result<DetailedSegment> {
+            found(self.segments.iter().find(|s| s.id == id).cloned(), "segment")
+        }
+
+        async fn get_efforts_by_segment_id(
+            &self,
+            segment_id: i64,
+            start_date_local: Option<&str>,
+            _end_date_local: Option<&str>,
+            page_number: u32,
+            per_page: u32,
+        ) -> Result<Vec<DetailedSegmentEffort>> {
+            let efforts: Vec<_> = self
+                .segment_efforts
+                .iter()
+                .filter(|e| e.segment.as_ref().map(|s| s.id) == Some(segment_id))
+                .filter(|e| start_date_local.map_or(true, |start| e.start_date_local.as_str() >= start))
+                .cloned()
+                .collect();
+            Ok(page(&efforts, page_number, per_page))
+        }
+
+        async fn get_routes_by_athlete_id(&self, _athlete_id: i64, page_number: u32, per_page: u32) -> Result<Vec<Route>> {
+            Ok(page(&self.routes, page_number, per_page))
+        }
+
+        async fn get_route_by_id(&self, id: i64) -> Result<Route> {
+            found(self.routes.iter().find(|r| r.id == id).cloned(), "route")
+        }
+
+        async fn get_route_as_gpx(&self, id: i64) -> Result<String> {
+            found(self.route_gpx.get(&id).cloned(), "route")
+        }
+
+        async fn get_logged_in_athlete_clubs(&self, page_number: u32, per_page: u32) -> Result<Vec<SummaryClub>> {
+            Ok(page(&self.clubs, page_number, per_page))
+        }
+
+        async fn get_club_activities_by_id(&self, id: i64, page_number: u32, per_page: u32) -> Result<Vec<ClubActivity>> {
+            let activities = found(self.club_activities.get(&id), "club")?;
+            Ok(page(activities, page_number, per_page))
+        }
+
+        async fn update_activity_by_id(&self, id: i64, update: &UpdatableActivity) -> Result<DetailedActivity> {
+            let mut activity = self.detail(id)?;
+            if let Some(name) = &update.name {
+                activity.name = name.clone();
+            }
+            if let Some(description) = &update.description {
+                activity.description = Some(description.clone());
+            }
+            if let Some(sport_type) = &update.sport_type {
+                activity.sport_type = sport_type.clone();
+            }
+            activity.workout_type = update.workout_type.or(activity.workout_type);
+            let mut details = self.details.lock().unwrap();
+            if let Some(saved) = details.iter_mut().find(|a| a.id == id) {
+                *saved = activity.clone();
+            }
+            Ok(activity)
+        }
+
+        async fn create_activity(&self, new: &NewActivity) -> Result<DetailedActivity> {
+            let mut activity = self.details.lock().unwrap().first().cloned().ok_or_else(|| anyhow!("No fixture activity to copy"))?;
+            activity.id = 9_000_000_001;
+            activity.name = new.name.clone();
+            activity.sport_type = new.sport_type.clone();
+            activity.start_date_local = new.start_date_local.clone();
+            activity.elapsed_time = new.elapsed_time;
+            activity.moving_time = new.elapsed_time;
+            activity.distance = new.distance.unwrap_or(0.0);
+            activity.description = new.description.clone();
+            Ok(activity)
+        }
+
+        async fn upload_activity(&self, upload: UploadRequest) -> Result<Upload> {
+            Ok(Upload {
+                id: 42,
+                id_str: Some("42".to_string()),
+                external_id: upload.external_id,
+                error: None,
+                status: "Your activity is ready.".to_string(),
+                activity_id: Some(9_000_000_002),
+            })
+        }
+    }
+}
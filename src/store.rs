@@ -0,0 +1,187 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use crate::activity::Activity;
+
+/// Activities imported from local files, and Strava activities kept current by webhook events, persisted as JSON next to the OAuth token
+#[derive(Debug, Default)]
+pub struct LocalStore {
+    path: PathBuf,
+    activities: Vec<Activity>,
+}
+
+impl LocalStore {
+    /// Load the store from disk, starting empty if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        let activities = if path.exists() {
+            let json = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read local store: {}", path.display()))?;
+            serde_json::from_str(&json)
+                .with_context(|| format!("Failed to parse local store: {}", path.display()))?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            activities,
+        })
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let json = serde_json::to_string(&self.activities)?;
+        std::fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write local store: {}", self.path.display()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.activities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.activities.is_empty()
+    }
+
+    /// Add an activity unless the same workout is already stored; returns whether it was added
+    pub fn insert(&mut self, activity: Activity) -> bool {
+        if self.activities.iter().any(|a| a.is_same_workout(&activity)) {
+            return false;
+        }
+
+        let index = self.activities.partition_point(|a| a.start_date <= activity.start_date);
+        self.activities.insert(index, activity);
+        true
+    }
+
+    /// Add or replace the Strava activity with the same id; returns whether the store changed
+    pub fn upsert(&mut self, activity: Activity) -> bool {
+        let Some(existing) = self.activities.iter().position(|a| activity.id.is_some() && a.id == activity.id) else {
+            return self.insert(activity);
+        };
+        if self.activities[existing] == activity {
+            return false;
+        }
+
+        // Replaced even when a file copy of the same workout is stored; only the order may change
+        self.activities.remove(existing);
+        let index = self.activities.partition_point(|a| a.start_date <= activity.start_date);
+        self.activities.insert(index, activity);
+        true
+    }
+
+    /// Remove the activity with a Strava id; returns whether it was stored
+    pub fn remove(&mut self, id: i64) -> bool {
+        let before = self.activities.len();
+        self.activities.retain(|a| a.id != Some(id));
+        self.activities.len() != before
+    }
+
+    /// Activities starting within [after, before), oldest first
+    pub fn between(&self, after: Option<DateTime<Utc>>, before: Option<DateTime<Utc>>) -> Vec<Activity> {
+        self.activities
+            .iter()
+            .filter(|a| after.map_or(true, |after| a.start_date >= after))
+            .filter(|a| before.map_or(true, |before| a.start_date < before))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Origin;
+    use chrono::{Duration, TimeZone};
+
+    fn activity(start: DateTime<Utc>) -> Activity {
+        Activity {
+            id: None,
+            name: "Morning Run".to_string(),
+            elapsed_time: 1550,
+            total_elevation_gain: 20.0,
+            origin: Origin::File(PathBuf::from("run.fit")),
+            ..Activity::test_run(start.naive_utc(), 5000.0, 1500)
+        }
+    }
+
+    #[test]
+    fn test_insert_deduplicates_and_sorts() {
+        let day = Utc.with_ymd_and_hms(2026, 1, 10, 8, 0, 0).unwrap();
+        let mut store = LocalStore::default();
+
+        assert!(store.insert(activity(day + Duration::days(1))));
+        assert!(store.insert(activity(day)));
+        assert!(!store.insert(activity(day + Duration::seconds(20))));
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.between(None, None)[0].start_date, day);
+    }
+
+    #[test]
+    fn test_upsert_and_remove() {
+        let day = Utc.with_ymd_and_hms(2026, 1, 10, 8, 0, 0).unwrap();
+        let mut store = LocalStore::default();
+        let mut run = Activity { id: Some(42), origin: Origin::Strava, ..activity(day) };
+
+        assert!(store.upsert(run.clone()));
+        assert!(!store.upsert(run.clone()));
+        run.name = "Renamed Run".to_string();
+        assert!(store.upsert(run));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.between(None, None)[0].name, "Renamed Run");
+
+        assert!(store.remove(42));
+        assert!(!store.remove(42));
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_upsert_beside_file_copy() {
+        let day = Utc.with_ymd_and_hms(2026, 1, 10, 8, 0, 0).unwrap();
+        let file_copy = activity(day);
+        let run = Activity { id: Some(42), origin: Origin::Strava, ..activity(day + Duration::seconds(20)) };
+        let mut store = LocalStore { path: PathBuf::new(), activities: vec![file_copy, run.clone()] };
+
+        let renamed = Activity { name: "Renamed Run".to_string(), ..run };
+        assert!(store.upsert(renamed));
+        assert_eq!(store.len(), 2);
+        let stored = store.between(None, None);
+        assert_eq!(stored[0].origin, Origin::File(PathBuf::from("run.fit")));
+        assert_eq!(stored[1].name, "Renamed Run");
+    }
+
+    #[test]
+    fn test_between() {
+        let day = Utc.with_ymd_and_hms(2026, 1, 10, 8, 0, 0).unwrap();
+        let mut store = LocalStore::default();
+        for offset in 0..5 {
+            store.insert(activity(day + Duration::days(offset)));
+        }
+
+        let window = store.between(Some(day + Duration::days(1)), Some(day + Duration::days(3)));
+        assert_eq!(window.len(), 2);
+        assert_eq!(store.between(Some(day + Duration::days(10)), None).len(), 0);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!("strava-mcp-store-{}.json", std::process::id()));
+        let mut store = LocalStore::load(&path).unwrap();
+        assert!(store.is_empty());
+
+        store.insert(activity(Utc.with_ymd_and_hms(2026, 1, 10, 8, 0, 0).unwrap()));
+        store.save().unwrap();
+
+        let reloaded = LocalStore::load(&path).unwrap();
+        assert_eq!(reloaded.between(None, None), store.between(None, None));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
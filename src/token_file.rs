@@ -0,0 +1,344 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use strava_api::Token;
+
+const CIPHER: &str = "xchacha20poly1305";
+const KDF: &str = "argon2id";
+
+/// Secret used to encrypt token files at rest
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKey {
+    Passphrase(String),
+    /// A file whose contents are the secret
+    KeyFile(PathBuf),
+}
+
+impl TokenKey {
+    /// Read STRAVA_TOKEN_PASSPHRASE or STRAVA_TOKEN_KEY_FILE; without either, tokens stay plaintext
+    pub fn from_env() -> Result<Option<Self>> {
+        let passphrase = std::env::var("STRAVA_TOKEN_PASSPHRASE").ok().filter(|p| !p.is_empty());
+        let key_file = std::env::var("STRAVA_TOKEN_KEY_FILE").ok().filter(|p| !p.is_empty());
+
+        match (passphrase, key_file) {
+            (Some(_), Some(_)) => bail!("Set only one of STRAVA_TOKEN_PASSPHRASE and STRAVA_TOKEN_KEY_FILE"),
+            (Some(passphrase), None) => Ok(Some(Self::Passphrase(passphrase))),
+            (None, Some(path)) => Ok(Some(Self::KeyFile(PathBuf::from(path)))),
+            (None, None) => Ok(None),
+        }
+    }
+
+    fn secret(&self) -> Result<Vec<u8>> {
+        const MIN_SECRET_LENGTH: usize = 12;
+
+        let secret = match self {
+            Self::Passphrase(passphrase) => passphrase.as_bytes().to_vec(),
+            Self::KeyFile(path) => {
+                check_permissions(path)?;
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read token key file: {}", path.display()))?;
+                // Ignore a trailing newline left by editors and `echo`
+                let end = bytes.iter().rposition(|b| !b.is_ascii_whitespace()).map_or(0, |i| i + 1);
+                bytes[..end].to_vec()
+            }
+        };
+
+        if secret.len() < MIN_SECRET_LENGTH {
+            bail!("Token encryption secret must be at least {} bytes", MIN_SECRET_LENGTH);
+        }
+        Ok(secret)
+    }
+}
+
+/// On-disk form of an encrypted token
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedToken {
+    encrypted: String,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// A token read from disk
+#[derive(Debug)]
+pub struct LoadedToken {
+    pub token: Token,
+    /// The file was plaintext but a key is configured, so it should be re-saved encrypted
+    pub needs_migration: bool,
+}
+
+/// A profile's token file, plaintext JSON or encrypted when a key is configured
+#[derive(Debug, Clone)]
+pub struct TokenFile {
+    path: PathBuf,
+    key: Option<TokenKey>,
+}
+
+impl TokenFile {
+    pub fn new(path: PathBuf, key: Option<TokenKey>) -> Self {
+        Self { path, key }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Whether tokens are encrypted when saved
+    pub fn is_encrypted(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// When the token was last written, i.e. issued or refreshed
+    pub fn modified(&self) -> Option<std::time::SystemTime> {
+        std::fs::metadata(&self.path).and_then(|m| m.modified()).ok()
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        if self.exists() {
+            std::fs::remove_file(&self.path)
+                .with_context(|| format!("Failed to delete token file: {}", self.path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Load the token, refusing files other users can read
+    pub fn load(&self) -> Result<LoadedToken> {
+        check_permissions(&self.path)?;
+
+        let text = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read token file: {}", self.path.display()))?;
+
+        match serde_json::from_str::<EncryptedToken>(&text) {
+            Ok(encrypted) => {
+                let key = self.key.as_ref().ok_or_else(|| {
+                    anyhow!(
+                        "{} is encrypted; set STRAVA_TOKEN_PASSPHRASE or STRAVA_TOKEN_KEY_FILE to read it",
+                        self.path.display()
+                    )
+                })?;
+                let token = decrypt(&encrypted, &key.secret()?)
+                    .with_context(|| format!("Failed to decrypt {}", self.path.display()))?;
+                Ok(LoadedToken {
+                    token,
+                    needs_migration: false,
+                })
+            }
+            Err(_) => {
+                let token = serde_json::from_str(&text)
+                    .with_context(|| format!("Failed to parse token file: {}", self.path.display()))?;
+                Ok(LoadedToken {
+                    token,
+                    needs_migration: self.key.is_some(),
+                })
+            }
+        }
+    }
+
+    /// Write the token (encrypted if a key is configured), readable only by the owner
+    pub fn save(&self, token: &Token) -> Result<()> {
+        let json = match &self.key {
+            Some(key) => serde_json::to_string_pretty(&encrypt(token, &key.secret()?)?)?,
+            None => serde_json::to_string_pretty(token)?,
+        };
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        // Write to a temporary file first so a crash never leaves a truncated token
+        let temp = self.path.with_extension("json.tmp");
+        write_private(&temp, json.as_bytes())?;
+        std::fs::rename(&temp, &self.path)
+            .with_context(|| format!("Failed to write token file: {}", self.path.display()))
+    }
+}
+
+fn derive_key(secret: &[u8], salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    argon2::Argon2::default()
+        .hash_password_into(secret, salt, &mut key)
+        .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn encrypt(token: &Token, secret: &[u8]) -> Result<EncryptedToken> {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = XChaCha20Poly1305::new(&derive_key(secret, &salt)?);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let plaintext = serde_json::to_vec(token)?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| anyhow!("Token encryption failed"))?;
+
+    Ok(EncryptedToken {
+        encrypted: CIPHER.to_string(),
+        kdf: KDF.to_string(),
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+fn decrypt(encrypted: &EncryptedToken, secret: &[u8]) -> Result<Token> {
+    if encrypted.encrypted != CIPHER || encrypted.kdf != KDF {
+        bail!(
+            "Unsupported token encryption ({} with {})",
+            encrypted.encrypted,
+            encrypted.kdf
+        );
+    }
+
+    let salt = BASE64.decode(&encrypted.salt).context("Invalid salt")?;
+    let nonce = BASE64.decode(&encrypted.nonce).context("Invalid nonce")?;
+    let ciphertext = BASE64.decode(&encrypted.ciphertext).context("Invalid ciphertext")?;
+    if nonce.len() != 24 {
+        bail!("Invalid nonce length");
+    }
+
+    let cipher = XChaCha20Poly1305::new(&derive_key(secret, &salt)?);
+    // Authentication fails for a wrong key as well as for tampered data
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| anyhow!("wrong passphrase or key file, or the file was modified"))?;
+
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Refuse secrets that every user on the machine can read
+#[cfg(unix)]
+pub fn check_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .permissions()
+        .mode();
+    if mode & 0o004 != 0 {
+        bail!(
+            "{} is readable by every user on this system (mode {:o}). Restrict it with: chmod 600 {}",
+            path.display(),
+            mode & 0o777,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn check_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    // The mode only applies to new files; a leftover file keeps its own until changed
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+        .with_context(|| format!("Failed to restrict permissions of {}", path.display()))?;
+    file.write_all(contents)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token() -> Token {
+        Token {
+            access_token: "access".to_string(),
+            refresh_token: "refresh".to_string(),
+            expires_at: 1_800_000_000,
+            scope: Some("read,activity:read_all".to_string()),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("strava-mcp-test-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("token.json")
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let encrypted = encrypt(&token(), b"correct horse battery").unwrap();
+
+        // The refresh token must not appear in the file
+        assert!(!serde_json::to_string(&encrypted).unwrap().contains("refresh"));
+
+        let decrypted = decrypt(&encrypted, b"correct horse battery").unwrap();
+        assert_eq!(decrypted.refresh_token, "refresh");
+        assert!(decrypt(&encrypted, b"wrong horse battery").is_err());
+    }
+
+    #[test]
+    fn test_migrates_plaintext() {
+        let path = temp_path("migrate");
+        let key = TokenKey::Passphrase("correct horse battery".to_string());
+
+        TokenFile::new(path.clone(), None).save(&token()).unwrap();
+        let loaded = TokenFile::new(path.clone(), Some(key.clone())).load().unwrap();
+        assert!(loaded.needs_migration);
+
+        let file = TokenFile::new(path.clone(), Some(key));
+        file.save(&loaded.token).unwrap();
+        let reloaded = file.load().unwrap();
+        assert!(!reloaded.needs_migration);
+        assert_eq!(reloaded.token.access_token, "access");
+
+        // Without the key the encrypted file cannot be read
+        assert!(TokenFile::new(path.clone(), None).load().is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_refuses_world_readable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = temp_path("permissions");
+        let file = TokenFile::new(path.clone(), None);
+        file.save(&token()).unwrap();
+        assert!(file.load().is_ok());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let error = file.load().unwrap_err().to_string();
+        assert!(error.contains("chmod 600"));
+
+        // A readable temp file left by an interrupted save is not reused as is
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, "{}").unwrap();
+        std::fs::set_permissions(&temp, std::fs::Permissions::from_mode(0o644)).unwrap();
+        file.save(&token()).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(file.load().is_ok());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+/// Aggregated distance, time and climbing over a set of activities
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    pub count: usize,
+    /// Distance in meters
+    pub distance: f64,
+    /// Moving time in seconds
+    pub moving_time: i32,
+    /// Elevation gain in meters
+    pub elevation_gain: f64,
+}
+
+impl Totals {
+    pub fn add(&mut self, distance: f64, moving_time: i32, elevation_gain: f64) {
+        self.count += 1;
+        self.distance += distance;
+        self.moving_time += moving_time;
+        self.elevation_gain += elevation_gain;
+    }
+
+    /// Average speed in meters per second from total distance and time (0 when unknown)
+    pub fn average_speed(&self) -> f64 {
+        if self.moving_time > 0 && self.distance > 0.0 {
+            self.distance / self.moving_time as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a crate::activity::Activity> for Totals {
+    fn from_iter<I: IntoIterator<Item = &'a crate::activity::Activity>>(iter: I) -> Self {
+        let mut totals = Self::default();
+        for activity in iter {
+            totals.add(activity.distance, activity.moving_time, activity.total_elevation_gain);
+        }
+        totals
+    }
+}
+
+/// Group activities by a key (e.g. athlete name) and rank the groups by total distance
+pub fn leaderboard<T>(
+    items: impl IntoIterator<Item = T>,
+    key: impl Fn(&T) -> String,
+    values: impl Fn(&T) -> (f64, i32, f64),
+) -> Vec<(String, Totals)> {
+    let mut groups: HashMap<String, Totals> = HashMap::new();
+    for item in items {
+        let (distance, moving_time, elevation_gain) = values(&item);
+        groups
+            .entry(key(&item))
+            .or_default()
+            .add(distance, moving_time, elevation_gain);
+    }
+
+    let mut ranked: Vec<_> = groups.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.distance.total_cmp(&a.1.distance).then_with(|| a.0.cmp(&b.0)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_totals() {
+        let mut totals = Totals::default();
+        totals.add(10_000.0, 3000, 50.0);
+        totals.add(5_000.0, 1500, 10.0);
+
+        assert_eq!(totals.count, 2);
+        assert_eq!(totals.distance, 15_000.0);
+        assert_eq!(totals.moving_time, 4500);
+        assert_eq!(totals.elevation_gain, 60.0);
+        assert!((totals.average_speed() - 3.333).abs() < 0.001);
+        assert_eq!(Totals::default().average_speed(), 0.0);
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let items = vec![("Ann", 10_000.0), ("Bob", 12_000.0), ("Ann", 5_000.0), ("Cat", 12_000.0)];
+
+        let ranked = leaderboard(items, |i| i.0.to_string(), |i| (i.1, 600, 0.0));
+
+        let names: Vec<_> = ranked.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Ann", "Bob", "Cat"]);
+        assert_eq!(ranked[0].1.count, 2);
+        assert_eq!(ranked[0].1.distance, 15_000.0);
+    }
+}
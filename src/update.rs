@@ -0,0 +1,166 @@
+use strava_api::{DetailedActivity, UpdatableActivity};
+
+/// OAuth scope Strava requires for editing activities
+pub const WRITE_SCOPE: &str = "activity:write";
+
+/// Whether a comma-separated list of granted scopes includes `scope`
+pub fn has_scope(granted: &str, scope: &str) -> bool {
+    granted.split(',').any(|s| s.trim() == scope)
+}
+
+/// Strava workout type code for a name like "race" or "long run".
+///
+/// Runs and rides use different codes for the same tags.
+pub fn workout_type_code(name: &str, sport_type: &str) -> Result<i32, String> {
+    let is_ride = sport_type.ends_with("Ride");
+    let normalized = name.trim().to_ascii_lowercase().replace([' ', '-'], "_");
+    match (normalized.as_str(), is_ride) {
+        ("default" | "none", false) => Ok(0),
+        ("race", false) => Ok(1),
+        ("long_run", false) => Ok(2),
+        ("workout", false) => Ok(3),
+        ("default" | "none", true) => Ok(10),
+        ("race", true) => Ok(11),
+        ("workout", true) => Ok(12),
+        ("long_run", true) => Err("long_run only applies to runs".to_string()),
+        _ => Err(format!(
+            "Unknown workout type '{}' (expected default, race, long_run or workout)",
+            name
+        )),
+    }
+}
+
+/// Human-readable name for a Strava workout type code
+pub fn workout_type_label(code: Option<i32>) -> &'static str {
+    match code {
+        Some(1 | 11) => "Race",
+        Some(2) => "Long Run",
+        Some(3 | 12) => "Workout",
+        _ => "Default",
+    }
+}
+
+/// One field an update would change
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub field: &'static str,
+    pub from: String,
+    pub to: String,
+}
+
+/// Fields in `update` that differ from the activity's current values
+pub fn changes(current: &DetailedActivity, update: &UpdatableActivity) -> Vec<Change> {
+    let text = |value: Option<&str>| match value {
+        Some(v) if !v.is_empty() => v.to_string(),
+        _ => "(none)".to_string(),
+    };
+    let flag = |value: bool| if value { "yes" } else { "no" }.to_string();
+
+    let mut changes = Vec::new();
+    let mut push = |field: &'static str, from: String, to: String| {
+        if from != to {
+            changes.push(Change { field, from, to });
+        }
+    };
+
+    if let Some(name) = &update.name {
+        push("Name", current.name.clone(), name.clone());
+    }
+    if let Some(description) = &update.description {
+        push("Description", text(current.description.as_deref()), text(Some(description)));
+    }
+    if let Some(sport_type) = &update.sport_type {
+        push("Sport Type", current.sport_type.clone(), sport_type.clone());
+    }
+    if let Some(code) = update.workout_type {
+        push(
+            "Workout Type",
+            workout_type_label(current.workout_type).to_string(),
+            workout_type_label(Some(code)).to_string(),
+        );
+    }
+    if let Some(gear_id) = &update.gear_id {
+        // Strava clears the gear when given "none"
+        let to = if gear_id == "none" { None } else { Some(gear_id.as_str()) };
+        push("Gear", text(current.gear_id.as_deref()), text(to));
+    }
+    if let Some(commute) = update.commute {
+        push("Commute", flag(current.commute), flag(commute));
+    }
+    if let Some(trainer) = update.trainer {
+        push("Trainer", flag(current.trainer), flag(trainer));
+    }
+    if let Some(hide) = update.hide_from_home {
+        push("Hide From Home", flag(current.hide_from_home.unwrap_or(false)), flag(hide));
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity() -> DetailedActivity {
+        DetailedActivity {
+            id: 1,
+            name: "Morning Run".to_string(),
+            activity_type: "Run".to_string(),
+            sport_type: "Run".to_string(),
+            distance: 10_000.0,
+            moving_time: 3000,
+            elapsed_time: 3100,
+            total_elevation_gain: 20.0,
+            start_date: "2026-03-01T07:00:00Z".to_string(),
+            start_date_local: "2026-03-01T08:00:00Z".to_string(),
+            average_speed: None,
+            max_speed: None,
+            average_heartrate: None,
+            max_heartrate: None,
+            gear_id: Some("g1".to_string()),
+            description: None,
+            workout_type: None,
+            commute: false,
+            trainer: false,
+            hide_from_home: None,
+            splits_metric: None,
+            segment_efforts: None,
+            best_efforts: None,
+        }
+    }
+
+    #[test]
+    fn test_has_scope() {
+        assert!(has_scope("read,activity:read_all,activity:write", WRITE_SCOPE));
+        assert!(!has_scope("read,activity:read_all", WRITE_SCOPE));
+        assert!(!has_scope("", WRITE_SCOPE));
+    }
+
+    #[test]
+    fn test_workout_type_code() {
+        assert_eq!(workout_type_code("race", "Run"), Ok(1));
+        assert_eq!(workout_type_code("Long Run", "TrailRun"), Ok(2));
+        assert_eq!(workout_type_code("workout", "Ride"), Ok(12));
+        assert!(workout_type_code("long_run", "Ride").is_err());
+        assert!(workout_type_code("tempo", "Run").is_err());
+    }
+
+    #[test]
+    fn test_changes() {
+        let update = UpdatableActivity {
+            name: Some("Parkrun".to_string()),
+            workout_type: Some(1),
+            gear_id: Some("none".to_string()),
+            commute: Some(false),
+            ..Default::default()
+        };
+
+        let changes = changes(&activity(), &update);
+
+        // Commute is already false, so it is not a change
+        let fields: Vec<_> = changes.iter().map(|c| c.field).collect();
+        assert_eq!(fields, vec!["Name", "Workout Type", "Gear"]);
+        assert_eq!(changes[1].to, "Race");
+        assert_eq!(changes[2].to, "(none)");
+    }
+}
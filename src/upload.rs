@@ -0,0 +1,186 @@
+use chrono::NaiveDateTime;
+use std::path::Path;
+
+use crate::import::FileFormat;
+
+/// How an activity file upload stands after a status check
+#[derive(Debug, Clone, PartialEq)]
+pub enum UploadOutcome {
+    /// Strava is still processing the file
+    Pending,
+    /// The file became this activity
+    Ready(i64),
+    /// The file matches an activity already on Strava
+    Duplicate { activity_id: Option<i64>, message: String },
+    Failed(String),
+}
+
+impl UploadOutcome {
+    pub fn from_upload(upload: &strava_api::Upload) -> Self {
+        if let Some(error) = upload.error.as_deref().filter(|e| !e.is_empty()) {
+            let message = strip_html(error);
+            return match message.to_ascii_lowercase().find("duplicate of") {
+                Some(index) => Self::Duplicate {
+                    activity_id: first_number(&message[index..]),
+                    message,
+                },
+                None => Self::Failed(message),
+            };
+        }
+
+        match upload.activity_id {
+            Some(id) => Self::Ready(id),
+            None if upload.status.to_ascii_lowercase().contains("deleted") => {
+                Self::Failed(upload.status.clone())
+            }
+            None => Self::Pending,
+        }
+    }
+}
+
+/// Strava upload `data_type` for a FIT, GPX or TCX file, optionally gzipped
+pub fn data_type(path: &Path) -> Option<String> {
+    let gzipped = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gz"));
+    let inner = if gzipped { Path::new(path.file_stem()?) } else { path };
+
+    let format = match FileFormat::from_path(inner)? {
+        FileFormat::Fit => "fit",
+        FileFormat::Gpx => "gpx",
+        FileFormat::Tcx => "tcx",
+    };
+
+    Some(if gzipped { format!("{}.gz", format) } else { format.to_string() })
+}
+
+/// Parse a duration given as "HH:MM:SS", "MM:SS" or whole minutes into seconds
+pub fn parse_duration(input: &str) -> Result<i32, String> {
+    let invalid = || format!("Invalid duration '{}' (expected HH:MM:SS, MM:SS or minutes)", input);
+
+    let parts = input
+        .trim()
+        .split(':')
+        .map(|p| p.parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let seconds = match parts.as_slice() {
+        [minutes] => minutes.checked_mul(60),
+        [minutes, seconds] if *seconds < 60 => minutes.checked_mul(60).and_then(|m| m.checked_add(*seconds)),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            hours.checked_mul(3600).and_then(|h| h.checked_add(minutes * 60 + seconds))
+        }
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+
+    i32::try_from(seconds).map_err(|_| invalid())
+}
+
+/// Parse a local start time such as "2026-02-09T18:30" or "2026-02-09 18:30:00"
+pub fn parse_start(input: &str) -> Result<NaiveDateTime, String> {
+    const FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"];
+
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input.trim(), format).ok())
+        .ok_or_else(|| format!("Invalid start time '{}' (expected YYYY-MM-DDTHH:MM)", input))
+}
+
+/// Upload errors sometimes embed a link to the duplicate activity
+fn strip_html(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => output.push(c),
+            _ => {}
+        }
+    }
+    output.trim().to_string()
+}
+
+fn first_number(text: &str) -> Option<i64> {
+    let digits: String = text
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(status: &str, error: Option<&str>, activity_id: Option<i64>) -> strava_api::Upload {
+        strava_api::Upload {
+            id: 1,
+            id_str: None,
+            external_id: None,
+            error: error.map(str::to_string),
+            status: status.to_string(),
+            activity_id,
+        }
+    }
+
+    #[test]
+    fn test_outcome() {
+        assert_eq!(
+            UploadOutcome::from_upload(&upload("Your activity is still being processed.", None, None)),
+            UploadOutcome::Pending
+        );
+        assert_eq!(
+            UploadOutcome::from_upload(&upload("Your activity is ready.", None, Some(42))),
+            UploadOutcome::Ready(42)
+        );
+        assert_eq!(
+            UploadOutcome::from_upload(&upload(
+                "There was an error processing your activity.",
+                Some("run.fit duplicate of <a href='/activities/123'>Morning Run</a> (activity 123)"),
+                None
+            )),
+            UploadOutcome::Duplicate {
+                activity_id: Some(123),
+                message: "run.fit duplicate of Morning Run (activity 123)".to_string()
+            }
+        );
+        assert_eq!(
+            UploadOutcome::from_upload(&upload("error", Some("Improperly formatted data."), None)),
+            UploadOutcome::Failed("Improperly formatted data.".to_string())
+        );
+    }
+
+    #[test]
+    fn test_data_type() {
+        assert_eq!(data_type(Path::new("run.FIT")).as_deref(), Some("fit"));
+        assert_eq!(data_type(Path::new("a/run.gpx.gz")).as_deref(), Some("gpx.gz"));
+        assert_eq!(data_type(Path::new("run.tcx")).as_deref(), Some("tcx"));
+        assert_eq!(data_type(Path::new("notes.txt.gz")), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(2700));
+        assert_eq!(parse_duration("32:30"), Ok(1950));
+        assert_eq!(parse_duration("1:05:00"), Ok(3900));
+        assert!(parse_duration("1:75").is_err());
+        assert!(parse_duration("abc").is_err());
+
+        // Too long to count in seconds
+        assert!(parse_duration("99999999").is_err());
+        assert!(parse_duration("99999999:00").is_err());
+        assert!(parse_duration("9999999:00:00").is_err());
+        assert!(parse_duration("999999:00:00").is_err());
+    }
+
+    #[test]
+    fn test_parse_start() {
+        let start = parse_start("2026-02-09T18:30").unwrap();
+        assert_eq!(start.to_string(), "2026-02-09 18:30:00");
+        assert!(parse_start("2026-02-09 18:30:15").is_ok());
+        assert!(parse_start("yesterday").is_err());
+    }
+}
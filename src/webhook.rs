@@ -0,0 +1,372 @@
+use anyhow::{bail, Context, Result};
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use strava_api::OAuthConfig;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::Sender;
+
+/// Path Strava calls for validation and events
+pub const PATH: &str = "/webhook";
+
+/// Largest event body accepted; Strava's are a few hundred bytes
+const MAX_BODY_LEN: usize = 64 * 1024;
+
+/// Events waiting to be applied; beyond this, senders are told to retry later
+pub const QUEUE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObjectType {
+    Activity,
+    Athlete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AspectType {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A push subscription event: something changed for an athlete who authorized this app
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Event {
+    pub object_type: ObjectType,
+    pub object_id: i64,
+    pub aspect_type: AspectType,
+    /// Athlete the object belongs to
+    pub owner_id: i64,
+    pub subscription_id: i64,
+    pub event_time: i64,
+    /// Changed fields, e.g. {"title": "Lunch Run"} or {"authorized": "false"}
+    #[serde(default)]
+    pub updates: Map<String, Value>,
+}
+
+impl Event {
+    /// The athlete revoked this app's access
+    pub fn is_deauthorization(&self) -> bool {
+        self.object_type == ObjectType::Athlete
+            && self.updates.get("authorized").and_then(Value::as_str) == Some("false")
+    }
+}
+
+/// Query string of Strava's subscription validation request
+#[derive(Debug, Deserialize)]
+struct Validation {
+    #[serde(rename = "hub.mode")]
+    mode: String,
+    #[serde(rename = "hub.verify_token")]
+    verify_token: String,
+    #[serde(rename = "hub.challenge")]
+    challenge: String,
+}
+
+/// Answers Strava's validation request and queues events for the MCP server
+struct Receiver {
+    verify_token: String,
+    events: Sender<Event>,
+}
+
+/// Accept connections on `listener` until it fails, sending each event to `events`
+pub async fn serve(listener: TcpListener, verify_token: String, events: Sender<Event>) -> Result<()> {
+    let receiver = Arc::new(Receiver { verify_token, events });
+    loop {
+        let (stream, _) = listener.accept().await.context("Webhook receiver failed to accept a connection")?;
+        let receiver = receiver.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| {
+                let receiver = receiver.clone();
+                async move { Ok::<_, hyper::Error>(receiver.handle(request).await) }
+            });
+            if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+                tracing::debug!(error = %e, "webhook connection closed with an error");
+            }
+        });
+    }
+}
+
+impl Receiver {
+    async fn handle(&self, request: Request<Incoming>) -> Response<Full<Bytes>> {
+        if request.uri().path() != PATH {
+            return respond(StatusCode::NOT_FOUND, Value::Null);
+        }
+        match *request.method() {
+            Method::GET => self.validate(request.uri().query().unwrap_or_default()),
+            Method::POST => self.receive(request.into_body()).await,
+            _ => respond(StatusCode::METHOD_NOT_ALLOWED, Value::Null),
+        }
+    }
+
+    /// Echo the challenge when Strava validates a new subscription with our verify token
+    fn validate(&self, query: &str) -> Response<Full<Bytes>> {
+        match serde_urlencoded::from_str::<Validation>(query) {
+            Ok(validation) if validation.mode == "subscribe" && validation.verify_token == self.verify_token => {
+                tracing::info!("validated webhook subscription");
+                respond(StatusCode::OK, json!({ "hub.challenge": validation.challenge }))
+            }
+            Ok(_) => {
+                tracing::warn!("rejected webhook validation with the wrong verify token");
+                respond(StatusCode::FORBIDDEN, Value::Null)
+            }
+            Err(_) => respond(StatusCode::BAD_REQUEST, Value::Null),
+        }
+    }
+
+    /// Queue an event and acknowledge it at once; Strava retries events not acknowledged within two seconds
+    async fn receive(&self, body: Incoming) -> Response<Full<Bytes>> {
+        let body = match http_body_util::Limited::new(body, MAX_BODY_LEN).collect().await {
+            Ok(body) => body.to_bytes(),
+            Err(_) => return respond(StatusCode::PAYLOAD_TOO_LARGE, Value::Null),
+        };
+        let event: Event = match serde_json::from_slice(&body) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!(error = %e, "ignored malformed webhook event");
+                return respond(StatusCode::BAD_REQUEST, Value::Null);
+            }
+        };
+        tracing::info!(
+            object_type = ?event.object_type,
+            object_id = event.object_id,
+            aspect_type = ?event.aspect_type,
+            owner_id = event.owner_id,
+            "received webhook event"
+        );
+        match self.events.try_send(event) {
+            Ok(()) => respond(StatusCode::OK, Value::Null),
+            Err(TrySendError::Full(event)) => {
+                tracing::warn!(object_id = event.object_id, "webhook queue is full; asked the sender to retry");
+                respond(StatusCode::TOO_MANY_REQUESTS, Value::Null)
+            }
+            Err(TrySendError::Closed(_)) => respond(StatusCode::SERVICE_UNAVAILABLE, Value::Null),
+        }
+    }
+}
+
+/// Keep only the latest event for each object, in the order those arrived.
+///
+/// Applying an event re-reads the object from Strava, so earlier events for it add nothing but requests.
+pub fn collapse(events: Vec<Event>) -> Vec<Event> {
+    let mut latest: Vec<Event> = Vec::with_capacity(events.len());
+    for event in events {
+        latest.retain(|e| (e.object_type, e.object_id) != (event.object_type, event.object_id));
+        latest.push(event);
+    }
+    latest
+}
+
+fn respond(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    let body = if body.is_null() { Bytes::new() } else { Bytes::from(body.to_string()) };
+    let mut response = Response::new(Full::new(body));
+    *response.status_mut() = status;
+    response
+}
+
+/// A push subscription, as listed by Strava
+#[derive(Debug, Clone, Deserialize)]
+pub struct Subscription {
+    pub id: i64,
+    pub callback_url: String,
+    pub created_at: Option<String>,
+}
+
+/// Manages the app's push subscription; Strava allows one per app, authenticated with the client credentials
+pub struct Subscriptions {
+    http: reqwest::Client,
+    url: String,
+    client_id: String,
+    client_secret: String,
+}
+
+impl Subscriptions {
+    pub fn new(oauth: &OAuthConfig) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: format!("{}/api/v3/push_subscriptions", oauth.base_url.trim_end_matches('/')),
+            client_id: oauth.client_id.clone(),
+            client_secret: oauth.client_secret.clone(),
+        }
+    }
+
+    /// Subscribe `callback_url`; Strava validates it with `verify_token` before answering, so the receiver must be reachable
+    pub async fn create(&self, callback_url: &str, verify_token: &str) -> Result<i64> {
+        #[derive(Deserialize)]
+        struct Created {
+            id: i64,
+        }
+
+        let form = [
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+            ("callback_url", callback_url),
+            ("verify_token", verify_token),
+        ];
+        let response = self.http.post(&self.url).form(&form).send().await?;
+        let created: Created = check(response).await?.json().await.context("Invalid subscription response")?;
+        Ok(created.id)
+    }
+
+    pub async fn list(&self) -> Result<Vec<Subscription>> {
+        let response = self.http.get(&self.url).query(&self.credentials()).send().await?;
+        check(response).await?.json().await.context("Invalid subscription list")
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<()> {
+        let url = format!("{}/{}", self.url, id);
+        let response = self.http.delete(url).query(&self.credentials()).send().await?;
+        check(response).await?;
+        Ok(())
+    }
+
+    fn credentials(&self) -> [(&str, &str); 2] {
+        [("client_id", self.client_id.as_str()), ("client_secret", self.client_secret.as_str())]
+    }
+}
+
+/// Fail with the status and body the way the Strava client reports API errors
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    bail!("Strava API error {}: {}", status, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// A receiver on a free local port queueing up to `queue_len` events, with the URL to call it on
+    async fn start(queue_len: usize) -> (String, mpsc::Receiver<Event>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), PATH);
+        let (sender, events) = mpsc::channel(queue_len);
+        tokio::spawn(serve(listener, "s3cret".to_string(), sender));
+        (url, events)
+    }
+
+    fn event(object_type: &str, object_id: i64, aspect_type: &str) -> Value {
+        json!({
+            "aspect_type": aspect_type,
+            "event_time": 1771318200,
+            "object_id": object_id,
+            "object_type": object_type,
+            "owner_id": 134815,
+            "subscription_id": 120475,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_validation() {
+        let (url, _events) = start(QUEUE_LEN).await;
+        let http = reqwest::Client::new();
+
+        let query = [("hub.mode", "subscribe"), ("hub.verify_token", "s3cret"), ("hub.challenge", "15f7d1a91c1f40f8")];
+        let response = http.get(&url).query(&query).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body, json!({ "hub.challenge": "15f7d1a91c1f40f8" }));
+
+        let query = [("hub.mode", "subscribe"), ("hub.verify_token", "guess"), ("hub.challenge", "15f7d1a91c1f40f8")];
+        let response = http.get(&url).query(&query).send().await.unwrap();
+        assert_eq!(response.status(), 403);
+
+        let response = http.get(&url).send().await.unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_events() {
+        let (url, mut events) = start(QUEUE_LEN).await;
+        let http = reqwest::Client::new();
+
+        let payload = json!({
+            "aspect_type": "update",
+            "event_time": 1771318200,
+            "object_id": 1360128428,
+            "object_type": "activity",
+            "owner_id": 134815,
+            "subscription_id": 120475,
+            "updates": { "title": "Messy" },
+        });
+        let response = http.post(&url).json(&payload).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let event = events.recv().await.unwrap();
+        assert_eq!((event.object_type, event.aspect_type), (ObjectType::Activity, AspectType::Update));
+        assert_eq!(event.object_id, 1360128428);
+        assert!(!event.is_deauthorization());
+
+        let payload = json!({
+            "aspect_type": "update",
+            "event_time": 1771318200,
+            "object_id": 134815,
+            "object_type": "athlete",
+            "owner_id": 134815,
+            "subscription_id": 120475,
+            "updates": { "authorized": "false" },
+        });
+        http.post(&url).json(&payload).send().await.unwrap();
+        assert!(events.recv().await.unwrap().is_deauthorization());
+
+        let response = http.post(&url).body("{\"object_type\": \"club\"}").send().await.unwrap();
+        assert_eq!(response.status(), 400);
+        let response = http.post(url.replace(PATH, "/other")).json(&payload).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_full_queue() {
+        let (url, mut events) = start(1).await;
+        let http = reqwest::Client::new();
+
+        let response = http.post(&url).json(&event("activity", 1, "create")).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let response = http.post(&url).json(&event("activity", 2, "create")).send().await.unwrap();
+        assert_eq!(response.status(), 429);
+
+        // Once the queue drains, events are accepted again
+        assert_eq!(events.recv().await.unwrap().object_id, 1);
+        let response = http.post(&url).json(&event("activity", 2, "create")).send().await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        drop(events);
+        let response = http.post(&url).json(&event("activity", 3, "create")).send().await.unwrap();
+        assert_eq!(response.status(), 503);
+    }
+
+    #[test]
+    fn test_collapse() {
+        let events: Vec<Event> = [
+            event("activity", 1, "create"),
+            event("activity", 2, "update"),
+            event("activity", 1, "update"),
+            event("athlete", 1, "update"),
+            event("activity", 1, "delete"),
+        ]
+        .into_iter()
+        .map(|e| serde_json::from_value(e).unwrap())
+        .collect();
+
+        let collapsed: Vec<_> = collapse(events).into_iter().map(|e| (e.object_type, e.object_id, e.aspect_type)).collect();
+        assert_eq!(
+            collapsed,
+            [
+                (ObjectType::Activity, 2, AspectType::Update),
+                (ObjectType::Athlete, 1, AspectType::Update),
+                (ObjectType::Activity, 1, AspectType::Delete),
+            ]
+        );
+    }
+}
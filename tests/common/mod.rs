@@ -0,0 +1,325 @@
+use chrono::DateTime;
+use rmcp::model::ProgressNotificationParam;
+use rmcp::service::{NotificationContext, RoleClient, RunningService};
+use rmcp::{ClientHandler, ServiceExt};
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use strava_api::OAuthConfig;
+use wiremock::matchers::{body_string_contains, method, path, path_regex, query_param};
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+use strava_mcp::StravaMcpServer;
+
+/// Strava's default limits: 200 requests per 15 minutes, 2000 per day
+const RATE_LIMIT: &str = "200,2000";
+
+/// A local HTTP server standing in for the Strava v3 API and OAuth token endpoint
+pub struct StravaStandIn {
+    server: MockServer,
+}
+
+impl StravaStandIn {
+    pub async fn start() -> Self {
+        Self {
+            server: MockServer::start().await,
+        }
+    }
+
+    /// OAuth config pointing the Strava client at this server
+    pub fn config(&self) -> OAuthConfig {
+        OAuthConfig::new("12345", "stand-in-secret").with_base_url(self.server.uri())
+    }
+
+    /// Serve summary activities to requests authorized with `access_token`
+    pub async fn activities(&self, access_token: &str, activities: Vec<Value>) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/athlete/activities"))
+            .respond_with(ActivitiesResponder {
+                access_token: access_token.to_string(),
+                activities,
+                requests: AtomicU32::new(0),
+            })
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Exchange `refresh_token` for a token with `access_token`
+    pub async fn refresh(&self, refresh_token: &str, access_token: &str) {
+        let expires_at = chrono::Utc::now().timestamp() + 6 * 3600;
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains(format!("refresh_token={}", refresh_token)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "token_type": "Bearer",
+                "access_token": access_token,
+                "refresh_token": format!("{}-next", refresh_token),
+                "expires_at": expires_at,
+                "expires_in": 6 * 3600,
+            })))
+            .mount(&self.server)
+            .await;
+
+        // Any other refresh token is rejected the way Strava does
+        Mock::given(method("POST"))
+            .and(path("/oauth/token"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(strava_error(
+                "Bad Request",
+                "RefreshToken",
+                "refresh_token",
+            )))
+            .with_priority(10)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Answer every activities request with a full page, after `delay`
+    pub async fn slow_activities(&self, delay: Duration) {
+        let start = DateTime::parse_from_rfc3339("2026-03-01T06:00:00Z").unwrap();
+        let page: Vec<Value> = (0..200)
+            .map(|i| {
+                let date = (start + chrono::Duration::hours(i)).to_rfc3339();
+                summary_activity(5000 + i, "Run", "Run", &date, 5000.0, 1500)
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/api/v3/athlete/activities"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(page).set_delay(delay))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serve the authorized athlete's profile
+    pub async fn athlete(&self, id: i64) {
+        Mock::given(method("GET"))
+            .and(path("/api/v3/athlete"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "id": id,
+                "firstname": "Stand",
+                "lastname": "In",
+            })))
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Serve detailed activities by id; other ids are not found
+    pub async fn activity_details(&self, activities: Vec<Value>) {
+        for activity in activities {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v3/activities/{}", activity["id"])))
+                .respond_with(ResponseTemplate::new(200).set_body_json(activity))
+                .mount(&self.server)
+                .await;
+        }
+        Mock::given(method("GET"))
+            .and(path_regex(r"^/api/v3/activities/\d+$"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(strava_error("Record Not Found", "Activity", "id")))
+            .with_priority(10)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Delete an activity served by `activity_details`
+    pub async fn delete_activity(&self, id: i64) {
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v3/activities/{}", id)))
+            .respond_with(ResponseTemplate::new(404).set_body_json(strava_error("Record Not Found", "Activity", "id")))
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Emulate /push_subscriptions holding one subscription, for requests with the stand-in's client credentials
+    pub async fn push_subscription(&self, id: i64, callback_url: &str) {
+        Mock::given(method("POST"))
+            .and(path("/api/v3/push_subscriptions"))
+            .and(body_string_contains("client_secret=stand-in-secret"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": id })))
+            .mount(&self.server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/v3/push_subscriptions"))
+            .and(query_param("client_secret", "stand-in-secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                "id": id,
+                "resource_state": 2,
+                "application_id": 12345,
+                "callback_url": callback_url,
+                "created_at": "2026-02-17T09:15:00+00:00",
+                "updated_at": "2026-02-17T09:15:00+00:00",
+            }])))
+            .mount(&self.server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path(format!("/api/v3/push_subscriptions/{}", id)))
+            .and(query_param("client_secret", "stand-in-secret"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&self.server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path_regex(r"^/api/v3/push_subscriptions/\d+$"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(strava_error("Record Not Found", "PushSubscription", "id")))
+            .with_priority(10)
+            .mount(&self.server)
+            .await;
+    }
+
+    /// Number of requests received for a path
+    pub async fn requests(&self, path: &str) -> usize {
+        let requests = self.server.received_requests().await.unwrap_or_default();
+        requests.iter().filter(|r| r.url.path() == path).count()
+    }
+
+    /// Fail every activities request with an HTTP error status
+    pub async fn fail(&self, status: u16) {
+        let response = match status {
+            401 => ResponseTemplate::new(401).set_body_json(strava_error("Authorization Error", "Athlete", "access_token")),
+            429 => ResponseTemplate::new(429)
+                .insert_header("X-RateLimit-Limit", RATE_LIMIT)
+                .insert_header("X-RateLimit-Usage", "201,1450")
+                .set_body_json(json!({ "message": "Rate Limit Exceeded", "errors": [] })),
+            status => ResponseTemplate::new(status).set_body_json(json!({ "message": "Internal Server Error", "errors": [] })),
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v3/athlete/activities"))
+            .respond_with(response)
+            .with_priority(1)
+            .mount(&self.server)
+            .await;
+    }
+}
+
+/// A Strava summary activity as returned by /athlete/activities
+pub fn summary_activity(id: i64, name: &str, sport_type: &str, start_date: &str, distance: f64, moving_time: i32) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "type": sport_type,
+        "sport_type": sport_type,
+        "distance": distance,
+        "moving_time": moving_time,
+        "elapsed_time": moving_time + 30,
+        "total_elevation_gain": 10.0,
+        "start_date": start_date,
+        "start_date_local": start_date,
+        "average_speed": distance / f64::from(moving_time),
+    })
+}
+
+/// A Strava detailed activity as returned by /activities/{id}
+pub fn detailed_activity(id: i64, name: &str, sport_type: &str, start_date: &str, distance: f64, moving_time: i32) -> Value {
+    let mut activity = summary_activity(id, name, sport_type, start_date, distance, moving_time);
+    activity["commute"] = json!(false);
+    activity["trainer"] = json!(false);
+    activity["description"] = json!(null);
+    activity
+}
+
+fn strava_error(message: &str, resource: &str, field: &str) -> Value {
+    json!({
+        "message": message,
+        "errors": [{ "resource": resource, "field": field, "code": "invalid" }],
+    })
+}
+
+/// Emulates /athlete/activities: bearer auth, `after`/`before` filtering, paging and rate-limit headers
+struct ActivitiesResponder {
+    access_token: String,
+    activities: Vec<Value>,
+    requests: AtomicU32,
+}
+
+impl Respond for ActivitiesResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let count = self.requests.fetch_add(1, Ordering::SeqCst) + 1;
+        let usage = format!("{},{}", count, count);
+
+        let authorized = request
+            .headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value == format!("Bearer {}", self.access_token));
+        if !authorized {
+            return ResponseTemplate::new(401).set_body_json(strava_error("Authorization Error", "Athlete", "access_token"));
+        }
+
+        let query = |name: &str| {
+            request
+                .url
+                .query_pairs()
+                .find(|(key, _)| key == name)
+                .and_then(|(_, value)| value.parse::<i64>().ok())
+        };
+        let after = query("after");
+        let before = query("before");
+        let page = query("page").unwrap_or(1).max(1) as usize;
+        let per_page = query("per_page").unwrap_or(30).clamp(1, 200) as usize;
+
+        let start = |activity: &Value| {
+            activity["start_date"]
+                .as_str()
+                .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+                .map_or(0, |date| date.timestamp())
+        };
+        let mut activities: Vec<&Value> = self
+            .activities
+            .iter()
+            .filter(|a| after.map_or(true, |after| start(a) > after) && before.map_or(true, |before| start(a) < before))
+            .collect();
+
+        // Newest first, except that Strava returns oldest first when `after` is given
+        activities.sort_by_key(|a| start(a));
+        if after.is_none() {
+            activities.reverse();
+        }
+
+        let page: Vec<&Value> = activities.into_iter().skip((page - 1) * per_page).take(per_page).collect();
+        ResponseTemplate::new(200)
+            .insert_header("X-RateLimit-Limit", RATE_LIMIT)
+            .insert_header("X-RateLimit-Usage", usage.as_str())
+            .set_body_json(page)
+    }
+}
+
+/// MCP client that records the progress notifications it receives
+#[derive(Clone, Default)]
+pub struct ProgressRecorder {
+    pub notifications: Arc<Mutex<Vec<ProgressNotificationParam>>>,
+}
+
+impl ClientHandler for ProgressRecorder {
+    async fn on_progress(&self, params: ProgressNotificationParam, _context: NotificationContext<RoleClient>) {
+        self.notifications.lock().unwrap().push(params);
+    }
+}
+
+/// Run the MCP server over an in-process transport and connect a client to it
+pub async fn connect(server: StravaMcpServer) -> RunningService<RoleClient, ()> {
+    connect_as(server, ()).await
+}
+
+/// As `connect`, with a client that handles notifications
+pub async fn connect_as<C: ClientHandler>(server: StravaMcpServer, client: C) -> RunningService<RoleClient, C> {
+    let (server_transport, client_transport) = tokio::io::duplex(64 * 1024);
+    tokio::spawn(async move {
+        let service = server.serve(server_transport).await?;
+        service.waiting().await?;
+        anyhow::Ok(())
+    });
+    client.serve(client_transport).await.expect("MCP client failed to connect")
+}
+
+/// Call a tool over MCP, returning its text or the error message
+pub async fn call_tool<C: ClientHandler>(client: &RunningService<RoleClient, C>, name: &str, args: Value) -> Result<String, String> {
+    let params = serde_json::from_value(json!({ "name": name, "arguments": args })).unwrap();
+    let result = client.call_tool(params).await.map_err(|e| e.to_string())?;
+    Ok(result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text())
+        .map(|t| t.text.as_str())
+        .collect())
+}
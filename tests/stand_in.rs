@@ -0,0 +1,394 @@
+mod common;
+
+use chrono::DateTime;
+use common::*;
+use rmcp::model::{
+    CallToolRequest, ClientRequest, ReadResourceRequestParams, ResourceContents, SubscribeRequestParams,
+};
+use rmcp::service::{PeerRequestOptions, RoleClient, RunningService};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use strava_api::Token;
+use strava_mcp::config::{self, Config};
+use strava_mcp::gear::RetirementThresholds;
+use strava_mcp::profiles::{ProfilePaths, DEFAULT_PROFILE};
+use strava_mcp::token_file::TokenFile;
+use strava_mcp::{webhook, Profile, StravaMcpServer};
+
+fn base_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("strava-mcp-stand-in-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn save_token(base_dir: &Path, access_token: &str, refresh_token: &str, expires_at: i64) {
+    let paths = ProfilePaths::new(base_dir, DEFAULT_PROFILE);
+    let token = Token {
+        access_token: access_token.to_string(),
+        refresh_token: refresh_token.to_string(),
+        expires_at,
+        scope: Some("read,activity:read_all".to_string()),
+    };
+    TokenFile::new(paths.token, None).save(&token).unwrap();
+}
+
+/// The MCP server with its default profile talking to the stand-in
+fn mcp_server(stand_in: &StravaStandIn, base_dir: &Path) -> StravaMcpServer {
+    let cache_dir = config::layer(&[("cache_dir", Some(base_dir.display().to_string()))]);
+    let config = Config::load(base_dir.join("config.toml"), false, toml::Table::new(), cache_dir).unwrap();
+    let profile = Profile::load_with_oauth(&config, DEFAULT_PROFILE, None, stand_in.config()).unwrap();
+    StravaMcpServer::new(config, profile, None, RetirementThresholds::default())
+}
+
+/// A client connected to `mcp_server`
+async fn server(stand_in: &StravaStandIn, base_dir: &Path) -> RunningService<RoleClient, ()> {
+    connect(mcp_server(stand_in, base_dir)).await
+}
+
+fn february_runs() -> Vec<Value> {
+    vec![
+        summary_activity(1, "Easy Run", "Run", "2026-02-09T07:00:00Z", 8000.0, 2880),
+        summary_activity(2, "Tempo Run", "Run", "2026-02-11T07:00:00Z", 3000.0, 900),
+        summary_activity(3, "Commute Ride", "Ride", "2026-02-11T17:30:00Z", 12000.0, 1800),
+        summary_activity(4, "Long Run", "Run", "2026-02-15T08:00:00Z", 18000.0, 6300),
+    ]
+}
+
+#[tokio::test]
+async fn test_refreshes_expired_token() {
+    let stand_in = StravaStandIn::start().await;
+    stand_in.refresh("old-refresh", "fresh-access").await;
+    stand_in.activities("fresh-access", february_runs()).await;
+
+    let base_dir = base_dir("refresh");
+    save_token(&base_dir, "old-access", "old-refresh", 1);
+    let client = server(&stand_in, &base_dir).await;
+
+    let output = call_tool(&client, "get_runs_for_date", json!({ "date": "2026-02-11" })).await.unwrap();
+    assert!(output.starts_with("# Runs for 2026-02-11\n\n## Tempo Run\n"));
+    assert!(!output.contains("Commute Ride"));
+
+    // The refreshed token is saved, and reused without refreshing again
+    let saved = TokenFile::new(ProfilePaths::new(&base_dir, DEFAULT_PROFILE).token, None).load().unwrap();
+    assert_eq!(saved.token.access_token, "fresh-access");
+    assert_eq!(saved.token.refresh_token, "old-refresh-next");
+
+    let output = call_tool(&client, "get_weekly_summary", json!({ "week_start": "2026-02-09" })).await.unwrap();
+    assert!(output.contains("- **Total Runs:** 3\n"));
+    assert!(output.contains("- **Total Distance:** 29.00 km\n"));
+    assert_eq!(stand_in.requests("/oauth/token").await, 1);
+    assert_eq!(stand_in.requests("/api/v3/athlete/activities").await, 2);
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_rejected_refresh_token() {
+    let stand_in = StravaStandIn::start().await;
+    stand_in.refresh("other-refresh", "fresh-access").await;
+    stand_in.activities("fresh-access", february_runs()).await;
+
+    let base_dir = base_dir("rejected-refresh");
+    save_token(&base_dir, "old-access", "revoked-refresh", 1);
+    let client = server(&stand_in, &base_dir).await;
+
+    let error = call_tool(&client, "get_recent_runs", json!({})).await.unwrap_err();
+    assert!(error.contains("-32001") && error.contains("\"kind\":\"token_expired\""), "{}", error);
+    assert!(error.contains("400") && error.contains("Call `authorize` again"), "{}", error);
+    assert_eq!(stand_in.requests("/api/v3/athlete/activities").await, 0);
+
+    // Imported files don't hide the failure behind a result without Strava's activities
+    let directory = base_dir.join("files");
+    std::fs::create_dir_all(&directory).unwrap();
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/morning_run.gpx");
+    std::fs::copy(fixture, directory.join("morning_run.gpx")).unwrap();
+    call_tool(&client, "import_activity_files", json!({ "directory": directory })).await.unwrap();
+    let error = call_tool(&client, "get_runs_for_month", json!({ "month": "2026-02" })).await.unwrap_err();
+    assert!(error.contains("\"kind\":\"token_expired\""), "{}", error);
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_requires_authorization() {
+    let stand_in = StravaStandIn::start().await;
+    let base_dir = base_dir("unauthorized");
+    let client = server(&stand_in, &base_dir).await;
+
+    let error = call_tool(&client, "get_athlete_profile", json!({})).await.unwrap_err();
+    assert!(error.contains("-32001") && error.contains("\"kind\":\"not_authorized\""), "{}", error);
+    assert!(error.contains("Profile 'default' is not authorized with Strava. Call `authorize`"), "{}", error);
+    assert_eq!(stand_in.requests("/oauth/token").await, 0);
+
+    client.cancel().await.unwrap();
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+#[tokio::test]
+async fn test_fetches_every_page() {
+    let stand_in = StravaStandIn::start().await;
+    let start = DateTime::parse_from_rfc3339("2026-03-01T06:00:00Z").unwrap();
+    let runs = (0..250)
+        .map(|i| {
+            let date = (start + chrono::Duration::hours(2 * i)).to_rfc3339();
+            summary_activity(1000 + i, "Run", "Run", &date, 5000.0, 1500)
+        })
+        .collect();
+    stand_in.activities("access", runs).await;
+
+    let base_dir = base_dir("pages");
+    save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+    let client = server(&stand_in, &base_dir).await;
+
+    let output = call_tool(&client, "get_runs_for_month", json!({ "month": "2026-03" })).await.unwrap();
+    assert!(output.starts_with("# Runs for 2026-03\n\nFound 250 runs\n"));
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_recent_runs_stop_paging() {
+    let stand_in = StravaStandIn::start().await;
+    let start = DateTime::parse_from_rfc3339("2026-01-01T06:00:00Z").unwrap();
+    let activities = (0..450)
+        .map(|i| {
+            let date = (start + chrono::Duration::hours(6 * i)).to_rfc3339();
+            // Mostly rides, with a run every tenth activity
+            let sport_type = if i % 10 == 0 { "Run" } else { "Ride" };
+            summary_activity(1000 + i, sport_type, sport_type, &date, 5000.0, 1500)
+        })
+        .collect();
+    stand_in.activities("access", activities).await;
+
+    let base_dir = base_dir("recent");
+    save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+    let client = server(&stand_in, &base_dir).await;
+
+    // The newest page holds 20 runs, so five don't need the rest of the history
+    let output = call_tool(&client, "get_recent_runs", json!({ "limit": 5 })).await.unwrap();
+    assert!(output.contains("## Run (2026-04-21)\n"), "{}", output);
+    assert_eq!(output.matches("## Run").count(), 5);
+    assert_eq!(stand_in.requests("/api/v3/athlete/activities").await, 1);
+
+    // Asking for more than the first page has keeps going, newest first
+    let output = call_tool(&client, "get_recent_runs", json!({ "limit": 30 })).await.unwrap();
+    assert_eq!(output.matches("## Run").count(), 30);
+    assert_eq!(stand_in.requests("/api/v3/athlete/activities").await, 3);
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_webhook_events_update_local_store() {
+    let stand_in = StravaStandIn::start().await;
+    stand_in.athlete(134815).await;
+    let run = detailed_activity(1360128428, "Lunch Run", "Run", "2026-02-17T12:00:00Z", 6000.0, 1800);
+    stand_in.activity_details(vec![run]).await;
+
+    let base_dir = base_dir("webhook");
+    save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+    let server = mcp_server(&stand_in, &base_dir);
+    let (sender, events) = tokio::sync::mpsc::channel(webhook::QUEUE_LEN);
+    tokio::spawn(server.clone().apply_webhook_events(events));
+    let client = connect(server).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}{}", listener.local_addr().unwrap(), webhook::PATH);
+    tokio::spawn(webhook::serve(listener, "s3cret".to_string(), sender));
+
+    let uri = "strava://profiles/default/activities".to_string();
+    client.subscribe(SubscribeRequestParams { meta: None, uri: uri.clone() }).await.unwrap();
+    let read = || async {
+        let result = client
+            .read_resource(ReadResourceRequestParams { meta: None, uri: uri.clone() })
+            .await
+            .unwrap();
+        match &result.contents[0] {
+            ResourceContents::TextResourceContents { text, .. } => text.clone(),
+            other => panic!("unexpected contents: {:?}", other),
+        }
+    };
+    let wait_for = |expected: bool| async move {
+        for _ in 0..100 {
+            if read().await.contains("Lunch Run") == expected {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        panic!("local store did not update");
+    };
+    let post = |aspect_type: &str| {
+        let payload = json!({
+            "aspect_type": aspect_type,
+            "event_time": 1771330000,
+            "object_id": 1360128428,
+            "object_type": "activity",
+            "owner_id": 134815,
+            "subscription_id": 120475,
+            "updates": {},
+        });
+        let request = reqwest::Client::new().post(&url).json(&payload).send();
+        async move { assert_eq!(request.await.unwrap().status(), 200) }
+    };
+
+    post("create").await;
+    wait_for(true).await;
+
+    stand_in.delete_activity(1360128428).await;
+    post("delete").await;
+    wait_for(false).await;
+
+    // The owner's profile is looked up once
+    assert_eq!(stand_in.requests("/api/v3/athlete").await, 1);
+    assert_eq!(stand_in.requests("/api/v3/activities/1360128428").await, 2);
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_webhook_subscription_tools() {
+    let stand_in = StravaStandIn::start().await;
+    stand_in.push_subscription(120475, "https://example.com/webhook").await;
+
+    let base_dir = base_dir("subscriptions");
+    let client = server(&stand_in, &base_dir).await;
+
+    let args = json!({ "callback_url": "https://example.com/webhook", "verify_token": "s3cret" });
+    let output = call_tool(&client, "create_webhook_subscription", args).await.unwrap();
+    assert!(output.contains("- **Subscription ID:** 120475\n"), "{}", output);
+
+    let output = call_tool(&client, "list_webhook_subscriptions", json!({})).await.unwrap();
+    assert!(output.starts_with("# 1 Webhook Subscriptions\n\n## 120475\n- **Callback URL:** https://example.com/webhook\n"));
+
+    let output = call_tool(&client, "delete_webhook_subscription", json!({ "subscription_id": 120475 })).await.unwrap();
+    assert_eq!(output, "Deleted webhook subscription 120475.");
+    let error = call_tool(&client, "delete_webhook_subscription", json!({ "subscription_id": 1 })).await.unwrap_err();
+    assert!(error.contains("\"kind\":\"not_found\""), "{}", error);
+
+    // Without a configured verify token one must be given
+    let error = call_tool(&client, "create_webhook_subscription", json!({ "callback_url": "https://example.com/webhook" }))
+        .await
+        .unwrap_err();
+    assert!(error.contains("verify_token"), "{}", error);
+
+    client.cancel().await.unwrap();
+    let _ = std::fs::remove_dir_all(&base_dir);
+}
+
+#[tokio::test]
+async fn test_reports_progress() {
+    let stand_in = StravaStandIn::start().await;
+    let start = DateTime::parse_from_rfc3339("2026-03-01T06:00:00Z").unwrap();
+    let runs = (0..450)
+        .map(|i| {
+            let date = (start + chrono::Duration::minutes(90 * i)).to_rfc3339();
+            summary_activity(1000 + i, "Run", "Run", &date, 5000.0, 1500)
+        })
+        .collect();
+    stand_in.activities("access", runs).await;
+
+    let base_dir = base_dir("progress");
+    save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+    let recorder = ProgressRecorder::default();
+    let client = connect_as(mcp_server(&stand_in, &base_dir), recorder.clone()).await;
+
+    let output = call_tool(&client, "get_runs_for_month", json!({ "month": "2026-03" })).await.unwrap();
+    assert!(output.starts_with("# Runs for 2026-03\n\nFound 450 runs\n"));
+
+    // Notifications may be handled just after the response
+    for _ in 0..50 {
+        if recorder.notifications.lock().unwrap().len() == 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let notifications = recorder.notifications.lock().unwrap().clone();
+    let messages: Vec<_> = notifications.iter().filter_map(|n| n.message.as_deref()).collect();
+    assert_eq!(
+        messages,
+        ["Fetched page 1 (200 activities)", "Fetched page 2 (400 activities)", "Fetched page 3 (450 activities)"]
+    );
+    let progress: Vec<_> = notifications.iter().map(|n| n.progress).collect();
+    assert_eq!(progress, [1.0, 2.0, 3.0]);
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_honors_cancellation() {
+    let stand_in = StravaStandIn::start().await;
+    stand_in.slow_activities(Duration::from_millis(300)).await;
+
+    let base_dir = base_dir("cancel");
+    save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+    let client = server(&stand_in, &base_dir).await;
+
+    // Tasks alive with the client and server idle; the stand-in runs on its own thread
+    call_tool(&client, "list_profiles", json!({})).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let idle_tasks = tokio::runtime::Handle::current().metrics().num_alive_tasks();
+
+    let params = serde_json::from_value(json!({ "name": "get_runs_for_month", "arguments": { "month": "2026-03" } })).unwrap();
+    let request = ClientRequest::CallToolRequest(CallToolRequest::new(params));
+    let handle = client.send_cancellable_request(request, PeerRequestOptions::no_options()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    handle.cancel(Some("user cancelled".to_string())).await.unwrap();
+
+    // Without the cancellation the full first page would be followed by a second request
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(stand_in.requests("/api/v3/athlete/activities").await, 1);
+
+    // The cancelled call's handler has finished rather than being left waiting
+    assert_eq!(alive_tasks(idle_tasks).await, idle_tasks);
+
+    // The server keeps serving other calls
+    let output = call_tool(&client, "list_profiles", json!({})).await.unwrap();
+    assert!(output.starts_with("# 1 Profiles\n"));
+
+    client.cancel().await.unwrap();
+    std::fs::remove_dir_all(&base_dir).unwrap();
+}
+
+/// Tasks alive on this runtime, after waiting up to a second for them to drop to `at_most`
+async fn alive_tasks(at_most: usize) -> usize {
+    let metrics = tokio::runtime::Handle::current().metrics();
+    for _ in 0..50 {
+        if metrics.num_alive_tasks() <= at_most {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    metrics.num_alive_tasks()
+}
+
+#[tokio::test]
+async fn test_reports_api_errors() {
+    let expected = [
+        (401, "-32001", "token_expired"),
+        (429, "-32029", "rate_limited"),
+        (500, "-32050", "unavailable"),
+    ];
+    for (status, code, kind) in expected {
+        let stand_in = StravaStandIn::start().await;
+        stand_in.activities("access", february_runs()).await;
+        stand_in.fail(status).await;
+
+        let base_dir = base_dir(&format!("error-{}", status));
+        save_token(&base_dir, "access", "refresh", chrono::Utc::now().timestamp() + 3600);
+        let client = server(&stand_in, &base_dir).await;
+
+        let error = call_tool(&client, "get_recent_runs", json!({ "limit": 3 })).await.unwrap_err();
+        assert!(error.contains(code), "{}", error);
+        assert!(error.contains(&format!("\"kind\":\"{}\"", kind)), "{}", error);
+
+        client.cancel().await.unwrap();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+}